@@ -0,0 +1,764 @@
+use wasm_bindgen::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use crate::legacy::fix_flattened_region;
+use crate::litematic::{bits_for_palette, unpack_tight};
+use crate::schematic::read_double_triple;
+use crate::{
+    checked_volume, decompress_with_limit, legacy_block_state, read_root_with_limits, upgrade_block_state, BlockEntity,
+    BlockState, ConversionOutput, ConversionWarning, CropBox, Entity, ExportFormat, IngestLimits, NbtCompound,
+    NbtCompression, NbtError, NeptuniumError, Region, Schematic, SchematicError, SchematicFormat, Vec3i,
+    FLATTENED_DATA_VERSION,
+};
+
+// Anvil 存档区域文件（r.X.Z.mca）读取：区块位置表 → 单个区块的压缩数据 → 按区段存储的方块，
+// 再把世界坐标中的任意长方体提取为通用投影模型
+
+const SECTOR_BYTES: usize = 4096;
+/// 位置表与时间戳表各占一个扇区
+const HEADER_BYTES: usize = 2 * SECTOR_BYTES;
+/// 每个区域文件包含 32×32 个区块
+pub const REGION_CHUNKS: i32 = 32;
+const SECTION_VOLUME: usize = 4096;
+/// 方块状态数组不再跨越 long 边界（1.16，20w17a）
+const PADDED_STATES_DATA_VERSION: i32 = 2527;
+/// 压缩类型的最高位表示区块数据存放在单独的 c.X.Z.mcc 文件中
+const EXTERNAL_CHUNK_FLAG: u8 = 0x80;
+const LZ4_BLOCK_MAGIC: &[u8] = b"LZ4Block";
+const LZ4_BLOCK_HEADER: usize = 21;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChunkCompression {
+    Gzip,
+    Zlib,
+    Uncompressed,
+    Lz4,
+}
+
+impl ChunkCompression {
+    fn from_id(id: u8) -> Option<ChunkCompression> {
+        match id {
+            1 => Some(ChunkCompression::Gzip),
+            2 => Some(ChunkCompression::Zlib),
+            3 => Some(ChunkCompression::Uncompressed),
+            4 => Some(ChunkCompression::Lz4),
+            _ => None,
+        }
+    }
+}
+
+/// 解析文件名中的区域坐标，如 `r.-1.2.mca`
+pub fn parse_region_file_name(filename: &str) -> Option<(i32, i32)> {
+    let name = filename.rsplit(['/', '\\']).next()?;
+    let mut parts = name.strip_prefix("r.")?.strip_suffix(".mca")?.split('.');
+    let x = parts.next()?.parse().ok()?;
+    let z = parts.next()?.parse().ok()?;
+    parts.next().is_none().then_some((x, z))
+}
+
+/// 区块 NBT 中记录的区块坐标；方块区块使用 xPos / zPos，1.17 起的实体区块使用 Position
+fn chunk_position(chunk: &NbtCompound) -> Option<(i32, i32)> {
+    let level = chunk.get_compound("Level").unwrap_or(chunk);
+    match (level.get_i32("xPos"), level.get_i32("zPos")) {
+        (Some(x), Some(z)) => Some((x, z)),
+        _ => match chunk.get_int_array("Position") {
+            Some([x, z]) => Some((*x, *z)),
+            _ => None,
+        },
+    }
+}
+
+pub struct AnvilRegion<'a> {
+    data: &'a [u8],
+    /// 区域坐标，即区块坐标整除 32
+    pub x: i32,
+    pub z: i32,
+}
+
+impl<'a> AnvilRegion<'a> {
+    /// coords 为空时由第一个已生成区块记录的坐标推断区域坐标
+    pub fn parse(
+        data: &'a [u8],
+        coords: Option<(i32, i32)>,
+        limits: &IngestLimits,
+    ) -> Result<AnvilRegion<'a>, SchematicError> {
+        if data.len() < HEADER_BYTES {
+            return Err(SchematicError::Invalid("Region file is shorter than its 8 KiB header".to_string()));
+        }
+        let mut region = AnvilRegion { data, x: 0, z: 0 };
+        let (x, z) = match coords {
+            Some(coords) => coords,
+            None => {
+                let mut inferred = None;
+                for (local_x, local_z) in region.chunks() {
+                    if let Some((cx, cz)) =
+                        region.read_chunk(local_x, local_z, limits)?.as_ref().and_then(chunk_position)
+                    {
+                        inferred = Some((cx.div_euclid(REGION_CHUNKS), cz.div_euclid(REGION_CHUNKS)));
+                        break;
+                    }
+                }
+                inferred.ok_or_else(|| {
+                    SchematicError::Invalid(
+                        "Cannot determine region coordinates; name the file r.<x>.<z>.mca".to_string(),
+                    )
+                })?
+            }
+        };
+        region.x = x;
+        region.z = z;
+        Ok(region)
+    }
+
+    /// 位置表中的 (字节偏移, 分配的字节数)；区块未生成时返回 None
+    fn location(&self, local_x: i32, local_z: i32) -> Option<(usize, usize)> {
+        let entry = 4 * (local_x + local_z * REGION_CHUNKS) as usize;
+        let raw =
+            u32::from_be_bytes([self.data[entry], self.data[entry + 1], self.data[entry + 2], self.data[entry + 3]]);
+        let (offset, sectors) = ((raw >> 8) as usize, (raw & 0xff) as usize);
+        (offset >= 2 && sectors > 0).then_some((offset * SECTOR_BYTES, sectors * SECTOR_BYTES))
+    }
+
+    /// 已生成区块的区域内坐标
+    pub fn chunks(&self) -> Vec<(i32, i32)> {
+        (0..REGION_CHUNKS)
+            .flat_map(|z| (0..REGION_CHUNKS).map(move |x| (x, z)))
+            .filter(|(x, z)| self.location(*x, *z).is_some())
+            .collect()
+    }
+
+    /// 世界区块坐标是否位于本区域文件内
+    pub fn contains_chunk(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        chunk_x.div_euclid(REGION_CHUNKS) == self.x && chunk_z.div_euclid(REGION_CHUNKS) == self.z
+    }
+
+    /// 读取并解压区域内坐标处的区块；区块未生成时返回 None
+    pub fn read_chunk(
+        &self,
+        local_x: i32,
+        local_z: i32,
+        limits: &IngestLimits,
+    ) -> Result<Option<NbtCompound>, SchematicError> {
+        let Some((start, capacity)) = self.location(local_x, local_z) else {
+            return Ok(None);
+        };
+        let invalid = |message: &str| {
+            SchematicError::Invalid(format!(
+                "Chunk [{}, {}] {}",
+                self.x * REGION_CHUNKS + local_x,
+                self.z * REGION_CHUNKS + local_z,
+                message
+            ))
+        };
+        let header =
+            self.data.get(start..start + 5).ok_or_else(|| invalid("points past the end of the region file"))?;
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let kind = header[4];
+        if kind & EXTERNAL_CHUNK_FLAG != 0 {
+            return Err(invalid("is stored in a separate .mcc file, which is not supported"));
+        }
+        if length == 0 || length + 4 > capacity {
+            return Err(invalid("has an invalid length"));
+        }
+        let body = self.data.get(start + 5..start + 4 + length).ok_or_else(|| invalid("is truncated"))?;
+        let limit = limits.max_decompressed_bytes;
+        let raw = match ChunkCompression::from_id(kind)
+            .ok_or_else(|| invalid(&format!("uses unknown compression {}", kind)))?
+        {
+            ChunkCompression::Gzip => decompress_with_limit(body, NbtCompression::Gzip, limit)?,
+            ChunkCompression::Zlib => decompress_with_limit(body, NbtCompression::Zlib, limit)?,
+            ChunkCompression::Uncompressed => decompress_with_limit(body, NbtCompression::None, limit)?,
+            ChunkCompression::Lz4 => decompress_lz4_stream(body, limit)?,
+        };
+        let (_, root) = read_root_with_limits(&raw, limits)?;
+        Ok(Some(root))
+    }
+}
+
+/// lz4-java 的 LZ4BlockOutputStream 格式：若干带 21 字节头（魔数、方式、压缩/原始长度、校验和）的块，
+/// 原始长度为 0 的块表示流结束；不校验 xxHash 校验和
+fn decompress_lz4_stream(data: &[u8], limit: usize) -> Result<Vec<u8>, NbtError> {
+    let corrupt = |message: &str| NbtError::Decompression(format!("LZ4: {}", message));
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let header = data.get(pos..pos + LZ4_BLOCK_HEADER).ok_or_else(|| corrupt("truncated block header"))?;
+        if &header[..8] != LZ4_BLOCK_MAGIC {
+            return Err(corrupt("missing block magic"));
+        }
+        let method = header[8] & 0xf0;
+        let compressed = u32::from_le_bytes([header[9], header[10], header[11], header[12]]) as usize;
+        let original = u32::from_le_bytes([header[13], header[14], header[15], header[16]]) as usize;
+        pos += LZ4_BLOCK_HEADER;
+        if original == 0 {
+            break;
+        }
+        if out.len().saturating_add(original) > limit {
+            return Err(NbtError::DecompressedTooLarge(limit));
+        }
+        let body = data.get(pos..pos.saturating_add(compressed)).ok_or_else(|| corrupt("truncated block"))?;
+        match method {
+            0x10 if compressed == original => out.extend_from_slice(body),
+            0x20 => decompress_lz4_block(body, original, &mut out).map_err(|_| corrupt("malformed block"))?,
+            _ => return Err(corrupt("unsupported block method")),
+        }
+        pos += compressed;
+    }
+    Ok(out)
+}
+
+/// 解压单个 LZ4 块，输出恰好 expected 字节
+fn decompress_lz4_block(input: &[u8], expected: usize, out: &mut Vec<u8>) -> Result<(), ()> {
+    let start = out.len();
+    let mut pos = 0;
+    let read_length = |pos: &mut usize, mut length: usize| -> Result<usize, ()> {
+        if length == 15 {
+            loop {
+                let byte = *input.get(*pos).ok_or(())?;
+                *pos += 1;
+                length += usize::from(byte);
+                if byte != 255 {
+                    break;
+                }
+            }
+        }
+        Ok(length)
+    };
+    loop {
+        let token = *input.get(pos).ok_or(())?;
+        pos += 1;
+        let literals = read_length(&mut pos, usize::from(token >> 4))?;
+        let bytes = input.get(pos..pos.saturating_add(literals)).ok_or(())?;
+        if out.len() - start + literals > expected {
+            return Err(());
+        }
+        out.extend_from_slice(bytes);
+        pos += literals;
+        // 最后一个序列只有字面量
+        if pos == input.len() {
+            break;
+        }
+        let offset = usize::from(u16::from_le_bytes([*input.get(pos).ok_or(())?, *input.get(pos + 1).ok_or(())?]));
+        pos += 2;
+        let length = read_length(&mut pos, usize::from(token & 0x0f))? + 4;
+        let written = out.len() - start;
+        if offset == 0 || offset > written || written + length > expected {
+            return Err(());
+        }
+        // 匹配区可能与输出重叠，逐字节复制
+        for _ in 0..length {
+            out.push(out[out.len() - offset]);
+        }
+    }
+    if out.len() - start != expected {
+        return Err(());
+    }
+    Ok(())
+}
+
+/// 16×16×16 的区段；indices 为空表示整段都是调色板第一项
+struct Section {
+    palette: Vec<BlockState>,
+    indices: Vec<u32>,
+}
+
+impl Section {
+    fn get(&self, index: usize) -> u32 {
+        self.indices.get(index).copied().unwrap_or(0)
+    }
+}
+
+struct ChunkBlocks {
+    /// 方块状态所对应的版本；1.13 前的区块在扁平化后视为 `FLATTENED_DATA_VERSION`
+    data_version: i32,
+    legacy: bool,
+    sections: HashMap<i32, Section>,
+    block_entities: Vec<NbtCompound>,
+    entities: Vec<NbtCompound>,
+}
+
+/// 解析区块中的区段：1.18 起为 sections[].block_states，1.13–1.17 为 Level.Sections[].Palette / BlockStates，
+/// 更早的版本为数字 id 的 Blocks / Data / Add 数组
+fn read_chunk_blocks(chunk: &NbtCompound) -> Result<ChunkBlocks, String> {
+    let data_version = chunk.get_i32("DataVersion").unwrap_or(0);
+    let legacy = data_version < FLATTENED_DATA_VERSION;
+    let level = chunk.get_compound("Level").unwrap_or(chunk);
+    let mut sections = HashMap::new();
+    let list = level.get_list("sections").or_else(|| level.get_list("Sections"));
+    for section in list.into_iter().flat_map(|list| list.compounds()) {
+        let Some(y) = section.get_i32("Y") else { continue };
+        let parsed = if legacy { read_legacy_section(section)? } else { read_paletted_section(section, data_version)? };
+        if let Some(parsed) = parsed {
+            sections.insert(y, parsed);
+        }
+    }
+    let compounds = |keys: &[&str]| -> Vec<NbtCompound> {
+        keys.iter()
+            .find_map(|key| level.get_list(key))
+            .map(|list| list.compounds().cloned().collect())
+            .unwrap_or_default()
+    };
+    Ok(ChunkBlocks {
+        data_version: data_version.max(FLATTENED_DATA_VERSION),
+        legacy,
+        sections,
+        block_entities: compounds(&["block_entities", "TileEntities"]),
+        entities: compounds(&["Entities"]),
+    })
+}
+
+fn read_paletted_section(section: &NbtCompound, data_version: i32) -> Result<Option<Section>, String> {
+    let (palette, data) = match section.get_compound("block_states") {
+        Some(states) => (states.get_list("palette"), states.get_long_array("data")),
+        None => (section.get_list("Palette"), section.get_long_array("BlockStates")),
+    };
+    // 只有光照数据的区段没有调色板
+    let Some(palette) = palette else { return Ok(None) };
+    let palette: Vec<BlockState> = palette
+        .compounds()
+        .map(BlockState::from_nbt)
+        .collect::<Option<_>>()
+        .ok_or_else(|| "has a malformed palette entry".to_string())?;
+    if palette.is_empty() {
+        return Ok(None);
+    }
+    let indices = match data {
+        Some(data) if palette.len() > 1 => {
+            let bits = bits_for_palette(palette.len()).max(4);
+            let indices = if data_version >= PADDED_STATES_DATA_VERSION {
+                unpack_padded(data, bits)
+            } else if data.len() * 64 == SECTION_VOLUME * bits as usize {
+                Some(unpack_tight(data, bits, SECTION_VOLUME))
+            } else {
+                None
+            }
+            .ok_or_else(|| format!("has {} block state longs for a palette of {}", data.len(), palette.len()))?;
+            if indices.iter().any(|i| *i as usize >= palette.len()) {
+                return Err("references an unknown palette entry".to_string());
+            }
+            indices
+        }
+        _ => Vec::new(),
+    };
+    Ok(Some(Section { palette, indices }))
+}
+
+/// 1.16 起每个 long 只存放整数个值，不跨越边界
+fn unpack_padded(longs: &[i64], bits: u32) -> Option<Vec<u32>> {
+    let per_long = (64 / bits) as usize;
+    if longs.len() != SECTION_VOLUME.div_ceil(per_long) {
+        return None;
+    }
+    let mask = (1u64 << bits) - 1;
+    Some(
+        (0..SECTION_VOLUME)
+            .map(|i| (((longs[i / per_long] as u64) >> ((i % per_long) as u32 * bits)) & mask) as u32)
+            .collect(),
+    )
+}
+
+fn read_legacy_section(section: &NbtCompound) -> Result<Option<Section>, String> {
+    let Some(blocks) = section.get_byte_array("Blocks") else { return Ok(None) };
+    let data = section.get_byte_array("Data").unwrap_or_default();
+    let add = section.get_byte_array("Add").unwrap_or_default();
+    if blocks.len() < SECTION_VOLUME {
+        return Err("has a truncated Blocks array".to_string());
+    }
+    // 区块中的半字节数组低 4 位在前，与 MCEdit 的 AddBlocks 相反
+    let nibble = |array: &[i8], i: usize| array.get(i >> 1).map_or(0, |b| ((*b as u8) >> ((i & 1) * 4)) & 0x0f);
+    let mut palette = Vec::new();
+    let mut lookup: HashMap<(u16, u8), u32> = HashMap::new();
+    let indices = (0..SECTION_VOLUME)
+        .map(|i| {
+            let id = u16::from(blocks[i] as u8) | u16::from(nibble(add, i)) << 8;
+            let meta = nibble(data, i);
+            *lookup.entry((id, meta)).or_insert_with(|| {
+                palette.push(legacy_block_state(id, meta));
+                (palette.len() - 1) as u32
+            })
+        })
+        .collect();
+    Ok(Some(Section { palette, indices }))
+}
+
+/// 从区域文件中提取世界坐标长方体 bounds 内的方块、方块实体与实体；
+/// 1.17 起实体存放在 entities/ 目录下的同名区域文件中，可通过 entities 一并传入。
+/// 区块的 DataVersion 可能各不相同，方块状态统一升级到其中最新的版本
+pub fn extract_anvil_region(
+    blocks: &AnvilRegion,
+    entities: Option<&AnvilRegion>,
+    bounds: &CropBox,
+    limits: &IngestLimits,
+    warnings: &mut Vec<ConversionWarning>,
+) -> Result<Schematic, SchematicError> {
+    let volume = checked_volume(bounds.size)? as i64;
+    if volume > limits.max_region_volume {
+        return Err(SchematicError::RegionTooLarge {
+            region: "Main".to_string(),
+            volume,
+            limit: limits.max_region_volume,
+        });
+    }
+    let min = bounds.min;
+    let max = Vec3i::new(min.x + bounds.size.x, min.y + bounds.size.y, min.z + bounds.size.z);
+    let chunk_range = |from: i32, to: i32| from.div_euclid(16)..=(to - 1).div_euclid(16);
+
+    let mut chunks = Vec::new();
+    let (mut outside, mut missing) = (0, 0);
+    for chunk_z in chunk_range(min.z, max.z) {
+        for chunk_x in chunk_range(min.x, max.x) {
+            if !blocks.contains_chunk(chunk_x, chunk_z) {
+                outside += 1;
+                continue;
+            }
+            let (local_x, local_z) = (chunk_x.rem_euclid(REGION_CHUNKS), chunk_z.rem_euclid(REGION_CHUNKS));
+            let Some(chunk) = blocks.read_chunk(local_x, local_z, limits)? else {
+                missing += 1;
+                continue;
+            };
+            let mut parsed = read_chunk_blocks(&chunk)
+                .map_err(|e| SchematicError::Invalid(format!("Chunk [{}, {}] {}", chunk_x, chunk_z, e)))?;
+            if let Some(entity_chunk) = entities.map(|r| r.read_chunk(local_x, local_z, limits)).transpose()?.flatten()
+            {
+                if let Some(list) = entity_chunk.get_list("Entities") {
+                    parsed.entities.extend(list.compounds().cloned());
+                }
+            }
+            chunks.push(((chunk_x, chunk_z), parsed));
+        }
+    }
+    if chunks.is_empty() {
+        return Err(SchematicError::Invalid(format!(
+            "Bounding box contains no generated chunks of region r.{}.{}.mca",
+            blocks.x, blocks.z
+        )));
+    }
+    if outside > 0 {
+        warnings.push(ConversionWarning::new(
+            "chunks_outside_region",
+            format!("{} chunks lie outside region r.{}.{}.mca and were filled with air", outside, blocks.x, blocks.z),
+        ));
+    }
+    if missing > 0 {
+        warnings.push(ConversionWarning::new(
+            "chunks_not_generated",
+            format!("{} chunks have not been generated and were filled with air", missing),
+        ));
+    }
+
+    let data_version = chunks.iter().map(|(_, c)| c.data_version).max().unwrap_or(FLATTENED_DATA_VERSION);
+    let mut region = Region::new("Main", Vec3i::default(), bounds.size)?;
+    let mut lookup: HashMap<BlockState, u32> = HashMap::from([(BlockState::air(), 0)]);
+    for ((chunk_x, chunk_z), chunk) in &chunks {
+        let x_range = (chunk_x * 16).max(min.x)..(chunk_x * 16 + 16).min(max.x);
+        let z_range = (chunk_z * 16).max(min.z)..(chunk_z * 16 + 16).min(max.z);
+        for (section_y, section) in &chunk.sections {
+            let y_range = (section_y * 16).max(min.y)..(section_y * 16 + 16).min(max.y);
+            if y_range.is_empty() {
+                continue;
+            }
+            let mapped: Vec<u32> = section
+                .palette
+                .iter()
+                .map(|state| {
+                    let state = upgrade_block_state(state, chunk.data_version, data_version);
+                    *lookup.entry(state).or_insert_with_key(|state| {
+                        region.palette.push(state.clone());
+                        (region.palette.len() - 1) as u32
+                    })
+                })
+                .collect();
+            for y in y_range.clone() {
+                for z in z_range.clone() {
+                    for x in x_range.clone() {
+                        let source = ((y.rem_euclid(16) * 16 + z.rem_euclid(16)) * 16 + x.rem_euclid(16)) as usize;
+                        let target = region.index(x - min.x, y - min.y, z - min.z);
+                        region.blocks[target] = mapped[section.get(source) as usize];
+                    }
+                }
+            }
+        }
+    }
+
+    for (_, chunk) in &chunks {
+        for nbt in &chunk.block_entities {
+            let mut nbt = nbt.clone();
+            let (Some(x), Some(y), Some(z)) = (nbt.remove("x"), nbt.remove("y"), nbt.remove("z")) else { continue };
+            let pos = Vec3i::new(
+                x.as_i64().unwrap_or(0) as i32 - min.x,
+                y.as_i64().unwrap_or(0) as i32 - min.y,
+                z.as_i64().unwrap_or(0) as i32 - min.z,
+            );
+            if !region.contains(pos.x, pos.y, pos.z) {
+                continue;
+            }
+            nbt.remove("keepPacked");
+            let id = nbt.remove("id").and_then(|t| t.as_str().map(str::to_string)).unwrap_or_default();
+            region.block_entities.push(BlockEntity { pos, id, nbt });
+        }
+        for nbt in &chunk.entities {
+            let mut nbt = nbt.clone();
+            let Some(pos) = read_double_triple(nbt.remove("Pos").as_ref()) else { continue };
+            let pos = [pos[0] - f64::from(min.x), pos[1] - f64::from(min.y), pos[2] - f64::from(min.z)];
+            if !region.contains(pos[0].floor() as i32, pos[1].floor() as i32, pos[2].floor() as i32) {
+                continue;
+            }
+            let id = nbt.remove("id").and_then(|t| t.as_str().map(str::to_string)).unwrap_or_default();
+            region.entities.push(Entity { pos, id, nbt });
+        }
+    }
+
+    if chunks.iter().any(|(_, c)| c.legacy) {
+        fix_flattened_region(&mut region);
+    } else {
+        region.compact_palette();
+    }
+
+    let mut schematic =
+        Schematic::new(format!("r.{}.{}", blocks.x, blocks.z), data_version, SchematicFormat::Litematica);
+    schematic.description = format!(
+        "Extracted from r.{}.{}.mca, {} {} {} to {} {} {}",
+        blocks.x,
+        blocks.z,
+        min.x,
+        min.y,
+        min.z,
+        max.x - 1,
+        max.y - 1,
+        max.z - 1
+    );
+    schematic.regions.push(region);
+    Ok(schematic)
+}
+
+/// 区域文件概况，供前端选择提取范围
+#[derive(Debug, Clone, Serialize)]
+pub struct AnvilRegionInfo {
+    pub x: i32,
+    pub z: i32,
+    /// 已生成区块的世界区块坐标
+    pub chunks: Vec<[i32; 2]>,
+    /// 区域覆盖的方块坐标范围（X / Z，含两端）
+    pub min_block: [i32; 2],
+    pub max_block: [i32; 2],
+}
+
+impl AnvilRegionInfo {
+    pub fn from_region(region: &AnvilRegion) -> AnvilRegionInfo {
+        let span = REGION_CHUNKS * 16;
+        AnvilRegionInfo {
+            x: region.x,
+            z: region.z,
+            chunks: region
+                .chunks()
+                .into_iter()
+                .map(|(x, z)| [region.x * REGION_CHUNKS + x, region.z * REGION_CHUNKS + z])
+                .collect(),
+            min_block: [region.x * span, region.z * span],
+            max_block: [region.x * span + span - 1, region.z * span + span - 1],
+        }
+    }
+}
+
+// 导出的函数
+/// 区域文件的坐标与已生成区块列表（JSON）；filename 形如 `r.0.-1.mca`，无法解析时由区块数据推断
+#[wasm_bindgen]
+pub fn get_anvil_region_info(data: &[u8], filename: &str) -> Result<String, NeptuniumError> {
+    let region = AnvilRegion::parse(data, parse_region_file_name(filename), &IngestLimits::current())?;
+    Ok(serde_json::to_string(&AnvilRegionInfo::from_region(&region)).unwrap_or_default())
+}
+
+/// 提取 `{"min":{"x":..,"y":..,"z":..},"size":{..}}` 指定的世界坐标长方体为投影；
+/// entities_data 为 entities/ 目录下的同名区域文件，可为空；target_format 留空时写出 litematica
+#[wasm_bindgen]
+pub fn extract_anvil_region_file(
+    data: &[u8],
+    entities_data: &[u8],
+    filename: &str,
+    box_json: &str,
+    target_format: &str,
+) -> Result<ConversionOutput, NeptuniumError> {
+    let bounds: CropBox = serde_json::from_str(box_json)
+        .map_err(|e| NeptuniumError::new(format!("Invalid bounding box: {}", e), "INVALID_CROP_BOX".to_string()))?;
+    let target = match target_format.trim() {
+        "" => Some(ExportFormat::Litematica),
+        name => ExportFormat::from_name(name),
+    }
+    .ok_or_else(|| {
+        NeptuniumError::new(format!("Unsupported target format: {}", target_format), "UNSUPPORTED_FORMAT".to_string())
+    })?;
+
+    let limits = IngestLimits::current();
+    let blocks = AnvilRegion::parse(data, parse_region_file_name(filename), &limits)?;
+    let entities = match entities_data.is_empty() {
+        true => None,
+        false => Some(AnvilRegion::parse(entities_data, Some((blocks.x, blocks.z)), &limits)?),
+    };
+    let mut warnings = Vec::new();
+    let schematic = extract_anvil_region(&blocks, entities.as_ref(), &bounds, &limits, &mut warnings)?;
+    let (bytes, write_warnings) = crate::write_schematic(&schematic, target)?;
+    warnings.extend(write_warnings);
+    Ok(ConversionOutput::new(bytes, target, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NbtList, NbtTag};
+
+    fn list(values: Vec<NbtTag>) -> NbtTag {
+        NbtTag::List(NbtList::from_values(values).unwrap())
+    }
+
+    /// 把区块写成区域文件：每个区块以 zlib 压缩，从第 2 个扇区起依次存放
+    fn region_file(chunks: &[((i32, i32), NbtCompound)]) -> Vec<u8> {
+        let mut data = vec![0u8; HEADER_BYTES];
+        for ((local_x, local_z), chunk) in chunks {
+            let body = crate::compress(&crate::write_root("", chunk).unwrap(), NbtCompression::Zlib).unwrap();
+            let sector = data.len() / SECTOR_BYTES;
+            let sectors = (body.len() + 5).div_ceil(SECTOR_BYTES);
+            let entry = 4 * (local_x + local_z * REGION_CHUNKS) as usize;
+            data[entry..entry + 4].copy_from_slice(&((sector as u32) << 8 | sectors as u32).to_be_bytes());
+            data.extend_from_slice(&(body.len() as u32 + 1).to_be_bytes());
+            data.push(2);
+            data.extend_from_slice(&body);
+            data.resize((sector + sectors) * SECTOR_BYTES, 0);
+        }
+        data
+    }
+
+    /// 1.18 起的区块：区段 Y=0 以 4 位填充存储，(1, 2, 3) 是石头，(15, 15, 15) 是朝东的熔炉
+    fn modern_chunk(x: i32, z: i32) -> NbtCompound {
+        let palette = [
+            BlockState::air(),
+            BlockState::new("minecraft:stone"),
+            BlockState::new("minecraft:furnace").with_property("facing", "east").with_property("lit", "false"),
+        ];
+        let mut longs = vec![0i64; 256];
+        for (index, value) in [((2 * 16 + 3) * 16 + 1, 1u64), (SECTION_VOLUME - 1, 2)] {
+            longs[index / 16] |= (value << ((index % 16) * 4)) as i64;
+        }
+        let mut states = NbtCompound::new();
+        states.insert("palette", list(palette.iter().map(|s| NbtTag::Compound(s.to_nbt())).collect()));
+        states.insert("data", NbtTag::LongArray(longs));
+        let mut section = NbtCompound::new();
+        section.insert("Y", NbtTag::Byte(0));
+        section.insert("block_states", NbtTag::Compound(states));
+
+        let mut furnace = NbtCompound::new();
+        furnace.insert("id", NbtTag::String("minecraft:furnace".to_string()));
+        furnace.insert("x", NbtTag::Int(x * 16 + 15));
+        furnace.insert("y", NbtTag::Int(15));
+        furnace.insert("z", NbtTag::Int(z * 16 + 15));
+        furnace.insert("keepPacked", NbtTag::Byte(0));
+        furnace.insert("BurnTime", NbtTag::Short(0));
+
+        let mut chunk = NbtCompound::new();
+        chunk.insert("DataVersion", NbtTag::Int(3700));
+        chunk.insert("xPos", NbtTag::Int(x));
+        chunk.insert("zPos", NbtTag::Int(z));
+        chunk.insert("sections", list(vec![NbtTag::Compound(section)]));
+        chunk.insert("block_entities", list(vec![NbtTag::Compound(furnace)]));
+        chunk
+    }
+
+    /// 1.12 的区块：Level.Sections 中的数字 id，(0, 0, 0) 是红色羊毛，(1, 0, 0) 是石头
+    fn legacy_chunk() -> NbtCompound {
+        let mut blocks = vec![0i8; SECTION_VOLUME];
+        let mut data = vec![0i8; SECTION_VOLUME / 2];
+        blocks[0] = 35;
+        data[0] = 14;
+        blocks[1] = 1;
+        let mut section = NbtCompound::new();
+        section.insert("Y", NbtTag::Byte(0));
+        section.insert("Blocks", NbtTag::ByteArray(blocks));
+        section.insert("Data", NbtTag::ByteArray(data));
+        let mut level = NbtCompound::new();
+        level.insert("xPos", NbtTag::Int(0));
+        level.insert("zPos", NbtTag::Int(0));
+        level.insert("Sections", list(vec![NbtTag::Compound(section)]));
+        let mut chunk = NbtCompound::new();
+        chunk.insert("DataVersion", NbtTag::Int(1343));
+        chunk.insert("Level", NbtTag::Compound(level));
+        chunk
+    }
+
+    fn extract(data: &[u8], bounds: CropBox) -> (Schematic, Vec<ConversionWarning>) {
+        let limits = IngestLimits::DEFAULT;
+        let region = AnvilRegion::parse(data, None, &limits).unwrap();
+        let mut warnings = Vec::new();
+        let schematic = extract_anvil_region(&region, None, &bounds, &limits, &mut warnings).unwrap();
+        (schematic, warnings)
+    }
+
+    #[test]
+    fn region_file_names_give_region_coordinates() {
+        assert_eq!(parse_region_file_name("world/region/r.-1.2.mca"), Some((-1, 2)));
+        assert_eq!(parse_region_file_name("C:\\saves\\r.0.0.mca"), Some((0, 0)));
+        assert_eq!(parse_region_file_name("r.1.mca"), None);
+        assert_eq!(parse_region_file_name("r.1.2.3.mca"), None);
+    }
+
+    #[test]
+    fn paletted_sections_are_extracted_with_block_entities() {
+        let data = region_file(&[((0, 0), modern_chunk(0, 0))]);
+        let (schematic, warnings) = extract(&data, CropBox::new(Vec3i::new(0, 0, 0), Vec3i::new(16, 16, 16)));
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(schematic.data_version, 3700);
+        let region = &schematic.regions[0];
+        assert_eq!(region.block(1, 2, 3).name, "minecraft:stone");
+        assert_eq!(region.block(15, 15, 15).property("facing"), Some("east"));
+        assert!(region.block(0, 0, 0).is_air());
+
+        let furnace = &region.block_entities[0];
+        assert_eq!((furnace.pos, furnace.id.as_str()), (Vec3i::new(15, 15, 15), "minecraft:furnace"));
+        assert!(furnace.nbt.contains_key("BurnTime") && !furnace.nbt.contains_key("keepPacked"));
+    }
+
+    #[test]
+    fn region_coordinates_are_inferred_and_boxes_are_world_relative() {
+        // 区块 (-31, 2) 位于区域 r.-1.0
+        let data = region_file(&[((1, 2), modern_chunk(-31, 2))]);
+        let region = AnvilRegion::parse(&data, None, &IngestLimits::DEFAULT).unwrap();
+        assert_eq!((region.x, region.z), (-1, 0));
+        assert_eq!(region.chunks(), [(1, 2)]);
+        assert!(region.contains_chunk(-31, 2) && !region.contains_chunk(1, 2));
+
+        let (schematic, _) = extract(&data, CropBox::new(Vec3i::new(-496, 0, 32), Vec3i::new(4, 4, 4)));
+        assert_eq!(schematic.regions[0].block(1, 2, 3).name, "minecraft:stone");
+    }
+
+    #[test]
+    fn missing_and_foreign_chunks_are_filled_with_air_and_reported() {
+        let data = region_file(&[((0, 0), modern_chunk(0, 0))]);
+        let (schematic, warnings) = extract(&data, CropBox::new(Vec3i::new(-8, 0, 0), Vec3i::new(32, 4, 4)));
+        let codes: Vec<_> = warnings.iter().map(|w| w.code.as_str()).collect();
+        assert_eq!(codes, ["chunks_outside_region", "chunks_not_generated"]);
+        assert_eq!(schematic.regions[0].block(9, 2, 3).name, "minecraft:stone");
+
+        let limits = IngestLimits::DEFAULT;
+        let region = AnvilRegion::parse(&data, None, &limits).unwrap();
+        let bounds = CropBox::new(Vec3i::new(16, 0, 0), Vec3i::new(4, 4, 4));
+        assert!(extract_anvil_region(&region, None, &bounds, &limits, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn legacy_sections_are_flattened() {
+        let data = region_file(&[((0, 0), legacy_chunk())]);
+        let (schematic, _) = extract(&data, CropBox::new(Vec3i::new(0, 0, 0), Vec3i::new(2, 1, 1)));
+        assert_eq!(schematic.data_version, FLATTENED_DATA_VERSION);
+        let region = &schematic.regions[0];
+        assert_eq!(region.block(0, 0, 0).name, "minecraft:red_wool");
+        assert_eq!(region.block(1, 0, 0).name, "minecraft:stone");
+    }
+
+    #[test]
+    fn truncated_files_and_external_chunks_are_rejected() {
+        let limits = IngestLimits::DEFAULT;
+        assert!(AnvilRegion::parse(&[0; 100], Some((0, 0)), &limits).is_err());
+        let mut data = region_file(&[((0, 0), modern_chunk(0, 0))]);
+        data[HEADER_BYTES + 4] |= EXTERNAL_CHUNK_FLAG;
+        let region = AnvilRegion::parse(&data, Some((0, 0)), &limits).unwrap();
+        assert!(region.read_chunk(0, 0, &limits).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
-use crate::{hash_string, verify_hash, ValidationResult};
+use crate::{hash_string, ValidationResult};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[wasm_bindgen]
@@ -187,7 +187,7 @@ pub fn is_verification_code_valid(code: &str) -> ValidationResult {
         return ValidationResult::new(false, "Verification code must be 6 digits".to_string());
     }
     
-    if !code.chars().all(|c| c.is_digit(10)) {
+    if !code.chars().all(|c| c.is_ascii_digit()) {
         return ValidationResult::new(false, "Verification code must contain only digits".to_string());
     }
     
@@ -196,7 +196,7 @@ pub fn is_verification_code_valid(code: &str) -> ValidationResult {
 
 // 权限检查
 #[wasm_bindgen]
-pub fn check_user_permissions(user_id: &str, resource: &str, action: &str) -> bool {
+pub fn check_user_permissions(_user_id: &str, _resource: &str, action: &str) -> bool {
     // 简单的权限检查逻辑
     // 在实际应用中，这里会查询数据库中的用户权限
     
@@ -0,0 +1,731 @@
+use wasm_bindgen::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::OnceLock;
+use crate::{BlockState, NbtCompound, NbtTag, SchematicError};
+
+// Java 版方块状态 ↔ Bedrock 版方块状态：先查表改名，再按通用规则转换其余属性
+
+const BEDROCK_BLOCKS: &str = include_str!("data/bedrock_blocks.txt");
+
+/// 写出的调色板条目所声明的 Bedrock 版本（1.21.40）
+pub const BEDROCK_BLOCK_VERSION: i32 = (1 << 24) | (21 << 16) | (40 << 8);
+
+/// 导入后仍无法对应到 Java 方块的占位命名空间，写回 .mcstructure 时还原
+pub const BEDROCK_PLACEHOLDER_NAMESPACE: &str = "bedrock";
+
+/// Bedrock 方块状态的取值，类型与 NBT 中一致
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BedrockValue {
+    Byte(i8),
+    Int(i32),
+    String(String),
+}
+
+impl BedrockValue {
+    /// 表中的写法：true/false 为字节，整数为整数，其余为字符串
+    pub fn parse(value: &str) -> BedrockValue {
+        match value {
+            "true" => BedrockValue::Byte(1),
+            "false" => BedrockValue::Byte(0),
+            _ => match value.parse::<i32>() {
+                Ok(v) => BedrockValue::Int(v),
+                Err(_) => BedrockValue::String(value.to_string()),
+            },
+        }
+    }
+
+    pub fn from_nbt(tag: &NbtTag) -> Option<BedrockValue> {
+        match tag {
+            NbtTag::Byte(v) => Some(BedrockValue::Byte(*v)),
+            NbtTag::Short(v) => Some(BedrockValue::Int(i32::from(*v))),
+            NbtTag::Int(v) => Some(BedrockValue::Int(*v)),
+            NbtTag::String(v) => Some(BedrockValue::String(v.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn to_nbt(&self) -> NbtTag {
+        match self {
+            BedrockValue::Byte(v) => NbtTag::Byte(*v),
+            BedrockValue::Int(v) => NbtTag::Int(*v),
+            BedrockValue::String(v) => NbtTag::String(v.clone()),
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            BedrockValue::Byte(v) => Some(i64::from(*v)),
+            BedrockValue::Int(v) => Some(i64::from(*v)),
+            BedrockValue::String(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for BedrockValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BedrockValue::Byte(0) => f.write_str("false"),
+            BedrockValue::Byte(1) => f.write_str("true"),
+            BedrockValue::Byte(v) => write!(f, "{}", v),
+            BedrockValue::Int(v) => write!(f, "{}", v),
+            BedrockValue::String(v) => f.write_str(v),
+        }
+    }
+}
+
+/// Bedrock 调色板条目：方块名与带类型的状态
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BedrockBlock {
+    pub name: String,
+    pub states: BTreeMap<String, BedrockValue>,
+}
+
+impl BedrockBlock {
+    pub fn new(name: impl Into<String>) -> BedrockBlock {
+        BedrockBlock { name: crate::normalize_id(&name.into()), states: BTreeMap::new() }
+    }
+
+    pub fn air() -> BedrockBlock {
+        BedrockBlock::new(crate::AIR)
+    }
+
+    pub fn with_state(mut self, key: impl Into<String>, value: BedrockValue) -> BedrockBlock {
+        self.states.insert(key.into(), value);
+        self
+    }
+
+    pub fn state(&self, key: &str) -> Option<&BedrockValue> {
+        self.states.get(key)
+    }
+
+    pub fn path(&self) -> &str {
+        self.name.split_once(':').map(|(_, path)| path).unwrap_or(&self.name)
+    }
+
+    /// 解析 `minecraft:torch[torch_facing_direction=top]` 形式的字符串，取值类型按 `BedrockValue::parse` 推断
+    pub fn parse(input: &str) -> Result<BedrockBlock, SchematicError> {
+        let state = BlockState::parse(input)?;
+        let states = state.properties.iter().map(|(k, v)| (k.clone(), BedrockValue::parse(v))).collect();
+        Ok(BedrockBlock { name: state.name, states })
+    }
+
+    /// 读取 `{name, states, version}` 形式的调色板条目
+    pub fn from_nbt(compound: &NbtCompound) -> Option<BedrockBlock> {
+        let mut block = BedrockBlock::new(compound.get_str("name")?);
+        if let Some(states) = compound.get_compound("states") {
+            for (key, value) in states.iter() {
+                block.states.insert(key.to_string(), BedrockValue::from_nbt(value)?);
+            }
+        }
+        Some(block)
+    }
+
+    pub fn to_nbt(&self) -> NbtCompound {
+        let mut compound = NbtCompound::new();
+        compound.insert("name", NbtTag::String(self.name.clone()));
+        let states = self.states.iter().map(|(k, v)| (k.clone(), v.to_nbt())).collect();
+        compound.insert("states", NbtTag::Compound(states));
+        compound.insert("version", NbtTag::Int(BEDROCK_BLOCK_VERSION));
+        compound
+    }
+
+    /// 含水方块第二层中的水
+    pub fn water() -> BedrockBlock {
+        BedrockBlock::new("minecraft:water").with_state("liquid_depth", BedrockValue::Int(0))
+    }
+}
+
+impl fmt::Display for BedrockBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if !self.states.is_empty() {
+            let states: Vec<String> = self.states.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+            write!(f, "[{}]", states.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+struct BedrockTable {
+    /// Java 方块名 → (匹配条件, Bedrock 方块)，按表顺序；None 表示 Bedrock 没有对应方块
+    forward: HashMap<String, Vec<(BlockState, Option<BedrockBlock>)>>,
+    /// Bedrock 方块名 → (匹配条件, Java 方块状态)，按表顺序；None 表示 Java 没有对应方块
+    reverse: HashMap<String, Vec<(BedrockBlock, Option<BlockState>)>>,
+}
+
+fn table() -> &'static BedrockTable {
+    static TABLE: OnceLock<BedrockTable> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut forward: HashMap<String, Vec<(BlockState, Option<BedrockBlock>)>> = HashMap::new();
+        let mut reverse: HashMap<String, Vec<(BedrockBlock, Option<BlockState>)>> = HashMap::new();
+        for line in BEDROCK_BLOCKS.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let Some((java, bedrock)) = line.split_once(' ') else { continue };
+            let java = match java {
+                "-" => None,
+                java => match BlockState::parse(java) {
+                    Ok(state) => Some(state),
+                    Err(_) => continue,
+                },
+            };
+            let bedrock = match bedrock.trim() {
+                "-" => None,
+                bedrock => match BedrockBlock::parse(bedrock) {
+                    Ok(block) => Some(block),
+                    Err(_) => continue,
+                },
+            };
+            if let Some(java) = &java {
+                forward.entry(java.name.clone()).or_default().push((java.clone(), bedrock.clone()));
+            }
+            if let Some(bedrock) = bedrock {
+                reverse.entry(bedrock.name.clone()).or_default().push((bedrock, java));
+            }
+        }
+        BedrockTable { forward, reverse }
+    })
+}
+
+/// Java 属性取值与 Bedrock 状态取值的对应方式
+#[derive(Clone, Copy)]
+enum ValueMap {
+    /// true/false ↔ 字节 1/0
+    Bit,
+    /// true/false ↔ 字节 0/1
+    InvertedBit,
+    /// Java 整数加上偏移量即为 Bedrock 整数
+    Int(i32),
+    /// 字符串原样保留
+    Same,
+    /// Java 取值 ↔ Bedrock 整数
+    Ints(&'static [(&'static str, i32)]),
+    /// Java 取值 ↔ Bedrock 字节
+    Bits(&'static [(&'static str, i8)]),
+    /// Java 取值 ↔ Bedrock 字符串
+    Strings(&'static [(&'static str, &'static str)]),
+}
+
+impl ValueMap {
+    fn to_bedrock(self, value: &str) -> Option<BedrockValue> {
+        match self {
+            ValueMap::Bit | ValueMap::InvertedBit => {
+                let bit = match value {
+                    "true" => 1,
+                    "false" => 0,
+                    _ => return None,
+                };
+                let inverted = matches!(self, ValueMap::InvertedBit);
+                Some(BedrockValue::Byte(if inverted { 1 - bit } else { bit }))
+            }
+            ValueMap::Int(offset) => value.parse::<i32>().ok().map(|v| BedrockValue::Int(v + offset)),
+            ValueMap::Same => Some(BedrockValue::String(value.to_string())),
+            ValueMap::Ints(pairs) => pairs.iter().find(|(j, _)| *j == value).map(|(_, b)| BedrockValue::Int(*b)),
+            ValueMap::Bits(pairs) => pairs.iter().find(|(j, _)| *j == value).map(|(_, b)| BedrockValue::Byte(*b)),
+            ValueMap::Strings(pairs) => {
+                pairs.iter().find(|(j, _)| *j == value).map(|(_, b)| BedrockValue::String(b.to_string()))
+            }
+        }
+    }
+
+    fn to_java(self, value: &BedrockValue) -> Option<String> {
+        match (self, value) {
+            (ValueMap::Same, BedrockValue::String(v)) => Some(v.clone()),
+            (ValueMap::Strings(pairs), BedrockValue::String(v)) => {
+                pairs.iter().find(|(_, b)| b == v).map(|(j, _)| j.to_string())
+            }
+            (ValueMap::Same | ValueMap::Strings(_), _) => None,
+            (ValueMap::Bit, value) => value.as_i64().map(|v| (v != 0).to_string()),
+            (ValueMap::InvertedBit, value) => value.as_i64().map(|v| (v == 0).to_string()),
+            (ValueMap::Int(offset), value) => value.as_i64().map(|v| (v as i32 - offset).to_string()),
+            (ValueMap::Ints(pairs), value) => {
+                let v = value.as_i64()?;
+                pairs.iter().find(|(_, b)| i64::from(*b) == v).map(|(j, _)| j.to_string())
+            }
+            (ValueMap::Bits(pairs), value) => {
+                let v = value.as_i64()?;
+                pairs.iter().find(|(_, b)| i64::from(*b) == v).map(|(j, _)| j.to_string())
+            }
+        }
+    }
+}
+
+/// 单条属性规则：适用于哪些 Java 方块（按不含命名空间的方块名判断），以及两侧的属性名与取值对应
+struct PropertyRule {
+    applies: fn(&str) -> bool,
+    java: &'static str,
+    bedrock: &'static str,
+    values: ValueMap,
+}
+
+const STAIRS_DIRECTIONS: &[(&str, i32)] = &[("east", 0), ("west", 1), ("south", 2), ("north", 3)];
+const HORIZONTAL_DIRECTIONS: &[(&str, i32)] = &[("south", 0), ("west", 1), ("north", 2), ("east", 3)];
+const FACING_DIRECTIONS: &[(&str, i32)] =
+    &[("down", 0), ("up", 1), ("north", 2), ("south", 3), ("west", 4), ("east", 5)];
+// Bedrock 记录火把朝向的是所贴附的那一面
+const TORCH_FACINGS: &[(&str, &str)] = &[("east", "west"), ("west", "east"), ("south", "north"), ("north", "south")];
+const RAIL_SHAPES: &[(&str, i32)] = &[
+    ("north_south", 0),
+    ("east_west", 1),
+    ("ascending_east", 2),
+    ("ascending_west", 3),
+    ("ascending_north", 4),
+    ("ascending_south", 5),
+    ("south_east", 6),
+    ("south_west", 7),
+    ("north_west", 8),
+    ("north_east", 9),
+];
+const HALVES: &[(&str, i8)] = &[("bottom", 0), ("top", 1)];
+const UPPER_LOWER: &[(&str, i8)] = &[("lower", 0), ("upper", 1)];
+const HINGES: &[(&str, i8)] = &[("left", 0), ("right", 1)];
+const BED_PARTS: &[(&str, i8)] = &[("foot", 0), ("head", 1)];
+
+const TALL_PLANTS: &[&str] = &["sunflower", "lilac", "tall_grass", "large_fern", "rose_bush", "peony", "pitcher_plant"];
+const CROPS: &[&str] = &["wheat", "carrots", "potatoes", "beetroots"];
+const SIX_WAY_BLOCKS: &[&str] = &[
+    "dispenser", "dropper", "observer", "piston", "sticky_piston", "barrel", "hopper", "end_rod", "lightning_rod",
+    "command_block", "chain_command_block", "repeating_command_block",
+];
+const CARDINAL_BLOCKS: &[&str] = &[
+    "furnace", "blast_furnace", "smoker", "carved_pumpkin", "jack_o_lantern", "end_portal_frame", "loom",
+    "stonecutter", "anvil", "chipped_anvil", "damaged_anvil", "lectern", "beehive", "bee_nest", "campfire",
+    "soul_campfire",
+];
+const DIRECTION_BLOCKS: &[&str] = &["repeater", "comparator", "bell", "cocoa", "tripwire_hook"];
+
+fn any(_: &str) -> bool {
+    true
+}
+
+fn stairs(path: &str) -> bool {
+    path.ends_with("_stairs")
+}
+
+fn slab(path: &str) -> bool {
+    path.ends_with("_slab")
+}
+
+fn door(path: &str) -> bool {
+    path.ends_with("_door")
+}
+
+fn trapdoor(path: &str) -> bool {
+    path.ends_with("_trapdoor")
+}
+
+fn button(path: &str) -> bool {
+    path.ends_with("_button")
+}
+
+fn fence_gate(path: &str) -> bool {
+    path.ends_with("_fence_gate")
+}
+
+fn wall_torch(path: &str) -> bool {
+    path.ends_with("wall_torch")
+}
+
+fn rail(path: &str) -> bool {
+    path == "rail" || path.ends_with("_rail")
+}
+
+fn lever(path: &str) -> bool {
+    path == "lever"
+}
+
+fn bed(path: &str) -> bool {
+    path.ends_with("_bed")
+}
+
+fn campfire(path: &str) -> bool {
+    path.ends_with("campfire")
+}
+
+fn liquid(path: &str) -> bool {
+    path == "water" || path == "lava"
+}
+
+fn tall_plant(path: &str) -> bool {
+    TALL_PLANTS.contains(&path)
+}
+
+fn crop(path: &str) -> bool {
+    CROPS.contains(&path)
+}
+
+fn six_way(path: &str) -> bool {
+    SIX_WAY_BLOCKS.contains(&path)
+}
+
+fn cardinal(path: &str) -> bool {
+    CARDINAL_BLOCKS.contains(&path)
+}
+
+fn directional(path: &str) -> bool {
+    fence_gate(path) || bed(path) || DIRECTION_BLOCKS.contains(&path)
+}
+
+// 按顺序取第一条适用的规则
+const PROPERTY_RULES: &[PropertyRule] = &[
+    PropertyRule { applies: stairs, java: "facing", bedrock: "weirdo_direction", values: ValueMap::Ints(STAIRS_DIRECTIONS) },
+    PropertyRule { applies: stairs, java: "half", bedrock: "upside_down_bit", values: ValueMap::Bits(HALVES) },
+    PropertyRule { applies: trapdoor, java: "facing", bedrock: "direction", values: ValueMap::Ints(STAIRS_DIRECTIONS) },
+    PropertyRule { applies: trapdoor, java: "half", bedrock: "upside_down_bit", values: ValueMap::Bits(HALVES) },
+    PropertyRule { applies: door, java: "facing", bedrock: "minecraft:cardinal_direction", values: ValueMap::Same },
+    PropertyRule { applies: door, java: "half", bedrock: "upper_block_bit", values: ValueMap::Bits(UPPER_LOWER) },
+    PropertyRule { applies: door, java: "hinge", bedrock: "door_hinge_bit", values: ValueMap::Bits(HINGES) },
+    PropertyRule { applies: tall_plant, java: "half", bedrock: "upper_block_bit", values: ValueMap::Bits(UPPER_LOWER) },
+    PropertyRule { applies: slab, java: "type", bedrock: "minecraft:vertical_half", values: ValueMap::Same },
+    PropertyRule { applies: wall_torch, java: "facing", bedrock: "torch_facing_direction", values: ValueMap::Strings(TORCH_FACINGS) },
+    PropertyRule { applies: rail, java: "shape", bedrock: "rail_direction", values: ValueMap::Ints(RAIL_SHAPES) },
+    PropertyRule { applies: rail, java: "powered", bedrock: "rail_data_bit", values: ValueMap::Bit },
+    PropertyRule { applies: button, java: "powered", bedrock: "button_pressed_bit", values: ValueMap::Bit },
+    PropertyRule { applies: lever, java: "powered", bedrock: "open_bit", values: ValueMap::Bit },
+    PropertyRule { applies: bed, java: "part", bedrock: "head_piece_bit", values: ValueMap::Bits(BED_PARTS) },
+    PropertyRule { applies: bed, java: "occupied", bedrock: "occupied_bit", values: ValueMap::Bit },
+    PropertyRule { applies: campfire, java: "lit", bedrock: "extinguished", values: ValueMap::InvertedBit },
+    PropertyRule { applies: crop, java: "age", bedrock: "growth", values: ValueMap::Int(0) },
+    PropertyRule { applies: liquid, java: "level", bedrock: "liquid_depth", values: ValueMap::Int(0) },
+    PropertyRule { applies: six_way, java: "facing", bedrock: "facing_direction", values: ValueMap::Ints(FACING_DIRECTIONS) },
+    PropertyRule { applies: cardinal, java: "facing", bedrock: "minecraft:cardinal_direction", values: ValueMap::Same },
+    PropertyRule { applies: directional, java: "facing", bedrock: "direction", values: ValueMap::Ints(HORIZONTAL_DIRECTIONS) },
+    PropertyRule { applies: any, java: "facing", bedrock: "facing_direction", values: ValueMap::Ints(FACING_DIRECTIONS) },
+    PropertyRule { applies: any, java: "axis", bedrock: "pillar_axis", values: ValueMap::Same },
+    PropertyRule { applies: any, java: "open", bedrock: "open_bit", values: ValueMap::Bit },
+    PropertyRule { applies: any, java: "persistent", bedrock: "persistent_bit", values: ValueMap::Bit },
+    PropertyRule { applies: any, java: "triggered", bedrock: "triggered_bit", values: ValueMap::Bit },
+    PropertyRule { applies: any, java: "attached", bedrock: "attached_bit", values: ValueMap::Bit },
+    PropertyRule { applies: any, java: "disarmed", bedrock: "disarmed_bit", values: ValueMap::Bit },
+    PropertyRule { applies: any, java: "conditional", bedrock: "conditional_bit", values: ValueMap::Bit },
+    PropertyRule { applies: any, java: "in_wall", bedrock: "in_wall_bit", values: ValueMap::Bit },
+    PropertyRule { applies: any, java: "hanging", bedrock: "hanging", values: ValueMap::Bit },
+    PropertyRule { applies: any, java: "eye", bedrock: "end_portal_eye_bit", values: ValueMap::Bit },
+    PropertyRule { applies: any, java: "drag", bedrock: "drag_down", values: ValueMap::Bit },
+    PropertyRule { applies: any, java: "unstable", bedrock: "explode_bit", values: ValueMap::Bit },
+    PropertyRule { applies: any, java: "lit", bedrock: "lit", values: ValueMap::Bit },
+    PropertyRule { applies: any, java: "age", bedrock: "age", values: ValueMap::Int(0) },
+    PropertyRule { applies: any, java: "layers", bedrock: "height", values: ValueMap::Int(-1) },
+    PropertyRule { applies: any, java: "power", bedrock: "redstone_signal", values: ValueMap::Int(0) },
+    PropertyRule { applies: any, java: "moisture", bedrock: "moisturized_amount", values: ValueMap::Int(0) },
+    PropertyRule { applies: any, java: "bites", bedrock: "bite_counter", values: ValueMap::Int(0) },
+    PropertyRule { applies: any, java: "delay", bedrock: "repeater_delay", values: ValueMap::Int(-1) },
+    PropertyRule { applies: any, java: "rotation", bedrock: "ground_sign_direction", values: ValueMap::Int(0) },
+    PropertyRule { applies: any, java: "candles", bedrock: "candles", values: ValueMap::Int(-1) },
+    PropertyRule { applies: any, java: "pickles", bedrock: "cluster_count", values: ValueMap::Int(-1) },
+    PropertyRule { applies: any, java: "charges", bedrock: "respawn_anchor_charge", values: ValueMap::Int(0) },
+    PropertyRule { applies: any, java: "honey_level", bedrock: "honey_level", values: ValueMap::Int(0) },
+];
+
+/// 游戏会在放置或方块更新时重新计算的 Java 属性，转换时不视为丢失
+fn is_derived_java_property(path: &str, key: &str) -> bool {
+    match key {
+        "snowy" | "distance" | "extended" | "short" | "instrument" => true,
+        "shape" => stairs(path),
+        "type" => path == "chest" || path == "trapped_chest",
+        "powered" => door(path) || trapdoor(path) || fence_gate(path) || path == "note_block",
+        "north" | "east" | "south" | "west" | "up" => {
+            path.ends_with("_fence") || path.ends_with("_wall") || path.ends_with("_pane")
+                || matches!(path, "iron_bars" | "redstone_wire" | "tripwire" | "chorus_plant" | "fire")
+        }
+        _ => false,
+    }
+}
+
+/// Bedrock 自行维护、Java 没有对应属性的状态
+const DERIVED_BEDROCK_STATES: &[&str] = &[
+    "update_bit",
+    "stability",
+    "stability_check",
+    "wall_post_bit",
+    "wall_connection_type_east",
+    "wall_connection_type_north",
+    "wall_connection_type_south",
+    "wall_connection_type_west",
+];
+
+/// 本身就处于水中的 Java 方块，Bedrock 同样在第二层放置水
+const ALWAYS_WATERLOGGED: &[&str] = &["seagrass", "tall_seagrass", "kelp", "kelp_plant", "bubble_column"];
+
+/// 可含水的 Java 方块；Bedrock 第二层为水时才能还原 waterlogged
+fn is_waterloggable(path: &str) -> bool {
+    const SUFFIXES: &[&str] = &[
+        "_stairs", "_slab", "_fence", "_wall", "_pane", "_trapdoor", "_sign", "_rail", "lantern", "candle", "_coral",
+        "_coral_fan", "_coral_wall_fan", "chest", "campfire", "_grate", "amethyst_bud",
+    ];
+    const BLOCKS: &[&str] = &[
+        "rail", "ladder", "iron_bars", "chain", "conduit", "scaffolding", "lightning_rod", "sea_pickle", "heavy_core",
+        "big_dripleaf", "big_dripleaf_stem", "small_dripleaf", "hanging_roots", "amethyst_cluster", "pointed_dripstone",
+        "glow_lichen", "sculk_vein", "sculk_sensor", "calibrated_sculk_sensor", "sculk_shrieker", "mangrove_propagule",
+        "mangrove_roots", "decorated_pot",
+    ];
+    SUFFIXES.iter().any(|suffix| path.ends_with(suffix)) || BLOCKS.contains(&path)
+}
+
+// Java 方块实体 id ↔ Bedrock 方块实体 id，反向映射取第一条
+const BLOCK_ENTITY_IDS: &[(&str, &str)] = &[
+    ("minecraft:chest", "Chest"),
+    ("minecraft:trapped_chest", "Chest"),
+    ("minecraft:barrel", "Barrel"),
+    ("minecraft:shulker_box", "ShulkerBox"),
+    ("minecraft:furnace", "Furnace"),
+    ("minecraft:blast_furnace", "BlastFurnace"),
+    ("minecraft:smoker", "Smoker"),
+    ("minecraft:hopper", "Hopper"),
+    ("minecraft:dispenser", "Dispenser"),
+    ("minecraft:dropper", "Dropper"),
+    ("minecraft:brewing_stand", "BrewingStand"),
+    ("minecraft:sign", "Sign"),
+    ("minecraft:hanging_sign", "HangingSign"),
+    ("minecraft:banner", "Banner"),
+    ("minecraft:bed", "Bed"),
+    ("minecraft:skull", "Skull"),
+    ("minecraft:flower_pot", "FlowerPot"),
+    ("minecraft:jukebox", "Jukebox"),
+    ("minecraft:lectern", "Lectern"),
+    ("minecraft:mob_spawner", "MobSpawner"),
+    ("minecraft:beacon", "Beacon"),
+    ("minecraft:bell", "Bell"),
+    ("minecraft:campfire", "Campfire"),
+    ("minecraft:beehive", "Beehive"),
+    ("minecraft:conduit", "Conduit"),
+    ("minecraft:comparator", "Comparator"),
+    ("minecraft:daylight_detector", "DaylightDetector"),
+    ("minecraft:enchanting_table", "EnchantTable"),
+    ("minecraft:ender_chest", "EnderChest"),
+    ("minecraft:end_gateway", "EndGateway"),
+    ("minecraft:command_block", "CommandBlock"),
+    ("minecraft:structure_block", "StructureBlock"),
+    ("minecraft:chiseled_bookshelf", "ChiseledBookshelf"),
+    ("minecraft:decorated_pot", "DecoratedPot"),
+    ("minecraft:crafter", "Crafter"),
+];
+
+/// Java 方块实体 id 对应的 Bedrock id
+pub fn bedrock_block_entity_id(java_id: &str) -> Option<&'static str> {
+    let java_id = crate::normalize_id(java_id);
+    BLOCK_ENTITY_IDS.iter().find(|(java, _)| *java == java_id).map(|(_, bedrock)| *bedrock)
+}
+
+/// Bedrock 方块实体 id 对应的 Java id
+pub fn java_block_entity_id(bedrock_id: &str) -> Option<&'static str> {
+    BLOCK_ENTITY_IDS.iter().find(|(_, bedrock)| *bedrock == bedrock_id).map(|(java, _)| *java)
+}
+
+/// Java → Bedrock 的映射结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct BedrockMapping {
+    pub block: BedrockBlock,
+    /// 含水方块在 Bedrock 中以第二层的水表示
+    pub waterlogged: bool,
+    /// 没有 Bedrock 对应状态而被丢弃的 Java 属性
+    pub dropped: Vec<String>,
+}
+
+/// Bedrock → Java 的映射结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct JavaMapping {
+    pub state: BlockState,
+    /// 没有 Java 对应属性而被丢弃的 Bedrock 状态
+    pub dropped: Vec<String>,
+}
+
+/// Java 方块状态对应的 Bedrock 方块；模组方块与仅 Java 版存在的方块返回 None
+pub fn java_to_bedrock(state: &BlockState) -> Option<BedrockMapping> {
+    if let Some(block) = placeholder_to_bedrock(state) {
+        return Some(BedrockMapping { block, waterlogged: false, dropped: Vec::new() });
+    }
+    if !state.name.starts_with("minecraft:") {
+        return None;
+    }
+
+    let mut remaining = state.properties.clone();
+    let waterlogged =
+        remaining.remove("waterlogged").as_deref() == Some("true") || ALWAYS_WATERLOGGED.contains(&state.path());
+    let double_slab = slab(state.path()) && state.property("type") == Some("double");
+    if double_slab {
+        remaining.remove("type");
+    }
+
+    let entry = table().forward.get(&state.name).and_then(|entries| {
+        entries.iter().find(|(java, _)| java.properties.iter().all(|(k, v)| state.property(k) == Some(v.as_str())))
+    });
+    let mut block = match entry {
+        Some((java, bedrock)) => {
+            let bedrock = bedrock.clone()?;
+            for key in java.properties.keys() {
+                remaining.remove(key);
+            }
+            bedrock
+        }
+        None => BedrockBlock::new(state.name.clone()),
+    };
+
+    // 双层台阶在 Bedrock 中是独立的方块
+    if double_slab {
+        let base = block.name.strip_suffix("_slab").unwrap_or(&block.name).to_string();
+        block.name = format!("{}_double_slab", base);
+        block.states.insert("minecraft:vertical_half".to_string(), BedrockValue::String("bottom".to_string()));
+    }
+
+    let path = state.path();
+    // 按钮的 face + facing 合并为六向的 facing_direction
+    if button(path) {
+        let face = remaining.remove("face");
+        let facing = remaining.remove("facing");
+        let direction = match face.as_deref() {
+            Some("floor") => Some(1),
+            Some("ceiling") => Some(0),
+            _ => facing.as_deref().and_then(|f| FACING_DIRECTIONS.iter().find(|(j, _)| *j == f)).map(|(_, b)| *b),
+        };
+        if let Some(direction) = direction {
+            block.states.insert("facing_direction".to_string(), BedrockValue::Int(direction));
+        }
+    }
+    // 拉杆的 face + facing 合并为 lever_direction，地面与天花板上只区分南北轴与东西轴
+    if lever(path) {
+        let face = remaining.remove("face");
+        let facing = remaining.remove("facing").unwrap_or_else(|| "north".to_string());
+        let axis = if facing == "east" || facing == "west" { "east_west" } else { "north_south" };
+        let direction = match face.as_deref() {
+            Some("floor") => format!("up_{}", axis),
+            Some("ceiling") => format!("down_{}", axis),
+            _ => facing,
+        };
+        block.states.insert("lever_direction".to_string(), BedrockValue::String(direction));
+    }
+
+    let mut dropped = Vec::new();
+    for (key, value) in remaining {
+        if is_derived_java_property(path, &key) {
+            continue;
+        }
+        let converted = PROPERTY_RULES
+            .iter()
+            .find(|rule| rule.java == key && (rule.applies)(path))
+            .and_then(|rule| rule.values.to_bedrock(&value).map(|v| (rule.bedrock, v)));
+        match converted {
+            Some((bedrock_key, bedrock_value)) => {
+                block.states.entry(bedrock_key.to_string()).or_insert(bedrock_value);
+            }
+            None => dropped.push(key),
+        }
+    }
+    Some(BedrockMapping { block, waterlogged, dropped })
+}
+
+/// Bedrock 方块对应的 Java 方块状态；第二层为水时为可含水的方块加上 waterlogged。
+/// 仅 Bedrock 版存在的方块返回 None
+pub fn bedrock_to_java(block: &BedrockBlock, waterlogged: bool) -> Option<JavaMapping> {
+    if !block.name.starts_with("minecraft:") {
+        return None;
+    }
+
+    let mut remaining = block.states.clone();
+    let (name, double_slab) = match block.name.strip_suffix("_double_slab") {
+        Some(base) => (format!("{}_slab", base), true),
+        None => (block.name.clone(), false),
+    };
+    if double_slab {
+        remaining.remove("minecraft:vertical_half");
+    }
+
+    let entry = table().reverse.get(&name).and_then(|entries| {
+        entries.iter().find(|(bedrock, _)| bedrock.states.iter().all(|(k, v)| block.states.get(k) == Some(v)))
+    });
+    let mut state = match entry {
+        Some((bedrock, java)) => {
+            let java = java.clone()?;
+            for key in bedrock.states.keys() {
+                remaining.remove(key);
+            }
+            java
+        }
+        None => BlockState::new(name),
+    };
+    if double_slab {
+        state.properties.insert("type".to_string(), "double".to_string());
+    }
+
+    let path = state.path().to_string();
+    if button(&path) {
+        if let Some(direction) = remaining.remove("facing_direction").and_then(|v| v.as_i64()) {
+            let (face, facing) = match direction {
+                0 => ("ceiling", "north"),
+                1 => ("floor", "north"),
+                d => ("wall", FACING_DIRECTIONS.iter().find(|(_, b)| i64::from(*b) == d).map_or("north", |(j, _)| *j)),
+            };
+            state.properties.insert("face".to_string(), face.to_string());
+            state.properties.insert("facing".to_string(), facing.to_string());
+        }
+    }
+    if lever(&path) {
+        if let Some(BedrockValue::String(direction)) = remaining.remove("lever_direction") {
+            let (face, facing) = match direction.split_once('_') {
+                Some(("up", axis)) => ("floor", if axis == "east_west" { "east" } else { "north" }),
+                Some(("down", axis)) => ("ceiling", if axis == "east_west" { "east" } else { "north" }),
+                _ => ("wall", direction.as_str()),
+            };
+            state.properties.insert("face".to_string(), face.to_string());
+            state.properties.insert("facing".to_string(), facing.to_string());
+        }
+    }
+
+    let mut dropped = Vec::new();
+    for (key, value) in remaining {
+        let converted = PROPERTY_RULES
+            .iter()
+            .find(|rule| rule.bedrock == key && (rule.applies)(&path))
+            .and_then(|rule| rule.values.to_java(&value).map(|v| (rule.java, v)));
+        match converted {
+            Some((java_key, java_value)) => {
+                state.properties.entry(java_key.to_string()).or_insert(java_value);
+            }
+            None if DERIVED_BEDROCK_STATES.contains(&key.as_str()) => {}
+            None => dropped.push(key),
+        }
+    }
+    if waterlogged && is_waterloggable(&path) {
+        state.properties.insert("waterlogged".to_string(), "true".to_string());
+    }
+    Some(JavaMapping { state, dropped })
+}
+
+/// 无法对应的 Bedrock 方块以 `bedrock:<name>` 占位，保留全部状态
+pub fn bedrock_placeholder(block: &BedrockBlock) -> BlockState {
+    let mut state = BlockState::new(format!("{}:{}", BEDROCK_PLACEHOLDER_NAMESPACE, block.path()));
+    for (key, value) in &block.states {
+        state.properties.insert(key.clone(), value.to_string());
+    }
+    state
+}
+
+/// 占位方块还原为原始 Bedrock 方块
+pub fn placeholder_to_bedrock(state: &BlockState) -> Option<BedrockBlock> {
+    let (namespace, path) = state.name.split_once(':')?;
+    if namespace != BEDROCK_PLACEHOLDER_NAMESPACE {
+        return None;
+    }
+    let states = state.properties.iter().map(|(k, v)| (k.clone(), BedrockValue::parse(v))).collect();
+    Some(BedrockBlock { name: format!("minecraft:{}", path), states })
+}
+
+// 导出的函数
+/// Java 方块状态字符串转换为 Bedrock 方块状态字符串；无法转换时返回空字符串
+#[wasm_bindgen]
+pub fn java_block_to_bedrock(state: &str) -> String {
+    BlockState::parse(state)
+        .ok()
+        .and_then(|state| java_to_bedrock(&state))
+        .map(|mapping| mapping.block.to_string())
+        .unwrap_or_default()
+}
+
+/// Bedrock 方块状态字符串转换为 Java 方块状态字符串；无法转换时返回空字符串
+#[wasm_bindgen]
+pub fn bedrock_block_to_java(state: &str) -> String {
+    BedrockBlock::parse(state)
+        .ok()
+        .and_then(|block| bedrock_to_java(&block, false))
+        .map(|mapping| mapping.state.to_string())
+        .unwrap_or_default()
+}
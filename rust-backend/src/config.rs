@@ -1,5 +1,4 @@
 use serde::{Deserialize, Serialize};
-use wasm_bindgen::prelude::*;
 use std::env;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,6 +108,6 @@ pub fn init_config() -> Result<(), Box<dyn std::error::Error>> {
 
 pub fn get_config() -> &'static Config {
     unsafe {
-        CONFIG.as_ref().expect("Config not initialized")
+        (*std::ptr::addr_of!(CONFIG)).as_ref().expect("Config not initialized")
     }
 }
@@ -0,0 +1,363 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::{BlockState, NeptuniumError, Region, Schematic, SchematicError, SchematicFormat, STRUCTURE_VOID};
+
+// 格式转换：任意受支持格式 → 通用模型 → 目标格式，并报告无法表示的内容
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Litematica,
+    SpongeV2,
+    SpongeV3,
+    McEdit,
+    Structure,
+    McStructure,
+}
+
+impl ExportFormat {
+    /// 接受格式名或扩展名；"sponge" / "schem" 默认写出兼容性最好的 v2
+    pub fn from_name(name: &str) -> Option<ExportFormat> {
+        match name.trim_start_matches('.').to_lowercase().as_str() {
+            "litematica" | "litematic" => Some(ExportFormat::Litematica),
+            "sponge" | "sponge_v2" | "schem" => Some(ExportFormat::SpongeV2),
+            "sponge_v3" => Some(ExportFormat::SpongeV3),
+            "mcedit" | "schematic" => Some(ExportFormat::McEdit),
+            "structure" | "nbt" => Some(ExportFormat::Structure),
+            "mcstructure" | "bedrock" => Some(ExportFormat::McStructure),
+            _ => None,
+        }
+    }
+
+    /// 写回源格式时使用的导出格式；Sponge 按源文件版本选择 v2 或 v3
+    pub fn for_source(format: SchematicFormat, version: Option<i32>) -> Option<ExportFormat> {
+        match format {
+            SchematicFormat::Litematica => Some(ExportFormat::Litematica),
+            SchematicFormat::Sponge if version == Some(3) => Some(ExportFormat::SpongeV3),
+            SchematicFormat::Sponge => Some(ExportFormat::SpongeV2),
+            SchematicFormat::McEdit => Some(ExportFormat::McEdit),
+            SchematicFormat::Structure => Some(ExportFormat::Structure),
+            SchematicFormat::McStructure => Some(ExportFormat::McStructure),
+            SchematicFormat::Unknown => None,
+        }
+    }
+
+    pub fn schematic_format(&self) -> SchematicFormat {
+        match self {
+            ExportFormat::Litematica => SchematicFormat::Litematica,
+            ExportFormat::SpongeV2 | ExportFormat::SpongeV3 => SchematicFormat::Sponge,
+            ExportFormat::McEdit => SchematicFormat::McEdit,
+            ExportFormat::Structure => SchematicFormat::Structure,
+            ExportFormat::McStructure => SchematicFormat::McStructure,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Litematica => "litematica",
+            ExportFormat::SpongeV2 => "sponge_v2",
+            ExportFormat::SpongeV3 => "sponge_v3",
+            ExportFormat::McEdit => "mcedit",
+            ExportFormat::Structure => "structure",
+            ExportFormat::McStructure => "mcstructure",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversionWarning {
+    pub code: String,
+    pub message: String,
+}
+
+impl ConversionWarning {
+    pub fn new(code: &str, message: String) -> ConversionWarning {
+        ConversionWarning { code: code.to_string(), message }
+    }
+}
+
+/// 将投影序列化为目标格式，返回文件字节与转换警告
+pub fn write_schematic(schematic: &Schematic, target: ExportFormat) -> Result<(Vec<u8>, Vec<ConversionWarning>), SchematicError> {
+    let mut warnings = Vec::new();
+
+    if schematic.data_version <= 0 {
+        warnings.push(ConversionWarning::new(
+            "unknown_data_version",
+            "Source file does not record a DataVersion; the output may need upgrading in-game".to_string(),
+        ));
+    }
+    warnings.extend(placeholder_warnings(schematic, Some(target)));
+
+    let document = match target {
+        ExportFormat::Litematica => crate::write_litematic(schematic, &mut warnings)?,
+        ExportFormat::SpongeV2 => crate::write_sponge(schematic, 2, &mut warnings)?,
+        ExportFormat::SpongeV3 => crate::write_sponge(schematic, 3, &mut warnings)?,
+        ExportFormat::McEdit => crate::write_mcedit(schematic, &mut warnings)?,
+        ExportFormat::Structure => crate::write_structure(schematic, &mut warnings)?,
+        ExportFormat::McStructure => crate::write_mcstructure(schematic, &mut warnings)?,
+    };
+    Ok((document.to_bytes()?, warnings))
+}
+
+/// 单区域格式的写出准备：多区域时合并并警告，非结构格式将结构空位替换为空气
+pub(crate) fn single_region(
+    schematic: &Schematic,
+    fill: &BlockState,
+    warnings: &mut Vec<ConversionWarning>,
+) -> Result<Region, SchematicError> {
+    let mut region = match schematic.regions.len() {
+        0 => return Err(SchematicError::Invalid("Schematic has no regions".to_string())),
+        1 => {
+            let mut region = schematic.regions[0].clone();
+            region.compact_palette();
+            region
+        }
+        count => {
+            let (region, conflicts) = schematic.flatten(fill)?;
+            let mut message = format!("{} regions were merged into a single region", count);
+            if conflicts > 0 {
+                message.push_str(&format!("; {} overlapping blocks were overwritten by later regions", conflicts));
+            }
+            warnings.push(ConversionWarning::new("multiple_regions_flattened", message));
+            region
+        }
+    };
+
+    if fill.name != STRUCTURE_VOID {
+        let voids: u64 = region
+            .palette
+            .iter()
+            .zip(region.palette_counts())
+            .filter(|(state, _)| state.name == STRUCTURE_VOID)
+            .map(|(_, count)| count)
+            .sum();
+        if voids > 0 {
+            for state in region.palette.iter_mut().filter(|s| s.name == STRUCTURE_VOID) {
+                *state = BlockState::air();
+            }
+            region.compact_palette();
+            warnings.push(ConversionWarning::new(
+                "structure_void_replaced",
+                format!("{} structure void positions were written as air", voids),
+            ));
+        }
+    }
+    Ok(region)
+}
+
+/// 占位方块（未知的旧版数字 id、无法映射的 Bedrock 方块）只有写回其来源格式时才能还原；
+/// target 为 None 时（材料清单、预览）报告所有占位方块
+pub fn placeholder_warnings(schematic: &Schematic, target: Option<ExportFormat>) -> Vec<ConversionWarning> {
+    let mut warnings = Vec::new();
+    let mut legacy: BTreeMap<String, u64> = BTreeMap::new();
+    let mut bedrock: BTreeMap<String, u64> = BTreeMap::new();
+    for region in &schematic.regions {
+        for (state, count) in region.palette.iter().zip(region.palette_counts()) {
+            if count == 0 {
+                continue;
+            }
+            if state.name.starts_with("legacy:") && target != Some(ExportFormat::McEdit) {
+                *legacy.entry(state.to_string()).or_default() += count;
+            } else if state.name.starts_with("bedrock:") && target != Some(ExportFormat::McStructure) {
+                *bedrock.entry(state.to_string()).or_default() += count;
+            }
+        }
+    }
+    for (state, count) in legacy {
+        warnings.push(ConversionWarning::new(
+            "legacy_block",
+            format!("{} is an unknown pre-1.13 block id with no flattening mapping ({} blocks)", state, count),
+        ));
+    }
+    for (state, count) in bedrock {
+        warnings.push(ConversionWarning::new(
+            "bedrock_block",
+            format!("{} is a Bedrock-only block with no Java equivalent ({} blocks)", state, count),
+        ));
+    }
+    warnings
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[wasm_bindgen]
+pub struct ConversionOutput {
+    data: Vec<u8>,
+    format: String,
+    extension: String,
+    warnings: Vec<ConversionWarning>,
+}
+
+#[wasm_bindgen]
+impl ConversionOutput {
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.data.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn format(&self) -> String {
+        self.format.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn extension(&self) -> String {
+        self.extension.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn warnings_json(&self) -> String {
+        serde_json::to_string(&self.warnings).unwrap_or_default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+}
+
+impl ConversionOutput {
+    pub fn new(data: Vec<u8>, target: ExportFormat, warnings: Vec<ConversionWarning>) -> ConversionOutput {
+        ConversionOutput {
+            data,
+            format: target.as_str().to_string(),
+            extension: target.schematic_format().extension().to_string(),
+            warnings,
+        }
+    }
+
+    pub fn warnings(&self) -> &[ConversionWarning] {
+        &self.warnings
+    }
+}
+
+/// 解析目标格式名；留空时按文件内容写回源格式
+pub(crate) fn resolve_export_format(data: &[u8], target_format: &str) -> Result<ExportFormat, NeptuniumError> {
+    let target = if target_format.trim().is_empty() {
+        let detection = crate::detect_format(data);
+        ExportFormat::for_source(detection.schematic_format(), detection.version())
+    } else {
+        ExportFormat::from_name(target_format)
+    };
+    target.ok_or_else(|| {
+        NeptuniumError::new(format!("Unsupported target format: {}", target_format), "UNSUPPORTED_FORMAT".to_string())
+    })
+}
+
+/// 读取投影，同时返回读取阶段的警告；目前只有 .mcstructure 在读取时做方块映射
+pub fn read_schematic_with_warnings(data: &[u8]) -> Result<(Schematic, Vec<ConversionWarning>), SchematicError> {
+    let document = crate::read_document_with_limits(data, &crate::IngestLimits::current())?;
+    let mut warnings = Vec::new();
+    let schematic = match crate::detect_from_root(&document.root).0 {
+        SchematicFormat::McStructure => {
+            let schematic = crate::read_mcstructure_with_warnings(&document.root, &mut warnings)?;
+            for region in &schematic.regions {
+                region.validate()?;
+            }
+            schematic
+        }
+        _ => Schematic::from_root(&document.root)?,
+    };
+    Ok((schematic, warnings))
+}
+
+/// 在格式之间转换，target_format 可为格式名（litematica / sponge_v2 / sponge_v3 / mcedit / structure / mcstructure）或扩展名。
+/// Bedrock 结构与 Java 格式之间的转换会在警告中列出无法映射的方块与状态
+#[wasm_bindgen]
+pub fn convert_schematic(data: &[u8], target_format: &str) -> Result<ConversionOutput, NeptuniumError> {
+    let target = ExportFormat::from_name(target_format).ok_or_else(|| {
+        NeptuniumError::new(format!("Unsupported target format: {}", target_format), "UNSUPPORTED_FORMAT".to_string())
+    })?;
+    let (schematic, mut warnings) = read_schematic_with_warnings(data)?;
+    let (bytes, write_warnings) = write_schematic(&schematic, target)?;
+    warnings.extend(write_warnings);
+    Ok(ConversionOutput::new(bytes, target, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockEntity, Entity, NbtCompound, NbtTag, Vec3i};
+
+    const JAVA_FORMATS: [ExportFormat; 5] = [
+        ExportFormat::Litematica,
+        ExportFormat::SpongeV2,
+        ExportFormat::SpongeV3,
+        ExportFormat::McEdit,
+        ExportFormat::Structure,
+    ];
+
+    /// 3×2×2 的区域：石头、朝东的橡木楼梯、带物品名的箱子和一头猪
+    fn sample() -> Schematic {
+        let mut region = Region::new("Main", Vec3i::new(0, 0, 0), Vec3i::new(3, 2, 2)).unwrap();
+        region.set_block(0, 0, 0, &BlockState::new("minecraft:stone"));
+        let stairs = BlockState::new("minecraft:oak_stairs")
+            .with_property("facing", "east")
+            .with_property("half", "bottom")
+            .with_property("shape", "straight")
+            .with_property("waterlogged", "false");
+        region.set_block(1, 0, 0, &stairs);
+        let chest = BlockState::new("minecraft:chest")
+            .with_property("facing", "north")
+            .with_property("type", "single")
+            .with_property("waterlogged", "false");
+        region.set_block(2, 1, 1, &chest);
+        let mut nbt = NbtCompound::new();
+        nbt.insert("CustomName", NbtTag::String("{\"text\":\"Loot\"}".to_string()));
+        region.block_entities.push(BlockEntity { pos: Vec3i::new(2, 1, 1), id: "minecraft:chest".to_string(), nbt });
+        region.entities.push(Entity { id: "minecraft:pig".to_string(), pos: [0.5, 1.0, 1.5], nbt: NbtCompound::new() });
+        let mut schematic = Schematic::new("Sample", 3700, SchematicFormat::Litematica);
+        schematic.regions.push(region);
+        schematic
+    }
+
+    fn round_trip(schematic: &Schematic, target: ExportFormat) -> (Schematic, Vec<ConversionWarning>) {
+        let (data, warnings) = write_schematic(schematic, target).unwrap();
+        let read = Schematic::from_bytes(&data).unwrap_or_else(|e| panic!("{:?}: {}", target, e));
+        assert_eq!(read.format, target.schematic_format(), "{:?}", target);
+        (read, warnings)
+    }
+
+    #[test]
+    fn java_formats_round_trip_blocks_block_entities_and_entities() {
+        let source = sample();
+        let expected = &source.regions[0];
+        for target in JAVA_FORMATS {
+            let (read, _) = round_trip(&source, target);
+            assert_eq!(read.regions.len(), 1, "{:?}", target);
+            let region = &read.regions[0];
+            assert_eq!(region.size, expected.size, "{:?}", target);
+            for i in 0..expected.volume() {
+                let pos = expected.position(i);
+                let block = region.block(pos.x, pos.y, pos.z);
+                assert_eq!(block, expected.block(pos.x, pos.y, pos.z), "{:?} {:?}", target, pos);
+            }
+            let chest = &region.block_entities[0];
+            assert_eq!((chest.pos, chest.id.as_str()), (Vec3i::new(2, 1, 1), "minecraft:chest"), "{:?}", target);
+            assert_eq!(chest.nbt.get_str("CustomName"), Some("{\"text\":\"Loot\"}"), "{:?}", target);
+            assert_eq!(region.entities.len(), 1, "{:?}", target);
+            assert_eq!((region.entities[0].id.as_str(), region.entities[0].pos), ("minecraft:pig", [0.5, 1.0, 1.5]));
+        }
+    }
+
+    #[test]
+    fn data_version_survives_every_format_that_records_it() {
+        for target in JAVA_FORMATS.into_iter().filter(|f| *f != ExportFormat::McEdit) {
+            assert_eq!(round_trip(&sample(), target).0.data_version, 3700, "{:?}", target);
+        }
+    }
+
+    #[test]
+    fn multiple_regions_are_flattened_for_single_region_formats() {
+        let mut schematic = sample();
+        let mut second = Region::new("Second", Vec3i::new(3, 0, 0), Vec3i::new(1, 1, 1)).unwrap();
+        second.set_block(0, 0, 0, &BlockState::new("minecraft:glass"));
+        schematic.regions.push(second);
+
+        assert_eq!(round_trip(&schematic, ExportFormat::Litematica).0.regions.len(), 2);
+        let (read, warnings) = round_trip(&schematic, ExportFormat::SpongeV2);
+        assert_eq!(read.regions[0].size, Vec3i::new(4, 2, 2));
+        assert_eq!(read.regions[0].block(3, 0, 0).name, "minecraft:glass");
+        assert!(warnings.iter().any(|w| w.code == "multiple_regions_flattened"));
+    }
+}
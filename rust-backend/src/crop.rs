@@ -0,0 +1,507 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{
+    BlockEntity, ConversionOutput, ConversionWarning, Entity, ExportFormat, NeptuniumError, Region, Schematic,
+    SchematicError, Vec3i, STRUCTURE_VOID,
+};
+
+// 裁剪、去除空气边界与分块：把超大投影拆成玩家能一次加载的若干独立文件
+
+/// 投影坐标系中的长方体，`size` 各分量为正
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CropBox {
+    pub min: Vec3i,
+    pub size: Vec3i,
+}
+
+impl CropBox {
+    pub fn new(min: Vec3i, size: Vec3i) -> CropBox {
+        CropBox { min, size }
+    }
+
+    /// 不含的最大角；超出 i32 范围时报错
+    fn max(&self) -> Result<Vec3i, SchematicError> {
+        let axis = |min: i32, size: i32| {
+            min.checked_add(size).ok_or_else(|| {
+                let Vec3i { x, y, z } = self.min;
+                SchematicError::Invalid(format!("Box at {}, {}, {} extends beyond the coordinate range", x, y, z))
+            })
+        };
+        Ok(Vec3i::new(axis(self.min.x, self.size.x)?, axis(self.min.y, self.size.y)?, axis(self.min.z, self.size.z)?))
+    }
+
+    /// 与另一个长方体的交集
+    pub(crate) fn intersect(&self, other: &CropBox) -> Result<Option<CropBox>, SchematicError> {
+        let (a, b) = (self.max()?, other.max()?);
+        let min = Vec3i::new(self.min.x.max(other.min.x), self.min.y.max(other.min.y), self.min.z.max(other.min.z));
+        let max = Vec3i::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+        Ok((max.x > min.x && max.y > min.y && max.z > min.z)
+            .then(|| CropBox::new(min, Vec3i::new(max.x - min.x, max.y - min.y, max.z - min.z))))
+    }
+
+    fn contains_point(&self, point: [f64; 3]) -> bool {
+        let axis = |min: i32, size: i32, value: f64| {
+            (f64::from(min)..f64::from(min) + f64::from(size)).contains(&value)
+        };
+        axis(self.min.x, self.size.x, point[0])
+            && axis(self.min.y, self.size.y, point[1])
+            && axis(self.min.z, self.size.z, point[2])
+    }
+}
+
+fn region_box(region: &Region) -> CropBox {
+    CropBox::new(region.origin, region.size)
+}
+
+/// 裁剪单个区域，与裁剪框不相交时返回 None；实体按其所在位置保留
+fn crop_region(region: &Region, bounds: &CropBox) -> Result<Option<Region>, SchematicError> {
+    let Some(inside) = region_box(region).intersect(bounds)? else {
+        return Ok(None);
+    };
+    let shift = Vec3i::new(inside.min.x - region.origin.x, inside.min.y - region.origin.y, inside.min.z - region.origin.z);
+    let mut output = Region::new(region.name.clone(), inside.min, inside.size)?;
+    output.palette = region.palette.clone();
+    for y in 0..inside.size.y {
+        for z in 0..inside.size.z {
+            for x in 0..inside.size.x {
+                let source = region.index(x + shift.x, y + shift.y, z + shift.z);
+                let target = output.index(x, y, z);
+                output.blocks[target] = region.blocks[source];
+            }
+        }
+    }
+    output.compact_palette();
+
+    output.block_entities = region
+        .block_entities
+        .iter()
+        .filter_map(|be| {
+            let pos = Vec3i::new(be.pos.x - shift.x, be.pos.y - shift.y, be.pos.z - shift.z);
+            output.contains(pos.x, pos.y, pos.z).then(|| BlockEntity { pos, ..be.clone() })
+        })
+        .collect();
+    output.entities = region
+        .entities
+        .iter()
+        .filter(|entity| {
+            inside.contains_point([
+                f64::from(region.origin.x) + entity.pos[0],
+                f64::from(region.origin.y) + entity.pos[1],
+                f64::from(region.origin.z) + entity.pos[2],
+            ])
+        })
+        .map(|entity| {
+            let mut entity = Entity {
+                pos: [
+                    entity.pos[0] - f64::from(shift.x),
+                    entity.pos[1] - f64::from(shift.y),
+                    entity.pos[2] - f64::from(shift.z),
+                ],
+                ..entity.clone()
+            };
+            crate::map_entity_tile(&mut entity, |p| Vec3i::new(p.x - shift.x, p.y - shift.y, p.z - shift.z));
+            entity
+        })
+        .collect();
+    Ok(Some(output))
+}
+
+/// 裁剪到给定长方体，坐标保持在原投影坐标系中
+pub fn crop_schematic(schematic: &Schematic, bounds: &CropBox) -> Result<Schematic, SchematicError> {
+    if bounds.size.x <= 0 || bounds.size.y <= 0 || bounds.size.z <= 0 {
+        return Err(SchematicError::Invalid(format!(
+            "Invalid crop size {}x{}x{}",
+            bounds.size.x, bounds.size.y, bounds.size.z
+        )));
+    }
+    let mut output = schematic.empty_like();
+    for region in &schematic.regions {
+        output.regions.extend(crop_region(region, bounds)?);
+    }
+    if output.regions.is_empty() {
+        return Err(SchematicError::Invalid("Crop box does not intersect the schematic".to_string()));
+    }
+    Ok(output)
+}
+
+/// 区域内非空气方块与实体的包围盒（投影坐标系）
+fn content_bounds(region: &Region) -> Option<CropBox> {
+    let empty: Vec<bool> = region.palette.iter().map(|s| s.is_air() || s.name == STRUCTURE_VOID).collect();
+    let mut min = Vec3i::new(i32::MAX, i32::MAX, i32::MAX);
+    let mut max = Vec3i::new(i32::MIN, i32::MIN, i32::MIN);
+    let mut include = |p: Vec3i| {
+        min = Vec3i::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+        max = Vec3i::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+    };
+    for (index, palette_index) in region.blocks.iter().enumerate() {
+        if !empty[*palette_index as usize] {
+            include(region.position(index));
+        }
+    }
+    for entity in &region.entities {
+        let pos = Vec3i::new(entity.pos[0].floor() as i32, entity.pos[1].floor() as i32, entity.pos[2].floor() as i32);
+        if region.contains(pos.x, pos.y, pos.z) {
+            include(pos);
+        }
+    }
+    (min.x <= max.x).then(|| {
+        CropBox::new(
+            Vec3i::new(region.origin.x + min.x, region.origin.y + min.y, region.origin.z + min.z),
+            Vec3i::new(max.x - min.x + 1, max.y - min.y + 1, max.z - min.z + 1),
+        )
+    })
+}
+
+/// 所有区域中非空气内容的包围盒
+pub fn schematic_content_bounds(schematic: &Schematic) -> Result<Option<CropBox>, SchematicError> {
+    let mut bounds: Option<CropBox> = None;
+    for b in schematic.regions.iter().filter_map(content_bounds) {
+        bounds = Some(match bounds {
+            None => b,
+            Some(a) => {
+                let (a_max, b_max) = (a.max()?, b.max()?);
+                let min = Vec3i::new(a.min.x.min(b.min.x), a.min.y.min(b.min.y), a.min.z.min(b.min.z));
+                let max = Vec3i::new(a_max.x.max(b_max.x), a_max.y.max(b_max.y), a_max.z.max(b_max.z));
+                CropBox::new(min, Vec3i::new(max.x - min.x, max.y - min.y, max.z - min.z))
+            }
+        });
+    }
+    Ok(bounds)
+}
+
+/// 将每个区域收缩到其非空气内容的包围盒，全为空气的区域被移除
+pub fn trim_schematic(schematic: &Schematic) -> Result<Schematic, SchematicError> {
+    let mut output = schematic.empty_like();
+    for region in &schematic.regions {
+        if let Some(bounds) = content_bounds(region) {
+            output.regions.extend(crop_region(region, &bounds)?);
+        }
+    }
+    if output.regions.is_empty() {
+        return Err(SchematicError::Invalid("Schematic contains only air".to_string()));
+    }
+    Ok(output)
+}
+
+// 单次分块允许产生的最大分块数
+const MAX_TILES: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TileOptions {
+    /// 分块在 X / Z 方向的边长；整个高度不拆分
+    pub size: i32,
+    /// 按世界区块网格（16 的倍数）对齐，边长向上取整到 16 的倍数
+    pub chunk_aligned: bool,
+    /// 保留全为空气的分块
+    pub include_empty: bool,
+    /// 每个分块再去除空气边界
+    pub trim: bool,
+}
+
+impl Default for TileOptions {
+    fn default() -> Self {
+        TileOptions { size: 64, chunk_aligned: false, include_empty: false, trim: true }
+    }
+}
+
+impl TileOptions {
+    pub fn from_json(json: &str) -> Result<TileOptions, NeptuniumError> {
+        if json.trim().is_empty() {
+            return Ok(TileOptions::default());
+        }
+        serde_json::from_str(json)
+            .map_err(|e| NeptuniumError::new(format!("Invalid tile options: {}", e), "INVALID_TILE_OPTIONS".to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchematicTile {
+    /// 分块网格中的列（X）与行（Z）
+    pub column: i32,
+    pub row: i32,
+    /// 分块最小角在原投影坐标系中的位置，即粘贴时相对原粘贴点的偏移
+    pub offset: Vec3i,
+    pub size: Vec3i,
+    pub block_count: u64,
+    #[serde(skip)]
+    pub schematic: Schematic,
+}
+
+/// 沿 X / Z 将投影拆成若干分块，按行、列顺序返回
+pub fn split_into_tiles(schematic: &Schematic, options: &TileOptions) -> Result<Vec<SchematicTile>, SchematicError> {
+    if options.size <= 0 {
+        return Err(SchematicError::Invalid(format!("Invalid tile size {}", options.size)));
+    }
+    if schematic.regions.is_empty() {
+        return Err(SchematicError::Invalid("Schematic has no regions".to_string()));
+    }
+    let (min, size) = schematic.bounds()?;
+    let overflow =
+        || SchematicError::Invalid(format!("Tile size {} is out of range for this schematic", options.size));
+    let (tile_size, grid_x, grid_z) = if options.chunk_aligned {
+        let tile = options.size.checked_add(15).ok_or_else(overflow)? / 16 * 16;
+        let align = |min: i32| min.div_euclid(tile).checked_mul(tile).ok_or_else(overflow);
+        (tile, align(min.x)?, align(min.z)?)
+    } else {
+        (options.size, min.x, min.z)
+    };
+    // 覆盖 [grid, min + size) 所需的分块数，向上取整
+    let count = |min: i32, size: i32, grid: i32| {
+        min.checked_add(size)
+            .and_then(|end| end.checked_sub(grid))
+            .and_then(|span| span.checked_add(tile_size - 1))
+            .map(|span| span / tile_size)
+            .ok_or_else(overflow)
+    };
+    // 第 index 个分块的起点
+    let start =
+        |grid: i32, index: i32| index.checked_mul(tile_size).and_then(|o| grid.checked_add(o)).ok_or_else(overflow);
+    let columns = count(min.x, size.x, grid_x)?;
+    let rows = count(min.z, size.z, grid_z)?;
+    if columns as usize * rows as usize > MAX_TILES {
+        return Err(SchematicError::Invalid(format!(
+            "Splitting into {}x{} tiles exceeds the limit of {} tiles",
+            columns, rows, MAX_TILES
+        )));
+    }
+
+    let mut tiles = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            let bounds = CropBox::new(
+                Vec3i::new(start(grid_x, column)?, min.y, start(grid_z, row)?),
+                Vec3i::new(tile_size, size.y, tile_size),
+            );
+            let mut tile = schematic.empty_like();
+            for region in &schematic.regions {
+                tile.regions.extend(crop_region(region, &bounds)?);
+            }
+            let block_count = tile.total_blocks();
+            if block_count == 0 && !options.include_empty {
+                continue;
+            }
+            if options.trim && block_count > 0 {
+                tile = trim_schematic(&tile)?;
+            }
+            if tile.regions.is_empty() {
+                continue;
+            }
+            tile.name = format!("{} [{}, {}]", schematic.name, column, row);
+            let (offset, size) = tile.bounds()?;
+            tiles.push(SchematicTile { column, row, offset, size, block_count, schematic: tile });
+        }
+    }
+    Ok(tiles)
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TileFile {
+    #[serde(flatten)]
+    tile: SchematicTile,
+    filename: String,
+    file_size: usize,
+    warnings: Vec<ConversionWarning>,
+}
+
+/// 分块结果：每个分块是独立的投影文件
+#[derive(Debug, Serialize)]
+#[wasm_bindgen]
+pub struct TileSet {
+    format: String,
+    extension: String,
+    tiles: Vec<TileFile>,
+    data: Vec<Vec<u8>>,
+}
+
+#[wasm_bindgen]
+impl TileSet {
+    #[wasm_bindgen(getter)]
+    pub fn format(&self) -> String {
+        self.format.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn extension(&self) -> String {
+        self.extension.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// 各分块的位置、尺寸、文件名与转换警告
+    #[wasm_bindgen(getter)]
+    pub fn tiles_json(&self) -> String {
+        serde_json::to_string(&self.tiles).unwrap_or_default()
+    }
+
+    /// 第 index 个分块的文件内容
+    pub fn tile_data(&self, index: usize) -> Option<Vec<u8>> {
+        self.data.get(index).cloned()
+    }
+
+    /// 第 index 个分块在 `projections/{prefix}/{file_id}` 下的存储路径
+    pub fn tile_storage_path(&self, index: usize, file_id: &str) -> Option<String> {
+        let tile = &self.tiles.get(index)?.tile;
+        Some(crate::generate_tile_storage_path(file_id, tile.column, tile.row, &self.extension))
+    }
+}
+
+fn write_output(schematic: &Schematic, target: ExportFormat) -> Result<ConversionOutput, NeptuniumError> {
+    let (bytes, warnings) = crate::write_schematic(schematic, target)?;
+    Ok(ConversionOutput::new(bytes, target, warnings))
+}
+
+// 导出的函数
+/// 去除空气边界；target_format 留空时写回源格式
+#[wasm_bindgen]
+pub fn trim_schematic_file(data: &[u8], target_format: &str) -> Result<ConversionOutput, NeptuniumError> {
+    let target = crate::resolve_export_format(data, target_format)?;
+    write_output(&trim_schematic(&Schematic::from_bytes(data)?)?, target)
+}
+
+/// 裁剪到 `{"min":{"x":..,"y":..,"z":..},"size":{..}}` 指定的长方体（投影坐标系）
+#[wasm_bindgen]
+pub fn crop_schematic_file(data: &[u8], box_json: &str, target_format: &str) -> Result<ConversionOutput, NeptuniumError> {
+    let bounds: CropBox = serde_json::from_str(box_json)
+        .map_err(|e| NeptuniumError::new(format!("Invalid crop box: {}", e), "INVALID_CROP_BOX".to_string()))?;
+    let target = crate::resolve_export_format(data, target_format)?;
+    write_output(&crop_schematic(&Schematic::from_bytes(data)?, &bounds)?, target)
+}
+
+/// 非空气内容的包围盒（JSON）；全为空气时返回 null
+#[wasm_bindgen]
+pub fn get_content_bounds(data: &[u8]) -> Result<String, NeptuniumError> {
+    let schematic = Schematic::from_bytes(data)?;
+    Ok(serde_json::to_string(&schematic_content_bounds(&schematic)?).unwrap_or_default())
+}
+
+/// 拆分为分块文件；options_json 见 `TileOptions`，留空使用 64×64 分块
+#[wasm_bindgen]
+pub fn split_schematic_tiles(data: &[u8], options_json: &str, target_format: &str) -> Result<TileSet, NeptuniumError> {
+    let options = TileOptions::from_json(options_json)?;
+    let target = crate::resolve_export_format(data, target_format)?;
+    let schematic = Schematic::from_bytes(data)?;
+    let extension = target.schematic_format().extension().to_string();
+    let base = match crate::sanitize_filename(&schematic.name).replace(' ', "_") {
+        name if name.is_empty() => "tile".to_string(),
+        name => name,
+    };
+
+    let mut tiles = Vec::new();
+    let mut files = Vec::new();
+    for tile in split_into_tiles(&schematic, &options)? {
+        let (bytes, warnings) = crate::write_schematic(&tile.schematic, target)?;
+        tiles.push(TileFile {
+            filename: format!("{}_{}_{}{}", base, tile.column, tile.row, extension),
+            file_size: bytes.len(),
+            warnings,
+            tile,
+        });
+        files.push(bytes);
+    }
+    Ok(TileSet { format: target.as_str().to_string(), extension, tiles, data: files })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockState, NbtCompound, NbtTag, SchematicFormat};
+
+    fn stone() -> BlockState {
+        BlockState::new("minecraft:stone")
+    }
+
+    /// 40×2×20 的区域，(35, 0, 5) 处有一个箱子和挂在其上的物品展示框
+    fn sample() -> Schematic {
+        let mut region = Region::new("Main", Vec3i::new(0, 0, 0), Vec3i::new(40, 2, 20)).unwrap();
+        region.set_block(1, 0, 1, &stone());
+        region.set_block(35, 0, 5, &BlockState::new("minecraft:chest"));
+        region.block_entities.push(BlockEntity {
+            pos: Vec3i::new(35, 0, 5),
+            id: "minecraft:chest".to_string(),
+            nbt: NbtCompound::new(),
+        });
+        let mut nbt = NbtCompound::new();
+        nbt.insert("TileX", NbtTag::Int(35));
+        nbt.insert("TileY", NbtTag::Int(0));
+        nbt.insert("TileZ", NbtTag::Int(6));
+        region.entities.push(Entity { id: "minecraft:item_frame".to_string(), pos: [35.5, 0.5, 6.25], nbt });
+        let mut schematic = Schematic::new("Sample", 3700, SchematicFormat::Litematica);
+        schematic.regions.push(region);
+        schematic
+    }
+
+    fn tile_of(entity: &Entity) -> (i64, i64, i64) {
+        let get = |key| entity.nbt.get(key).and_then(NbtTag::as_i64).unwrap();
+        (get("TileX"), get("TileY"), get("TileZ"))
+    }
+
+    #[test]
+    fn crop_keeps_schematic_coordinates_and_shifts_contents() {
+        let cropped = crop_schematic(&sample(), &CropBox::new(Vec3i::new(30, 0, 4), Vec3i::new(10, 1, 10))).unwrap();
+        let region = &cropped.regions[0];
+        assert_eq!((region.origin, region.size), (Vec3i::new(30, 0, 4), Vec3i::new(10, 1, 10)));
+        assert_eq!(region.block(5, 0, 1).name, "minecraft:chest");
+        assert_eq!(region.block_entities[0].pos, Vec3i::new(5, 0, 1));
+        assert_eq!(region.entities[0].pos, [5.5, 0.5, 2.25]);
+        assert_eq!(tile_of(&region.entities[0]), (5, 0, 2));
+    }
+
+    #[test]
+    fn crop_rejects_boxes_outside_the_schematic_or_the_coordinate_range() {
+        let schematic = sample();
+        assert!(crop_schematic(&schematic, &CropBox::new(Vec3i::new(100, 0, 0), Vec3i::new(5, 1, 5))).is_err());
+        assert!(crop_schematic(&schematic, &CropBox::new(Vec3i::new(0, 0, 0), Vec3i::new(0, 1, 5))).is_err());
+        let overflowing = CropBox::new(Vec3i::new(i32::MAX - 1, 0, 0), Vec3i::new(5, 1, 5));
+        assert!(crop_schematic(&schematic, &overflowing).is_err());
+    }
+
+    #[test]
+    fn trim_shrinks_to_the_content() {
+        let trimmed = trim_schematic(&sample()).unwrap();
+        let region = &trimmed.regions[0];
+        assert_eq!((region.origin, region.size), (Vec3i::new(1, 0, 1), Vec3i::new(35, 1, 6)));
+        assert_eq!(
+            schematic_content_bounds(&sample()).unwrap(),
+            Some(CropBox::new(Vec3i::new(1, 0, 1), Vec3i::new(35, 1, 6)))
+        );
+    }
+
+    #[test]
+    fn tiles_cover_the_schematic_and_skip_empty_ones() {
+        let options = TileOptions { size: 16, trim: false, ..TileOptions::default() };
+        let tiles = split_into_tiles(&sample(), &options).unwrap();
+        // 3×2 个分块中只有 (0, 0) 与 (2, 0) 含方块
+        assert_eq!(tiles.iter().map(|t| (t.column, t.row)).collect::<Vec<_>>(), vec![(0, 0), (2, 0)]);
+        let last = &tiles[1];
+        assert_eq!(last.offset, Vec3i::new(32, 0, 0));
+        let region = &last.schematic.regions[0];
+        assert_eq!(region.block_entities[0].pos, Vec3i::new(3, 0, 5));
+        assert_eq!(tile_of(&region.entities[0]), (3, 0, 6));
+
+        let all = split_into_tiles(&sample(), &TileOptions { include_empty: true, ..options }).unwrap();
+        assert_eq!(all.len(), 6);
+    }
+
+    #[test]
+    fn chunk_aligned_tiles_round_up_to_whole_chunks() {
+        let mut schematic = sample();
+        schematic.regions[0].origin = Vec3i::new(-20, 0, 0);
+        let options = TileOptions { size: 20, chunk_aligned: true, include_empty: true, trim: false };
+        let tiles = split_into_tiles(&schematic, &options).unwrap();
+        // 边长 20 取整为 32，网格从 -32 起，第一个分块只覆盖投影内的 -20..0
+        assert_eq!(tiles.iter().map(|t| (t.offset.x, t.size.x)).collect::<Vec<_>>(), vec![(-20, 20), (0, 20)]);
+    }
+
+    #[test]
+    fn tile_arithmetic_overflow_is_an_error() {
+        let mut schematic = sample();
+        schematic.regions[0].origin = Vec3i::new(i32::MAX - 100, 0, 0);
+        let options = TileOptions { size: i32::MAX, chunk_aligned: true, ..TileOptions::default() };
+        assert!(split_into_tiles(&schematic, &options).is_err());
+        assert!(split_into_tiles(&sample(), &TileOptions { size: 0, ..TileOptions::default() }).is_err());
+    }
+}
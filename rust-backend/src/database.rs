@@ -1,5 +1,5 @@
 use wasm_bindgen::prelude::*;
-use serde::{Deserialize, Serialize};
+use crate::NeptuniumError;
 
 // 简化的数据库查询构建器，专为 WASM 环境设计
 // 生成 SQL 查询字符串，由 TypeScript 层执行
@@ -29,6 +29,7 @@ pub fn build_verify_user_query(user_id: &str) -> String {
 }
 
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
 pub fn build_create_projection_file_query(
     file_id: &str,
     user_id: Option<String>,
@@ -56,6 +57,17 @@ pub fn build_find_projection_file_query(file_id: &str) -> String {
     }).to_string()
 }
 
+/// 元数据不是合法 JSON 时返回错误，避免用空对象覆盖已存储的元数据
+#[wasm_bindgen]
+pub fn build_update_projection_metadata_query(file_id: &str, metadata_json: &str) -> Result<String, NeptuniumError> {
+    let metadata: serde_json::Value = serde_json::from_str(metadata_json)
+        .map_err(|e| NeptuniumError::new(format!("Invalid metadata JSON: {}", e), "INVALID_METADATA".to_string()))?;
+    Ok(serde_json::json!({
+        "query": "UPDATE projection_files SET metadata = $2, updated_at = NOW() WHERE file_id = $1",
+        "params": [file_id, metadata]
+    }).to_string())
+}
+
 #[wasm_bindgen]
 pub fn build_find_user_files_query(user_id: &str, limit: i32, offset: i32) -> String {
     serde_json::json!({
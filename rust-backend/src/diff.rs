@@ -0,0 +1,391 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use crate::{
+    display_locale, display_name, BlockEntity, BlockState, MaterialList, MaterialMode, NeptuniumError, Region, Schematic,
+    SchematicError, Vec3i, STRUCTURE_VOID,
+};
+
+// 同一投影两个版本之间的结构差异：方块、调色板、方块实体、包围盒与材料变化
+
+/// 默认最多列出的方块变化位置，计数不受此限制
+pub const DEFAULT_MAX_LISTED_CHANGES: usize = 10_000;
+
+/// 材料变化摘要中列出的条目数
+const SUMMARY_ENTRIES: usize = 8;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DiffOptions {
+    /// 按各自包围盒的最小角对齐，而不是按区域坐标对齐（适用于整体平移过的修订版）
+    pub align_to_min_corner: bool,
+    pub max_listed_changes: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions { align_to_min_corner: false, max_listed_changes: DEFAULT_MAX_LISTED_CHANGES }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockChange {
+    pub pos: Vec3i,
+    pub kind: ChangeKind,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockEntityChange {
+    pub pos: Vec3i,
+    pub kind: ChangeKind,
+    pub from_id: Option<String>,
+    pub to_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaletteCountChange {
+    pub state: String,
+    pub old_count: u64,
+    pub new_count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaterialDelta {
+    pub id: String,
+    pub name: String,
+    pub old_count: u64,
+    pub new_count: u64,
+    pub delta: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoundsDiff {
+    pub old_min: Vec3i,
+    pub old_size: Vec3i,
+    pub new_min: Vec3i,
+    pub new_size: Vec3i,
+    pub min_delta: Vec3i,
+    pub size_delta: Vec3i,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchematicDiff {
+    pub added: u64,
+    pub removed: u64,
+    pub changed: u64,
+    pub unchanged: u64,
+    /// 按位置排序的方块变化，超过上限时截断
+    pub changes: Vec<BlockChange>,
+    pub truncated: bool,
+    /// 只出现在旧版本中的方块状态
+    pub palette_removed: Vec<PaletteCountChange>,
+    /// 只出现在新版本中的方块状态
+    pub palette_added: Vec<PaletteCountChange>,
+    pub block_entity_changes: Vec<BlockEntityChange>,
+    pub bounds: BoundsDiff,
+    pub material_delta: Vec<MaterialDelta>,
+    /// 例如 "+120 stone, −4 hopper"
+    pub summary: String,
+}
+
+impl SchematicDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.removed == 0 && self.changed == 0 && self.block_entity_changes.is_empty()
+    }
+}
+
+/// 结构空位与空气一样视为"没有方块"
+pub(crate) fn is_empty_block(state: &BlockState) -> bool {
+    state.is_air() || state.name == STRUCTURE_VOID
+}
+
+/// 合并为单个区域，并返回该区域在比较坐标系中的偏移
+fn comparison_region(schematic: &Schematic, align_to_min_corner: bool) -> Result<(Region, Vec3i), SchematicError> {
+    let region = match schematic.regions.len() {
+        0 => return Err(SchematicError::Invalid("Schematic has no regions".to_string())),
+        1 => schematic.regions[0].clone(),
+        _ => schematic.flatten(&BlockState::air())?.0,
+    };
+    let offset = if align_to_min_corner { Vec3i::default() } else { region.origin };
+    Ok((region, offset))
+}
+
+fn block_at(region: &Region, offset: Vec3i, pos: Vec3i) -> Option<&BlockState> {
+    let (x, y, z) = (pos.x - offset.x, pos.y - offset.y, pos.z - offset.z);
+    region.contains(x, y, z).then(|| region.block(x, y, z)).filter(|s| !is_empty_block(s))
+}
+
+/// 等待列出的方块变化，按 (y, z, x) 排序；方块状态在最终输出时才转为字符串
+struct ListedChange<'a> {
+    key: (i32, i32, i32),
+    kind: ChangeKind,
+    from: Option<&'a BlockState>,
+    to: Option<&'a BlockState>,
+}
+
+impl PartialEq for ListedChange<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for ListedChange<'_> {}
+
+impl PartialOrd for ListedChange<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ListedChange<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+pub fn diff_schematics(old: &Schematic, new: &Schematic, options: &DiffOptions) -> Result<SchematicDiff, SchematicError> {
+    let (old_region, old_offset) = comparison_region(old, options.align_to_min_corner)?;
+    let (new_region, new_offset) = comparison_region(new, options.align_to_min_corner)?;
+
+    // 所有变化都计数，但只在最大堆中保留位置最靠前的 max_listed_changes 条，内存不随变化数量增长
+    let mut listed: BinaryHeap<ListedChange> = BinaryHeap::new();
+    let (mut added, mut removed, mut changed, mut unchanged) = (0u64, 0u64, 0u64, 0u64);
+    let mut record = |pos: Vec3i, from: Option<_>, to: Option<_>| {
+        let kind = match (from, to) {
+            (None, None) => return,
+            (Some(a), Some(b)) if a == b => {
+                unchanged += 1;
+                return;
+            }
+            (None, Some(_)) => {
+                added += 1;
+                ChangeKind::Added
+            }
+            (Some(_), None) => {
+                removed += 1;
+                ChangeKind::Removed
+            }
+            (Some(_), Some(_)) => {
+                changed += 1;
+                ChangeKind::Changed
+            }
+        };
+        let change = ListedChange { key: (pos.y, pos.z, pos.x), kind, from, to };
+        if listed.len() < options.max_listed_changes {
+            listed.push(change);
+        } else if listed.peek().is_some_and(|last| change < *last) {
+            listed.pop();
+            listed.push(change);
+        }
+    };
+
+    // 先遍历旧版本的全部位置，再补上新版本中落在旧区域之外的位置
+    for index in 0..old_region.volume() {
+        let local = old_region.position(index);
+        let pos = Vec3i::new(local.x + old_offset.x, local.y + old_offset.y, local.z + old_offset.z);
+        let from = Some(&old_region.palette[old_region.blocks[index] as usize]).filter(|s| !is_empty_block(s));
+        record(pos, from, block_at(&new_region, new_offset, pos));
+    }
+    for index in 0..new_region.volume() {
+        let local = new_region.position(index);
+        let pos = Vec3i::new(local.x + new_offset.x, local.y + new_offset.y, local.z + new_offset.z);
+        let (x, y, z) = (pos.x - old_offset.x, pos.y - old_offset.y, pos.z - old_offset.z);
+        if old_region.contains(x, y, z) {
+            continue;
+        }
+        let to = Some(&new_region.palette[new_region.blocks[index] as usize]).filter(|s| !is_empty_block(s));
+        record(pos, None, to);
+    }
+
+    let truncated = added + removed + changed > options.max_listed_changes as u64;
+    let changes: Vec<BlockChange> = listed
+        .into_sorted_vec()
+        .into_iter()
+        .map(|c| BlockChange {
+            pos: Vec3i::new(c.key.2, c.key.0, c.key.1),
+            kind: c.kind,
+            from: c.from.map(|s| s.to_string()),
+            to: c.to.map(|s| s.to_string()),
+        })
+        .collect();
+
+    let (palette_removed, palette_added) = palette_differences(&old_region, &new_region);
+    let block_entity_changes = block_entity_differences(&old_region, old_offset, &new_region, new_offset);
+    let bounds = bounds_difference(old, new)?;
+    let material_delta = material_differences(old, new);
+    let summary = material_summary(&material_delta);
+
+    Ok(SchematicDiff {
+        added,
+        removed,
+        changed,
+        unchanged,
+        changes,
+        truncated,
+        palette_removed,
+        palette_added,
+        block_entity_changes,
+        bounds,
+        material_delta,
+        summary,
+    })
+}
+
+fn state_counts(region: &Region) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for (state, count) in region.palette.iter().zip(region.palette_counts()) {
+        if count > 0 && !is_empty_block(state) {
+            *counts.entry(state.to_string()).or_default() += count;
+        }
+    }
+    counts
+}
+
+fn palette_differences(old: &Region, new: &Region) -> (Vec<PaletteCountChange>, Vec<PaletteCountChange>) {
+    let old_counts = state_counts(old);
+    let new_counts = state_counts(new);
+    let removed = old_counts
+        .iter()
+        .filter(|(state, _)| !new_counts.contains_key(*state))
+        .map(|(state, count)| PaletteCountChange { state: state.clone(), old_count: *count, new_count: 0 })
+        .collect();
+    let added = new_counts
+        .iter()
+        .filter(|(state, _)| !old_counts.contains_key(*state))
+        .map(|(state, count)| PaletteCountChange { state: state.clone(), old_count: 0, new_count: *count })
+        .collect();
+    (removed, added)
+}
+
+fn block_entity_differences(old: &Region, old_offset: Vec3i, new: &Region, new_offset: Vec3i) -> Vec<BlockEntityChange> {
+    let index = |region: &Region, offset: Vec3i| -> HashMap<Vec3i, BlockEntity> {
+        region
+            .block_entities
+            .iter()
+            .map(|be| (Vec3i::new(be.pos.x + offset.x, be.pos.y + offset.y, be.pos.z + offset.z), be.clone()))
+            .collect()
+    };
+    let old_entities = index(old, old_offset);
+    let new_entities = index(new, new_offset);
+
+    let mut changes = Vec::new();
+    for (pos, before) in &old_entities {
+        match new_entities.get(pos) {
+            None => changes.push(BlockEntityChange {
+                pos: *pos,
+                kind: ChangeKind::Removed,
+                from_id: Some(before.id.clone()),
+                to_id: None,
+            }),
+            Some(after) if after.id != before.id || after.nbt != before.nbt => changes.push(BlockEntityChange {
+                pos: *pos,
+                kind: ChangeKind::Changed,
+                from_id: Some(before.id.clone()),
+                to_id: Some(after.id.clone()),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (pos, after) in &new_entities {
+        if !old_entities.contains_key(pos) {
+            changes.push(BlockEntityChange {
+                pos: *pos,
+                kind: ChangeKind::Added,
+                from_id: None,
+                to_id: Some(after.id.clone()),
+            });
+        }
+    }
+    changes.sort_by_key(|c| (c.pos.y, c.pos.z, c.pos.x));
+    changes
+}
+
+fn bounds_difference(old: &Schematic, new: &Schematic) -> Result<BoundsDiff, SchematicError> {
+    let (old_min, old_size) = old.bounds()?;
+    let (new_min, new_size) = new.bounds()?;
+    let delta = |a: Vec3i, b: Vec3i| {
+        let axis = |a: i32, b: i32| {
+            a.checked_sub(b)
+                .ok_or_else(|| SchematicError::Invalid("Schematic bounds are too far apart to compare".to_string()))
+        };
+        Ok::<_, SchematicError>(Vec3i::new(axis(a.x, b.x)?, axis(a.y, b.y)?, axis(a.z, b.z)?))
+    };
+    Ok(BoundsDiff {
+        old_min,
+        old_size,
+        new_min,
+        new_size,
+        min_delta: delta(new_min, old_min)?,
+        size_delta: delta(new_size, old_size)?,
+    })
+}
+
+/// 按物品统计的材料变化，按变化量绝对值降序
+fn material_differences(old: &Schematic, new: &Schematic) -> Vec<MaterialDelta> {
+    let old_list = MaterialList::from_schematic(old, MaterialMode::Items);
+    let new_list = MaterialList::from_schematic(new, MaterialMode::Items);
+    let mut ids: Vec<&String> = old_list.entries.iter().chain(&new_list.entries).map(|e| &e.id).collect();
+    ids.sort();
+    ids.dedup();
+
+    let locale = display_locale();
+    let mut deltas: Vec<MaterialDelta> = ids
+        .into_iter()
+        .filter_map(|id| {
+            let old_count = old_list.count_of(id);
+            let new_count = new_list.count_of(id);
+            let delta = new_count as i64 - old_count as i64;
+            (delta != 0).then(|| MaterialDelta {
+                id: id.clone(),
+                name: display_name(id, &locale),
+                old_count,
+                new_count,
+                delta,
+            })
+        })
+        .collect();
+    deltas.sort_by(|a, b| b.delta.abs().cmp(&a.delta.abs()).then_with(|| a.id.cmp(&b.id)));
+    deltas
+}
+
+fn material_summary(deltas: &[MaterialDelta]) -> String {
+    if deltas.is_empty() {
+        return "No material changes".to_string();
+    }
+    let mut parts: Vec<String> = deltas
+        .iter()
+        .take(SUMMARY_ENTRIES)
+        .map(|d| {
+            let name = d.id.strip_prefix("minecraft:").unwrap_or(&d.id);
+            if d.delta > 0 {
+                format!("+{} {}", d.delta, name)
+            } else {
+                format!("\u{2212}{} {}", -d.delta, name)
+            }
+        })
+        .collect();
+    if deltas.len() > SUMMARY_ENTRIES {
+        parts.push(format!("and {} more", deltas.len() - SUMMARY_ENTRIES));
+    }
+    parts.join(", ")
+}
+
+/// 比较两个版本的投影文件，返回 JSON 格式的差异报告
+#[wasm_bindgen]
+pub fn diff_schematic_files(old_data: &[u8], new_data: &[u8], align_to_min_corner: bool) -> Result<String, NeptuniumError> {
+    let old = Schematic::from_bytes(old_data)?;
+    let new = Schematic::from_bytes(new_data)?;
+    let options = DiffOptions { align_to_min_corner, ..DiffOptions::default() };
+    let diff = diff_schematics(&old, &new, &options)?;
+    Ok(serde_json::to_string(&diff).unwrap_or_default())
+}
@@ -1,6 +1,9 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
-use crate::{ValidationResult, ProjectionFile};
+use crate::{
+    ConversionWarning, FormatDetection, InventoryReport, LitematicMetadata, NbtDocument, NeptuniumError, ProjectionFile,
+    SafetyReport, SchematicFormat, ValidationResult,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[wasm_bindgen]
@@ -9,6 +12,15 @@ pub struct FileUploadResult {
     message: String,
     file_id: Option<String>,
     storage_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preview: Option<Vec<u8>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preview_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    safety: Option<SafetyReport>,
+    /// 生成预览时遇到的占位方块，这些方块在预览中以灰色显示
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    preview_warnings: Vec<ConversionWarning>,
 }
 
 #[wasm_bindgen]
@@ -25,6 +37,10 @@ impl FileUploadResult {
             message,
             file_id,
             storage_path,
+            preview: None,
+            preview_path: None,
+            safety: None,
+            preview_warnings: Vec::new(),
         }
     }
 
@@ -47,6 +63,59 @@ impl FileUploadResult {
     pub fn storage_path(&self) -> Option<String> {
         self.storage_path.clone()
     }
+
+    /// 上传时生成的等距预览图 PNG
+    #[wasm_bindgen(getter)]
+    pub fn preview(&self) -> Option<Vec<u8>> {
+        self.preview.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn preview_path(&self) -> Option<String> {
+        self.preview_path.clone()
+    }
+
+    /// 安全扫描报告（JSON）；无法解析为投影时为空
+    #[wasm_bindgen(getter)]
+    pub fn safety_json(&self) -> Option<String> {
+        self.safety.as_ref().and_then(|report| serde_json::to_string(report).ok())
+    }
+
+    /// 预览警告（JSON 数组）
+    #[wasm_bindgen(getter)]
+    pub fn preview_warnings_json(&self) -> String {
+        serde_json::to_string(&self.preview_warnings).unwrap_or_default()
+    }
+
+    /// 扫描结果要求隔离：文件已保存，但在人工审核前不应分享给其他玩家
+    #[wasm_bindgen(getter)]
+    pub fn quarantined(&self) -> bool {
+        self.safety.as_ref().is_some_and(SafetyReport::is_quarantined)
+    }
+}
+
+impl FileUploadResult {
+    pub fn with_preview(mut self, preview: Vec<u8>, preview_path: String) -> FileUploadResult {
+        self.preview = Some(preview);
+        self.preview_path = Some(preview_path);
+        self
+    }
+
+    pub fn with_safety(mut self, safety: SafetyReport) -> FileUploadResult {
+        if safety.is_quarantined() {
+            self.message = "File upload prepared and quarantined for review".to_string();
+        }
+        self.safety = Some(safety);
+        self
+    }
+
+    pub fn safety(&self) -> Option<&SafetyReport> {
+        self.safety.as_ref()
+    }
+
+    pub fn preview_warnings(&self) -> &[ConversionWarning] {
+        &self.preview_warnings
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -57,6 +126,10 @@ pub struct FileMetadata {
     file_type: String,
     mime_type: String,
     checksum: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    litematic: Option<LitematicMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inventory: Option<InventoryReport>,
 }
 
 #[wasm_bindgen]
@@ -75,6 +148,8 @@ impl FileMetadata {
             file_type,
             mime_type,
             checksum,
+            litematic: None,
+            inventory: None,
         }
     }
 
@@ -102,6 +177,64 @@ impl FileMetadata {
     pub fn checksum(&self) -> Option<String> {
         self.checksum.clone()
     }
+
+    /// Litematica 元数据（JSON 字符串），非 .litematic 文件为空
+    #[wasm_bindgen(getter)]
+    pub fn litematic_json(&self) -> Option<String> {
+        self.litematic
+            .as_ref()
+            .map(|metadata| serde_json::to_string(metadata).unwrap_or_default())
+    }
+
+    /// 容器内物品清单（JSON 字符串），投影中没有装有物品的容器时为空
+    #[wasm_bindgen(getter)]
+    pub fn inventory_json(&self) -> Option<String> {
+        self.inventory
+            .as_ref()
+            .map(|inventory| serde_json::to_string(inventory).unwrap_or_default())
+    }
+}
+
+impl FileMetadata {
+    pub fn litematic(&self) -> Option<&LitematicMetadata> {
+        self.litematic.as_ref()
+    }
+
+    pub fn inventory(&self) -> Option<&InventoryReport> {
+        self.inventory.as_ref()
+    }
+}
+
+/// 根据上传的文件内容生成完整元数据；.litematic 文件会附带解析出的 Litematica 元数据
+#[wasm_bindgen]
+pub fn extract_file_metadata(filename: &str, data: &[u8]) -> Result<FileMetadata, NeptuniumError> {
+    let sanitized_filename = crate::sanitize_filename(filename);
+    // 只解压解析一次，格式识别、Litematica 元数据与容器物品共用同一文档；读取失败时仅保留格式识别的原因
+    let document = crate::read_document_with_limits(data, &crate::IngestLimits::current()).ok();
+    let detection = match &document {
+        Some(document) => crate::detect_document(document),
+        None => crate::detect_format(data),
+    };
+
+    let litematic = match (detection.schematic_format(), &document) {
+        (SchematicFormat::Litematica, Some(document)) => Some(LitematicMetadata::from_root(&document.root)?),
+        _ => None,
+    };
+
+    let mut metadata = FileMetadata::new(
+        sanitized_filename.clone(),
+        data.len() as i64,
+        detection.format(),
+        get_mime_type(&sanitized_filename),
+        Some(calculate_file_checksum(data)),
+    );
+    metadata.litematic = litematic;
+    // 容器物品只是附加信息，读取失败不影响元数据提取
+    metadata.inventory = document
+        .and_then(|document| crate::Schematic::from_root(&document.root).ok())
+        .map(|schematic| crate::extract_inventories(&schematic))
+        .filter(|inventory| !inventory.is_empty());
+    Ok(metadata)
 }
 
 #[wasm_bindgen]
@@ -126,6 +259,57 @@ pub fn validate_file_upload(metadata: &FileMetadata) -> ValidationResult {
     ValidationResult::new(true, "Valid file upload".to_string())
 }
 
+/// 在扩展名检查之外，校验文件内容的实际格式与扩展名一致
+#[wasm_bindgen]
+pub fn validate_file_upload_content(metadata: &FileMetadata, data: &[u8]) -> ValidationResult {
+    match read_upload_document(metadata, data) {
+        Ok(_) => ValidationResult::new(true, "Valid file upload".to_string()),
+        Err(validation) => validation,
+    }
+}
+
+/// 只解压解析一次上传内容：检查上限、识别格式并与扩展名比对，返回的文档供后续读取模型、预览与安全扫描复用
+fn read_upload_document(
+    metadata: &FileMetadata,
+    data: &[u8],
+) -> Result<(NbtDocument, FormatDetection), ValidationResult> {
+    let basic_validation = validate_file_upload(metadata);
+    if !basic_validation.is_valid() {
+        return Err(basic_validation);
+    }
+
+    if data.len() as i64 != metadata.file_size() {
+        return Err(ValidationResult::new(false, "File size does not match uploaded data".to_string()));
+    }
+
+    // 解压大小、嵌套深度、声明尺寸等上限先于格式识别检查，给出准确的拒绝原因
+    let document = crate::read_document_with_limits(data, &crate::IngestLimits::current())
+        .map_err(|err| ValidationResult::new(false, err.to_string()))?;
+
+    let detection = crate::detect_document(&document);
+    let detected = detection.schematic_format();
+    if detected == SchematicFormat::Unknown {
+        return Err(ValidationResult::new(
+            false,
+            format!("File content is not a recognised schematic: {}", detection.message()),
+        ));
+    }
+
+    let extension = crate::get_file_extension(&metadata.filename());
+    if SchematicFormat::from_extension(&extension) != Some(detected) {
+        return Err(ValidationResult::new(
+            false,
+            format!(
+                "File content is a {} schematic but the extension is {}",
+                detected.as_str(),
+                extension
+            ),
+        ));
+    }
+
+    Ok((document, detection))
+}
+
 #[wasm_bindgen]
 pub fn prepare_file_upload(
     filename: &str,
@@ -173,6 +357,72 @@ pub fn prepare_file_upload(
     )
 }
 
+/// 与 `prepare_file_upload` 相同，但基于文件内容校验，并以识别出的格式作为 file_type，同时生成预览图
+#[wasm_bindgen]
+pub fn prepare_file_upload_with_content(
+    filename: &str,
+    data: &[u8],
+    upload_ip: &str,
+    user_id: Option<String>,
+) -> FileUploadResult {
+    let sanitized_filename = crate::sanitize_filename(filename);
+    let file_size = data.len() as i64;
+    // 先按扩展名声明的格式记录，校验通过即与识别结果一致
+    let claimed_format = SchematicFormat::from_extension(&crate::get_file_extension(&sanitized_filename))
+        .unwrap_or(SchematicFormat::Unknown);
+
+    let metadata = FileMetadata::new(
+        sanitized_filename.clone(),
+        file_size,
+        claimed_format.as_str().to_string(),
+        get_mime_type(&sanitized_filename),
+        Some(calculate_file_checksum(data)),
+    );
+
+    let (document, detection) = match read_upload_document(&metadata, data) {
+        Ok(parsed) => parsed,
+        Err(validation) => return FileUploadResult::new(false, validation.message(), None, None),
+    };
+
+    // 无法读取方块数据的文件也就无法做安全扫描，直接拒绝而不是跳过扫描放行
+    let schematic = match crate::Schematic::from_root(&document.root) {
+        Ok(schematic) => schematic,
+        Err(err) => return FileUploadResult::new(false, format!("File content could not be read: {}", err), None, None),
+    };
+
+    let anonymous = crate::is_anonymous_upload(user_id.clone());
+    let projection_file = ProjectionFile::new(
+        sanitized_filename,
+        file_size,
+        detection.format(),
+        upload_ip.to_string(),
+        user_id,
+    );
+
+    // 分享前扫描危险内容，匿名上传使用更严格的隔离阈值
+    let result = FileUploadResult::new(
+        true,
+        "File upload prepared successfully".to_string(),
+        Some(projection_file.file_id()),
+        Some(projection_file.storage_path()),
+    )
+    .with_safety(crate::scan_schematic(&schematic, &crate::SafetyOptions::default(), anonymous));
+
+    // 优先使用 Litematica 作者自带的预览图；预览图生成失败不影响上传
+    let embedded_preview =
+        crate::read_preview_image(&document.root).ok().flatten().and_then(|image| image.to_png().ok());
+    if let Some(png) = embedded_preview {
+        return result.with_preview(png, projection_file.preview_path());
+    }
+    match crate::render_preview_png(&schematic, &crate::RenderOptions::default()) {
+        Ok(png) => FileUploadResult {
+            preview_warnings: crate::placeholder_warnings(&schematic, None),
+            ..result.with_preview(png, projection_file.preview_path())
+        },
+        Err(_) => result,
+    }
+}
+
 #[wasm_bindgen]
 pub fn get_mime_type(filename: &str) -> String {
     let extension = crate::get_file_extension(filename);
@@ -183,6 +433,7 @@ pub fn get_mime_type(filename: &str) -> String {
         ".schematic" => "application/octet-stream",
         ".nbt" => "application/octet-stream",
         ".structure" => "application/octet-stream",
+        ".mcstructure" => "application/octet-stream",
         _ => "application/octet-stream",
     }.to_string()
 }
@@ -194,6 +445,13 @@ pub fn generate_storage_path(file_id: &str, filename: &str) -> String {
     format!("projections/{}/{}{}", prefix, file_id, extension)
 }
 
+/// 分块文件存放在原投影路径下：`projections/{prefix}/{file_id}/tiles/{column}_{row}{extension}`
+#[wasm_bindgen]
+pub fn generate_tile_storage_path(file_id: &str, column: i32, row: i32, extension: &str) -> String {
+    let prefix = &file_id[0..2];
+    format!("projections/{}/{}/tiles/{}_{}{}", prefix, file_id, column, row, extension)
+}
+
 #[wasm_bindgen]
 pub fn calculate_file_checksum(data: &[u8]) -> String {
     use sha2::{Sha256, Digest};
@@ -205,6 +463,28 @@ pub fn calculate_file_checksum(data: &[u8]) -> String {
     general_purpose::STANDARD.encode(result)
 }
 
+/// 提取 .litematic 内嵌的作者预览图并编码为 PNG；没有预览图时返回 None
+#[wasm_bindgen]
+pub fn extract_litematic_preview(data: &[u8]) -> Result<Option<Vec<u8>>, NeptuniumError> {
+    let document = NbtDocument::from_bytes(data)?;
+    match crate::read_preview_image(&document.root)? {
+        Some(image) => Ok(Some(image.to_png()?)),
+        None => Ok(None),
+    }
+}
+
+/// 与 `extract_litematic_preview` 相同，但返回可直接用于 <img src> 的 PNG data URI
+#[wasm_bindgen]
+pub fn extract_litematic_preview_data_uri(data: &[u8]) -> Result<Option<String>, NeptuniumError> {
+    Ok(extract_litematic_preview(data)?.map(|png| png_data_uri(&png)))
+}
+
+pub fn png_data_uri(png: &[u8]) -> String {
+    use base64::{Engine as _, engine::general_purpose};
+
+    format!("data:image/png;base64,{}", general_purpose::STANDARD.encode(png))
+}
+
 #[wasm_bindgen]
 pub fn verify_file_checksum(data: &[u8], expected_checksum: &str) -> bool {
     let computed_checksum = calculate_file_checksum(data);
@@ -260,6 +540,12 @@ impl FileFilter {
     }
 }
 
+impl Default for FileFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 pub fn matches_file_filter(file: &ProjectionFile, filter: &FileFilter) -> bool {
     // 检查文件类型
@@ -0,0 +1,303 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::{NbtByteOrder, NbtCompound, NbtCompression, NbtDocument, NbtError, NeptuniumError};
+
+// 基于文件内容（压缩魔数 + 根复合标签结构）识别投影格式
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchematicFormat {
+    Litematica,
+    Sponge,
+    McEdit,
+    Structure,
+    /// Bedrock 版结构方块 (.mcstructure)
+    McStructure,
+    Unknown,
+}
+
+impl SchematicFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SchematicFormat::Litematica => "litematica",
+            SchematicFormat::Sponge => "sponge",
+            SchematicFormat::McEdit => "mcedit",
+            SchematicFormat::Structure => "structure",
+            SchematicFormat::McStructure => "mcstructure",
+            SchematicFormat::Unknown => "unknown",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SchematicFormat> {
+        match name.to_lowercase().as_str() {
+            "litematica" | "litematic" => Some(SchematicFormat::Litematica),
+            "sponge" | "schem" => Some(SchematicFormat::Sponge),
+            "mcedit" | "schematic" => Some(SchematicFormat::McEdit),
+            "structure" | "nbt" => Some(SchematicFormat::Structure),
+            "mcstructure" | "bedrock" => Some(SchematicFormat::McStructure),
+            _ => None,
+        }
+    }
+
+    /// 根据扩展名（含前导点）推断格式
+    pub fn from_extension(extension: &str) -> Option<SchematicFormat> {
+        match extension.to_lowercase().as_str() {
+            ".litematic" => Some(SchematicFormat::Litematica),
+            ".schem" => Some(SchematicFormat::Sponge),
+            ".schematic" => Some(SchematicFormat::McEdit),
+            ".nbt" | ".structure" => Some(SchematicFormat::Structure),
+            ".mcstructure" => Some(SchematicFormat::McStructure),
+            _ => None,
+        }
+    }
+
+    /// 该格式的规范扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SchematicFormat::Litematica => ".litematic",
+            SchematicFormat::Sponge => ".schem",
+            SchematicFormat::McEdit => ".schematic",
+            SchematicFormat::Structure => ".nbt",
+            SchematicFormat::McStructure => ".mcstructure",
+            SchematicFormat::Unknown => "",
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SchematicError {
+    #[error(transparent)]
+    Nbt(#[from] NbtError),
+    #[error("Expected a {expected} schematic, found {found}")]
+    WrongFormat { expected: &'static str, found: &'static str },
+    #[error("Missing or invalid field `{0}`")]
+    MissingField(String),
+    #[error("Invalid schematic data: {0}")]
+    Invalid(String),
+    #[error("Region `{region}` declares a volume of {volume} blocks, exceeding the limit of {limit}")]
+    RegionTooLarge { region: String, volume: i64, limit: i64 },
+    #[error("Schematic declares a total volume of {volume} blocks, exceeding the limit of {limit}")]
+    TotalVolumeTooLarge { volume: i64, limit: i64 },
+    #[error("Region `{region}` palette has {size} entries, exceeding the limit of {limit}")]
+    PaletteTooLarge { region: String, size: usize, limit: usize },
+}
+
+impl SchematicError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            SchematicError::Nbt(err) => err.code(),
+            SchematicError::WrongFormat { .. } => "SCHEMATIC_WRONG_FORMAT",
+            SchematicError::MissingField(_) => "SCHEMATIC_MISSING_FIELD",
+            SchematicError::Invalid(_) => "SCHEMATIC_INVALID",
+            SchematicError::RegionTooLarge { .. } => "SCHEMATIC_REGION_TOO_LARGE",
+            SchematicError::TotalVolumeTooLarge { .. } => "SCHEMATIC_VOLUME_TOO_LARGE",
+            SchematicError::PaletteTooLarge { .. } => "SCHEMATIC_PALETTE_TOO_LARGE",
+        }
+    }
+}
+
+impl From<SchematicError> for NeptuniumError {
+    fn from(err: SchematicError) -> Self {
+        NeptuniumError::new(err.to_string(), err.code().to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[wasm_bindgen]
+pub struct FormatDetection {
+    format: SchematicFormat,
+    confidence: f64,
+    compression: Option<NbtCompression>,
+    version: Option<i32>,
+    message: String,
+}
+
+#[wasm_bindgen]
+impl FormatDetection {
+    #[wasm_bindgen(getter)]
+    pub fn format(&self) -> String {
+        self.format.as_str().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn confidence(&self) -> f64 {
+        self.confidence
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn compression(&self) -> Option<String> {
+        self.compression.map(|c| c.as_str().to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn version(&self) -> Option<i32> {
+        self.version
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_schematic(&self) -> bool {
+        self.format != SchematicFormat::Unknown
+    }
+}
+
+impl FormatDetection {
+    pub fn schematic_format(&self) -> SchematicFormat {
+        self.format
+    }
+
+    fn unknown(compression: Option<NbtCompression>, message: String) -> FormatDetection {
+        FormatDetection { format: SchematicFormat::Unknown, confidence: 0.0, compression, version: None, message }
+    }
+}
+
+// 每种格式的特征键及权重；required 的键缺失即判定不匹配
+struct Signature {
+    format: SchematicFormat,
+    required: &'static [&'static str],
+    optional: &'static [&'static str],
+}
+
+const SIGNATURES: &[Signature] = &[
+    Signature {
+        format: SchematicFormat::Litematica,
+        required: &["Regions"],
+        optional: &["MinecraftDataVersion", "Version", "Metadata"],
+    },
+    Signature {
+        format: SchematicFormat::Sponge,
+        required: &["Palette", "BlockData"],
+        optional: &["Version", "Width", "Height", "Length", "PaletteMax"],
+    },
+    Signature {
+        format: SchematicFormat::McEdit,
+        required: &["Blocks", "Data"],
+        optional: &["Materials", "Width", "Height", "Length"],
+    },
+    Signature {
+        format: SchematicFormat::Structure,
+        required: &["size", "blocks"],
+        optional: &["palette", "DataVersion", "entities"],
+    },
+    Signature {
+        format: SchematicFormat::McStructure,
+        required: &["format_version", "size", "structure"],
+        optional: &["structure_world_origin"],
+    },
+];
+
+/// 对已解析的根复合标签做结构识别，返回 (格式, 置信度, 版本号)
+pub fn detect_from_root(root: &NbtCompound) -> (SchematicFormat, f64, Option<i32>) {
+    // Sponge v3 将所有内容包在 "Schematic" 复合标签内
+    if let Some(inner) = root.get_compound("Schematic") {
+        if inner.contains_key("Version") && (inner.contains_key("Blocks") || inner.contains_key("Width")) {
+            let mut score = 0.6;
+            if inner.get_compound("Blocks").is_some_and(|b| b.contains_key("Palette") && b.contains_key("Data")) {
+                score += 0.3;
+            }
+            if ["Width", "Height", "Length"].iter().all(|k| inner.contains_key(k)) {
+                score += 0.1;
+            }
+            return (SchematicFormat::Sponge, score, inner.get_i32("Version"));
+        }
+    }
+
+    let mut best = (SchematicFormat::Unknown, 0.0, None);
+    for signature in SIGNATURES {
+        if !signature.required.iter().all(|k| root.contains_key(k)) {
+            continue;
+        }
+        let matched = signature.optional.iter().filter(|k| root.contains_key(k)).count();
+        let mut score = 0.6 + 0.4 * matched as f64 / signature.optional.len() as f64;
+
+        // 键名相同但类型不符时降低置信度
+        let type_ok = match signature.format {
+            SchematicFormat::Litematica => root.get_compound("Regions").is_some(),
+            SchematicFormat::Sponge => root.get_compound("Palette").is_some() && root.get_byte_array("BlockData").is_some(),
+            SchematicFormat::McEdit => root.get_byte_array("Blocks").is_some() && root.get_byte_array("Data").is_some(),
+            SchematicFormat::Structure => {
+                root.get_list("size").is_some() && root.get_list("blocks").is_some()
+                    && (root.contains_key("palette") || root.contains_key("palettes"))
+            }
+            SchematicFormat::McStructure => {
+                root.get_list("size").is_some()
+                    && root.get_compound("structure").is_some_and(|s| s.contains_key("block_indices"))
+            }
+            SchematicFormat::Unknown => false,
+        };
+        if !type_ok {
+            score *= 0.5;
+        }
+
+        if score > best.1 {
+            let version = match signature.format {
+                SchematicFormat::Litematica | SchematicFormat::Sponge => root.get_i32("Version"),
+                SchematicFormat::Structure => root.get_i32("DataVersion"),
+                SchematicFormat::McStructure => root.get_i32("format_version"),
+                _ => None,
+            };
+            best = (signature.format, score, version);
+        }
+    }
+    best
+}
+
+pub fn detect_format(data: &[u8]) -> FormatDetection {
+    if data.is_empty() {
+        return FormatDetection::unknown(None, "File is empty".to_string());
+    }
+
+    let compression = NbtCompression::detect(data);
+    if compression == NbtCompression::None && data[0] != crate::TAG_COMPOUND {
+        return FormatDetection::unknown(None, "File is not NBT data".to_string());
+    }
+
+    match NbtDocument::from_bytes(data) {
+        Ok(document) => detect_document(&document),
+        Err(err) => FormatDetection::unknown(Some(compression), err.to_string()),
+    }
+}
+
+/// 识别已解析文档的格式，供已持有文档的调用方避免重复解压解析
+pub fn detect_document(document: &NbtDocument) -> FormatDetection {
+    let compression = document.compression;
+    let (format, mut confidence, version) = detect_from_root(&document.root);
+    if format == SchematicFormat::Unknown {
+        return FormatDetection::unknown(Some(compression), "NBT root does not match any schematic format".to_string());
+    }
+
+    // 各格式的常见封装：Litematica / Sponge / MCEdit 均为 gzip，.mcstructure 为未压缩的小端序
+    let expected_wrapping = match format {
+        SchematicFormat::Structure => true,
+        SchematicFormat::McStructure => document.byte_order == NbtByteOrder::Little,
+        _ => compression == NbtCompression::Gzip,
+    };
+    if !expected_wrapping {
+        confidence *= 0.9;
+    }
+
+    let confidence = (confidence * 100.0).round() / 100.0;
+    FormatDetection {
+        format,
+        confidence,
+        compression: Some(compression),
+        version,
+        message: format!("Detected {} schematic", format.as_str()),
+    }
+}
+
+#[wasm_bindgen]
+pub fn detect_file_format(data: &[u8]) -> FormatDetection {
+    detect_format(data)
+}
+
+#[wasm_bindgen]
+pub fn format_detection_to_json(detection: &FormatDetection) -> String {
+    serde_json::to_string(detection).unwrap_or_default()
+}
+
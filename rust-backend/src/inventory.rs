@@ -0,0 +1,238 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::{
+    normalize_id, BlockEntity, MaterialEntry, MaterialList, MaterialMode, NbtCompound, NbtTag, NeptuniumError,
+    Region, Schematic, Vec3i,
+};
+
+// 容器内物品提取：箱子、木桶、潜影盒、漏斗、发射器等方块实体中的物品，含潜影盒等嵌套内容
+
+/// 单件方块实体物品字段：唱片机、讲台、饰纹陶罐
+const SINGLE_ITEM_KEYS: &[&str] = &["RecordItem", "Book", "item"];
+
+// 嵌套层数上限，防止恶意构造的物品数据无限套娃
+const MAX_ITEM_NESTING: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemStack {
+    pub id: String,
+    pub count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_name: Option<String>,
+    /// 潜影盒、收纳袋等物品内部的物品
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contents: Vec<ItemStack>,
+}
+
+impl ItemStack {
+    /// 从物品 NBT 读取；同时支持 1.20.5 之前的 `Count`/`tag` 与之后的 `count`/`components` 结构
+    pub fn from_nbt(nbt: &NbtCompound) -> Option<ItemStack> {
+        ItemStack::read(nbt, 0)
+    }
+
+    fn read(nbt: &NbtCompound, depth: usize) -> Option<ItemStack> {
+        let id = match nbt.get("id")? {
+            NbtTag::String(id) => normalize_id(id),
+            // 1.8 之前的数字物品 id
+            other => format!("legacy:{}", other.as_i64()?),
+        };
+        if id == "minecraft:air" {
+            return None;
+        }
+        let count = nbt.get_i64("count").or_else(|| nbt.get_i64("Count")).unwrap_or(1);
+        if count <= 0 {
+            return None;
+        }
+
+        let components = nbt.get_compound("components");
+        let tag = nbt.get_compound("tag");
+        let custom_name = components
+            .and_then(|c| c.get("minecraft:custom_name"))
+            .or_else(|| tag.and_then(|t| t.get_compound("display")).and_then(|d| d.get("Name")))
+            .and_then(text_component);
+
+        let mut contents = Vec::new();
+        if depth < MAX_ITEM_NESTING {
+            if let Some(container) = components.and_then(|c| c.get_list("minecraft:container")) {
+                for entry in container.compounds() {
+                    if let Some(mut item) = entry.get_compound("item").and_then(|i| ItemStack::read(i, depth + 1)) {
+                        item.slot = entry.get_i64("slot").map(|s| s as i32);
+                        contents.push(item);
+                    }
+                }
+            }
+            if let Some(bundle) = components.and_then(|c| c.get_list("minecraft:bundle_contents")) {
+                contents.extend(bundle.compounds().filter_map(|i| ItemStack::read(i, depth + 1)));
+            }
+            if let Some(items) = tag.and_then(|t| t.get_compound("BlockEntityTag")).and_then(|b| b.get_list("Items")) {
+                contents.extend(items.compounds().filter_map(|i| ItemStack::read(i, depth + 1)));
+            }
+            if let Some(items) = tag.and_then(|t| t.get_list("Items")) {
+                contents.extend(items.compounds().filter_map(|i| ItemStack::read(i, depth + 1)));
+            }
+        }
+
+        Some(ItemStack {
+            id,
+            count: count as u64,
+            slot: nbt.get_i64("Slot").map(|s| s as i32),
+            custom_name,
+            contents,
+        })
+    }
+
+    /// 计入自身与全部嵌套内容；嵌套内容按外层堆叠数量倍增，恶意数量饱和而不溢出
+    fn add_to(&self, totals: &mut HashMap<String, u64>, multiplier: u64) {
+        let count = self.count.saturating_mul(multiplier);
+        let total = totals.entry(self.id.clone()).or_default();
+        *total = total.saturating_add(count);
+        for item in &self.contents {
+            item.add_to(totals, count);
+        }
+    }
+}
+
+/// 文本组件转为纯文本：JSON 字符串（1.21.5 之前）或 NBT 复合标签（之后）
+fn text_component(tag: &NbtTag) -> Option<String> {
+    let text = match tag {
+        NbtTag::String(raw) => match serde_json::from_str::<serde_json::Value>(raw) {
+            Ok(value) => json_text(&value),
+            Err(_) => raw.clone(),
+        },
+        NbtTag::Compound(compound) => nbt_text(compound),
+        NbtTag::List(list) => list.iter().filter_map(text_component).collect(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+fn json_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(parts) => parts.iter().map(json_text).collect(),
+        serde_json::Value::Object(object) => {
+            let mut text = object
+                .get("text")
+                .or_else(|| object.get("translate"))
+                .and_then(|t| t.as_str())
+                .unwrap_or_default()
+                .to_string();
+            if let Some(serde_json::Value::Array(extra)) = object.get("extra") {
+                text.extend(extra.iter().map(json_text));
+            }
+            text
+        }
+        _ => String::new(),
+    }
+}
+
+fn nbt_text(compound: &NbtCompound) -> String {
+    let mut text = compound
+        .get_str("text")
+        .or_else(|| compound.get_str("translate"))
+        .unwrap_or_default()
+        .to_string();
+    if let Some(extra) = compound.get_list("extra") {
+        text.extend(extra.iter().filter_map(text_component));
+    }
+    text
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerInventory {
+    pub region: String,
+    /// 投影坐标系中的位置（区域原点 + 区域内位置）
+    pub pos: Vec3i,
+    /// 方块实体 id；缺失时取该位置的方块 id
+    pub container: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_name: Option<String>,
+    pub items: Vec<ItemStack>,
+    /// 本容器内按物品汇总（含嵌套内容）
+    pub totals: Vec<MaterialEntry>,
+    pub total_items: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InventoryReport {
+    pub containers: Vec<ContainerInventory>,
+    /// 所有容器按物品汇总
+    pub totals: Vec<MaterialEntry>,
+    pub total_items: u64,
+}
+
+impl InventoryReport {
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty()
+    }
+}
+
+/// 读取单个方块实体中的物品；不是容器或容器为空时返回 None
+pub fn container_inventory(region: &Region, block_entity: &BlockEntity) -> Option<ContainerInventory> {
+    let nbt = &block_entity.nbt;
+    let mut items: Vec<ItemStack> = nbt
+        .get_list("Items")
+        .map(|list| list.compounds().filter_map(ItemStack::from_nbt).collect())
+        .unwrap_or_default();
+    for key in SINGLE_ITEM_KEYS {
+        if let Some(item) = nbt.get_compound(key).and_then(ItemStack::from_nbt) {
+            items.push(item);
+        }
+    }
+    if items.is_empty() {
+        return None;
+    }
+
+    let mut counts = HashMap::new();
+    for item in &items {
+        item.add_to(&mut counts, 1);
+    }
+    let totals = MaterialList::from_counts(counts, MaterialMode::Items);
+    let pos = block_entity.pos;
+    let container = if !block_entity.id.is_empty() {
+        normalize_id(&block_entity.id)
+    } else if region.contains(pos.x, pos.y, pos.z) {
+        region.block(pos.x, pos.y, pos.z).name.clone()
+    } else {
+        String::new()
+    };
+    Some(ContainerInventory {
+        region: region.name.clone(),
+        pos: Vec3i::new(region.origin.x + pos.x, region.origin.y + pos.y, region.origin.z + pos.z),
+        container,
+        custom_name: nbt.get("CustomName").and_then(text_component),
+        items,
+        totals: totals.entries,
+        total_items: totals.total,
+    })
+}
+
+/// 提取投影中所有容器的物品，并按物品汇总
+pub fn extract_inventories(schematic: &Schematic) -> InventoryReport {
+    let mut containers: Vec<ContainerInventory> = schematic
+        .regions
+        .iter()
+        .flat_map(|region| region.block_entities.iter().filter_map(move |be| container_inventory(region, be)))
+        .collect();
+    containers.sort_by_key(|c| (c.pos.y, c.pos.z, c.pos.x));
+
+    let mut counts: HashMap<String, u64> = HashMap::new();
+    for container in &containers {
+        for entry in &container.totals {
+            let count = counts.entry(entry.id.clone()).or_default();
+            *count = count.saturating_add(entry.count);
+        }
+    }
+    let totals = MaterialList::from_counts(counts, MaterialMode::Items);
+    InventoryReport { containers, totals: totals.entries, total_items: totals.total }
+}
+
+// 导出的函数
+#[wasm_bindgen]
+pub fn extract_container_inventories(data: &[u8]) -> Result<String, NeptuniumError> {
+    let schematic = Schematic::from_bytes(data)?;
+    Ok(serde_json::to_string(&extract_inventories(&schematic)).unwrap_or_default())
+}
@@ -0,0 +1,491 @@
+use wasm_bindgen::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use crate::{normalize_id, BlockEntity, BlockState, NbtCompound, NbtTag, Region, Vec3i};
+
+// 1.12 数字方块 id → 1.13 扁平化方块状态，以及依赖方块实体/相邻方块的修正
+
+/// 扁平化后的方块按 1.13 (DataVersion 1519) 处理
+pub const FLATTENED_DATA_VERSION: i32 = 1519;
+
+const LEGACY_BLOCKS: &str = include_str!("data/legacy_blocks.txt");
+
+const COLORS: [&str; 16] = [
+    "white", "orange", "magenta", "light_blue", "yellow", "lime", "pink", "gray",
+    "light_gray", "cyan", "purple", "blue", "brown", "green", "red", "black",
+];
+
+// 1.13 之后改名的方块，写回旧格式时先换回 1.13 名称
+const MODERN_ALIASES: &[(&str, &str)] = &[
+    ("minecraft:oak_sign", "minecraft:sign"),
+    ("minecraft:oak_wall_sign", "minecraft:wall_sign"),
+    ("minecraft:smooth_stone_slab", "minecraft:stone_slab"),
+    ("minecraft:dirt_path", "minecraft:grass_path"),
+    ("minecraft:short_grass", "minecraft:grass"),
+    ("minecraft:water_cauldron", "minecraft:cauldron"),
+];
+
+// 1.11 之前的方块实体 id
+const LEGACY_BLOCK_ENTITY_IDS: &[(&str, &str)] = &[
+    ("Airportal", "end_portal"),
+    ("Banner", "banner"),
+    ("Beacon", "beacon"),
+    ("Cauldron", "brewing_stand"),
+    ("Chest", "chest"),
+    ("Comparator", "comparator"),
+    ("Control", "command_block"),
+    ("DLDetector", "daylight_detector"),
+    ("Dropper", "dropper"),
+    ("EnchantTable", "enchanting_table"),
+    ("EndGateway", "end_gateway"),
+    ("EnderChest", "ender_chest"),
+    ("FlowerPot", "flower_pot"),
+    ("Furnace", "furnace"),
+    ("Hopper", "hopper"),
+    ("MobSpawner", "mob_spawner"),
+    ("Music", "noteblock"),
+    ("Piston", "piston"),
+    ("RecordPlayer", "jukebox"),
+    ("Sign", "sign"),
+    ("Skull", "skull"),
+    ("Structure", "structure_block"),
+    ("Trap", "dispenser"),
+];
+
+// 花盆中物品的旧版名称与数字 id
+const POT_ITEM_IDS: &[(&str, u16)] = &[
+    ("sapling", 6),
+    ("tallgrass", 31),
+    ("deadbush", 32),
+    ("yellow_flower", 37),
+    ("red_flower", 38),
+    ("brown_mushroom", 39),
+    ("red_mushroom", 40),
+    ("cactus", 81),
+];
+
+// 按旧版 SkullType 顺序排列的 (落地, 挂墙) 头颅
+const SKULL_KINDS: [(&str, &str); 6] = [
+    ("skeleton_skull", "skeleton_wall_skull"),
+    ("wither_skeleton_skull", "wither_skeleton_wall_skull"),
+    ("zombie_head", "zombie_wall_head"),
+    ("player_head", "player_wall_head"),
+    ("creeper_head", "creeper_wall_head"),
+    ("dragon_head", "dragon_wall_head"),
+];
+
+// 这些方块在 1.12 中由上下两格共同记录状态
+const DOUBLE_PLANTS: [&str; 6] = ["sunflower", "lilac", "tall_grass", "large_fern", "rose_bush", "peony"];
+
+/// 旧版 (id, 数据值)
+type LegacyId = (u16, u8);
+
+struct LegacyTable {
+    forward: HashMap<LegacyId, BlockState>,
+    /// 方块名 → 表中出现的全部 (状态, id, 数据值)，按表顺序
+    reverse: HashMap<String, Vec<(BlockState, LegacyId)>>,
+}
+
+fn table() -> &'static LegacyTable {
+    static TABLE: OnceLock<LegacyTable> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut forward = HashMap::new();
+        let mut reverse: HashMap<String, Vec<(BlockState, LegacyId)>> = HashMap::new();
+        for line in LEGACY_BLOCKS.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let Some((key, state)) = line.split_once(' ') else { continue };
+            let Some((id, meta)) = key.split_once(':') else { continue };
+            let (Ok(id), Ok(meta), Ok(state)) = (id.parse::<u16>(), meta.parse::<u8>(), BlockState::parse(state)) else {
+                continue;
+            };
+            reverse.entry(state.name.clone()).or_default().push((state.clone(), (id, meta)));
+            forward.insert((id, meta), state);
+        }
+        LegacyTable { forward, reverse }
+    })
+}
+
+/// 查表得到扁平化方块状态；表中没有该数据值时与原版一样回退到数据值 0
+pub fn flatten_legacy_block(id: u16, meta: u8) -> Option<BlockState> {
+    let forward = &table().forward;
+    forward.get(&(id, meta & 0x0f)).or_else(|| forward.get(&(id, 0))).cloned()
+}
+
+/// 方块状态在 1.12 中最接近的 (id, 数据值)；1.12 不存在的方块返回 None
+pub fn unflatten_block(state: &BlockState) -> Option<(u16, u8)> {
+    let name = MODERN_ALIASES
+        .iter()
+        .find(|(modern, _)| *modern == state.name)
+        .map(|(_, legacy)| *legacy)
+        .unwrap_or(&state.name);
+    // 高花上半格在 1.12 中不区分种类
+    if state.property("half") == Some("upper") && DOUBLE_PLANTS.contains(&state.path()) {
+        return Some((175, 8));
+    }
+    let candidates = table().reverse.get(name)?;
+
+    // half / part / type 决定了旧版数据值的含义，优先在取值相同的候选中选择
+    let anchored: Vec<_> = candidates
+        .iter()
+        .filter(|(candidate, _)| {
+            ["half", "part", "type"].iter().all(|key| match (candidate.property(key), state.property(key)) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            })
+        })
+        .collect();
+    let pool = if anchored.is_empty() { candidates.iter().collect() } else { anchored };
+
+    let mut best: Option<(usize, (u16, u8))> = None;
+    for (candidate, key) in pool {
+        let score = candidate.properties.iter().filter(|(k, v)| state.property(k) == Some(v.as_str())).count();
+        if best.is_none_or(|(top, _)| score > top) {
+            best = Some((score, *key));
+        }
+    }
+    best.map(|(_, key)| key)
+}
+
+/// 旧版方块实体 id 转为带命名空间的形式
+pub fn normalize_legacy_block_entity_id(id: &str) -> String {
+    let id = LEGACY_BLOCK_ENTITY_IDS
+        .iter()
+        .find(|(legacy, _)| *legacy == id)
+        .map(|(_, modern)| *modern)
+        .unwrap_or(id);
+    normalize_id(id)
+}
+
+/// 扁平化后需要结合方块实体与相邻方块才能确定的状态：
+/// 床/旗帜颜色、头颅种类、花盆内容、音符盒音高，以及门和高花的上下半格
+pub(crate) fn fix_flattened_region(region: &mut Region) {
+    for block_entity in region.block_entities.iter_mut() {
+        block_entity.id = normalize_legacy_block_entity_id(&block_entity.id);
+    }
+
+    let mut removed = HashSet::new();
+    for i in 0..region.block_entities.len() {
+        let pos = region.block_entities[i].pos;
+        if !region.contains(pos.x, pos.y, pos.z) {
+            continue;
+        }
+        let state = region.block(pos.x, pos.y, pos.z).clone();
+        let nbt = &mut region.block_entities[i].nbt;
+        let fixed = match state.path() {
+            "red_bed" => {
+                let color = nbt.remove("color").and_then(|t| t.as_i64()).unwrap_or(14);
+                Some(recolor(&state, "red_bed", &format!("{}_bed", color_name(color))))
+            }
+            "white_banner" | "white_wall_banner" => {
+                let base = nbt.remove("Base").and_then(|t| t.as_i64()).unwrap_or(15);
+                flip_pattern_colors(nbt);
+                Some(recolor(&state, "white", color_name(15 - (base & 15))))
+            }
+            "skeleton_skull" | "skeleton_wall_skull" => {
+                let kind = nbt.remove("SkullType").and_then(|t| t.as_i64()).unwrap_or(0);
+                let rotation = nbt.remove("Rot").and_then(|t| t.as_i64()).unwrap_or(0);
+                Some(skull_state(&state, kind, rotation))
+            }
+            "flower_pot" => {
+                removed.insert(i);
+                potted_plant(nbt.get("Item"), nbt.get_i32("Data").unwrap_or(0))
+            }
+            "note_block" => {
+                removed.insert(i);
+                let note = nbt.get_i32("note").unwrap_or(0).clamp(0, 24);
+                Some(state.clone().with_property("note", note.to_string()))
+            }
+            _ => None,
+        };
+        if let Some(fixed) = fixed {
+            region.set_block(pos.x, pos.y, pos.z, &fixed);
+        }
+    }
+    if !removed.is_empty() {
+        let mut index = 0;
+        region.block_entities.retain(|_| {
+            index += 1;
+            !removed.contains(&(index - 1))
+        });
+    }
+
+    fix_double_blocks(region);
+    region.compact_palette();
+}
+
+/// 写回 1.12 前的逆向修正：把 1.13 起由方块名/属性表示的颜色、头颅种类、花盆内容与音高
+/// 还原为旧版方块状态加方块实体数据
+pub(crate) fn unfix_for_legacy(region: &mut Region) {
+    let mut extra: HashMap<u32, (String, NbtCompound)> = HashMap::new();
+    for i in 0..region.palette.len() {
+        if let Some((state, id, nbt)) = legacy_block_entity_for(&region.palette[i]) {
+            region.palette[i] = state;
+            extra.insert(i as u32, (id, nbt));
+        }
+    }
+    if extra.is_empty() {
+        return;
+    }
+
+    let mut existing: HashMap<Vec3i, usize> =
+        region.block_entities.iter().enumerate().map(|(i, be)| (be.pos, i)).collect();
+    for index in 0..region.blocks.len() {
+        let Some((id, nbt)) = extra.get(&region.blocks[index]) else { continue };
+        let pos = region.position(index);
+        match existing.get(&pos) {
+            Some(&i) => {
+                let target = &mut region.block_entities[i].nbt;
+                if id == "minecraft:banner" {
+                    flip_pattern_colors(target);
+                }
+                for (key, value) in nbt.iter() {
+                    target.insert(key, value.clone());
+                }
+            }
+            None => {
+                existing.insert(pos, region.block_entities.len());
+                region.block_entities.push(BlockEntity { pos, id: id.clone(), nbt: nbt.clone() });
+            }
+        }
+    }
+}
+
+fn legacy_block_entity_for(state: &BlockState) -> Option<(BlockState, String, NbtCompound)> {
+    let path = state.path();
+    let mut nbt = NbtCompound::new();
+    let rename = |to: &str| {
+        let mut renamed = state.clone();
+        renamed.name = normalize_id(to);
+        renamed
+    };
+
+    if let Some(color) = path.strip_suffix("_bed").and_then(color_index) {
+        nbt.insert("color", NbtTag::Int(color));
+        return Some((rename("red_bed"), "minecraft:bed".to_string(), nbt));
+    }
+    for (suffix, legacy) in [("_wall_banner", "white_wall_banner"), ("_banner", "white_banner")] {
+        if let Some(color) = path.strip_suffix(suffix).and_then(color_index) {
+            nbt.insert("Base", NbtTag::Int(15 - color));
+            return Some((rename(legacy), "minecraft:banner".to_string(), nbt));
+        }
+    }
+    if let Some(kind) = SKULL_KINDS.iter().position(|(floor, _)| *floor == path) {
+        let rotation = state.property("rotation").and_then(|r| r.parse::<i8>().ok()).unwrap_or(0);
+        nbt.insert("SkullType", NbtTag::Byte(kind as i8));
+        nbt.insert("Rot", NbtTag::Byte(rotation));
+        return Some((BlockState::new("skeleton_skull").with_property("rotation", "0"), "minecraft:skull".to_string(), nbt));
+    }
+    if let Some(kind) = SKULL_KINDS.iter().position(|(_, wall)| *wall == path) {
+        nbt.insert("SkullType", NbtTag::Byte(kind as i8));
+        return Some((rename("skeleton_wall_skull"), "minecraft:skull".to_string(), nbt));
+    }
+    if let Some(plant) = path.strip_prefix("potted_") {
+        let (id, meta) = unflatten_block(&BlockState::new(plant))?;
+        let (item, _) = POT_ITEM_IDS.iter().find(|(_, legacy)| *legacy == id)?;
+        nbt.insert("Item", NbtTag::String(format!("minecraft:{}", item)));
+        nbt.insert("Data", NbtTag::Int(i32::from(meta)));
+        return Some((BlockState::new("flower_pot"), "minecraft:flower_pot".to_string(), nbt));
+    }
+    if path == "note_block" {
+        let note = state.property("note").and_then(|n| n.parse::<i8>().ok()).unwrap_or(0);
+        nbt.insert("note", NbtTag::Byte(note));
+        return Some((state.clone().with_property("note", "0"), "minecraft:noteblock".to_string(), nbt));
+    }
+    None
+}
+
+fn color_index(color: &str) -> Option<i32> {
+    COLORS.iter().position(|c| *c == color).map(|i| i as i32)
+}
+
+/// 旧版旗帜颜色按染料数据值存储，与羊毛颜色顺序相反
+fn flip_pattern_colors(nbt: &mut NbtCompound) {
+    if let Some(NbtTag::List(patterns)) = nbt.get_mut("Patterns") {
+        for pattern in patterns.values.iter_mut() {
+            if let NbtTag::Compound(pattern) = pattern {
+                if let Some(color) = pattern.get_i32("Color") {
+                    pattern.insert("Color", NbtTag::Int(15 - (color & 15)));
+                }
+            }
+        }
+    }
+}
+
+fn color_name(index: i64) -> &'static str {
+    COLORS[(index & 15) as usize]
+}
+
+fn recolor(state: &BlockState, from: &str, to: &str) -> BlockState {
+    let mut fixed = state.clone();
+    fixed.name = normalize_id(&state.path().replacen(from, to, 1));
+    fixed
+}
+
+fn skull_state(state: &BlockState, kind: i64, rotation: i64) -> BlockState {
+    let (floor, wall) = SKULL_KINDS.get(kind as usize).copied().unwrap_or(SKULL_KINDS[0]);
+    if state.path() == "skeleton_wall_skull" {
+        let mut fixed = state.clone();
+        fixed.name = normalize_id(wall);
+        fixed
+    } else {
+        BlockState::new(floor).with_property("rotation", (rotation & 15).to_string())
+    }
+}
+
+/// 花盆的 Item 可能是字符串 id 或数字 id
+fn potted_plant(item: Option<&NbtTag>, data: i32) -> Option<BlockState> {
+    let id = match item? {
+        NbtTag::String(name) => {
+            let name = name.strip_prefix("minecraft:").unwrap_or(name);
+            POT_ITEM_IDS.iter().find(|(legacy, _)| *legacy == name).map(|(_, id)| *id)?
+        }
+        tag => tag.as_i64().and_then(|id| u16::try_from(id).ok())?,
+    };
+    let plant = flatten_legacy_block(id, data as u8)?;
+    let pottable = matches!(
+        plant.path(),
+        "dandelion" | "poppy" | "blue_orchid" | "allium" | "azure_bluet" | "red_tulip" | "orange_tulip"
+            | "white_tulip" | "pink_tulip" | "oxeye_daisy" | "brown_mushroom" | "red_mushroom" | "cactus"
+            | "dead_bush" | "fern"
+    ) || plant.path().ends_with("_sapling");
+    pottable.then(|| BlockState::new(format!("potted_{}", plant.path())))
+}
+
+/// 1.12 的门下半格记录朝向/开关，上半格记录铰链/充能；高花只有下半格记录种类
+fn fix_double_blocks(region: &mut Region) {
+    let candidates: HashSet<u32> = region
+        .palette
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.property("half") == Some("lower") && (s.path().ends_with("_door") || DOUBLE_PLANTS.contains(&s.path())))
+        .map(|(i, _)| i as u32)
+        .collect();
+    if candidates.is_empty() {
+        return;
+    }
+
+    let mut updates: Vec<(Vec3i, BlockState)> = Vec::new();
+    for (index, palette_index) in region.blocks.iter().enumerate() {
+        if !candidates.contains(palette_index) {
+            continue;
+        }
+        let pos = region.position(index);
+        if !region.contains(pos.x, pos.y + 1, pos.z) {
+            continue;
+        }
+        let lower = &region.palette[*palette_index as usize];
+        let upper = region.block(pos.x, pos.y + 1, pos.z);
+        if upper.property("half") != Some("upper") {
+            continue;
+        }
+        if lower.path().ends_with("_door") {
+            if upper.name != lower.name {
+                continue;
+            }
+            let mut fixed_lower = lower.clone();
+            let mut fixed_upper = upper.clone();
+            for key in ["hinge", "powered"] {
+                if let Some(value) = upper.property(key) {
+                    fixed_lower.properties.insert(key.to_string(), value.to_string());
+                }
+            }
+            for key in ["facing", "open"] {
+                if let Some(value) = lower.property(key) {
+                    fixed_upper.properties.insert(key.to_string(), value.to_string());
+                }
+            }
+            updates.push((pos, fixed_lower));
+            updates.push((Vec3i::new(pos.x, pos.y + 1, pos.z), fixed_upper));
+        } else if DOUBLE_PLANTS.contains(&upper.path()) {
+            let mut fixed_upper = lower.clone();
+            fixed_upper.properties.insert("half".to_string(), "upper".to_string());
+            updates.push((Vec3i::new(pos.x, pos.y + 1, pos.z), fixed_upper));
+        }
+    }
+    for (pos, state) in updates {
+        region.set_block(pos.x, pos.y, pos.z, &state);
+    }
+}
+
+/// 查询旧版数字 id 对应的方块状态字符串，未知 id 返回空字符串
+#[wasm_bindgen]
+pub fn legacy_block_to_state(id: u16, meta: u8) -> String {
+    flatten_legacy_block(id, meta).map(|state| state.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MaterialList, MaterialMode, Schematic, SchematicFormat};
+
+    fn column(states: &[BlockState]) -> Region {
+        let mut region = Region::new("Main", Vec3i::new(0, 0, 0), Vec3i::new(1, states.len() as i32, 1)).unwrap();
+        for (y, state) in states.iter().enumerate() {
+            region.set_block(0, y as i32, 0, state);
+        }
+        region
+    }
+
+    #[test]
+    fn table_lookups_fall_back_to_data_value_zero() {
+        assert_eq!(flatten_legacy_block(1, 3), Some(BlockState::new("minecraft:diorite")));
+        assert_eq!(flatten_legacy_block(35, 14).map(|s| s.name), Some("minecraft:red_wool".to_string()));
+        assert_eq!(flatten_legacy_block(4, 7), Some(BlockState::new("minecraft:cobblestone")));
+        assert_eq!(flatten_legacy_block(253, 0), None);
+        assert_eq!(legacy_block_to_state(5, 1), "minecraft:spruce_planks");
+    }
+
+    #[test]
+    fn every_table_entry_maps_back_to_an_id_with_the_same_state() {
+        for (&(id, meta), state) in &table().forward {
+            let key = unflatten_block(state).unwrap_or_else(|| panic!("{}:{} {}", id, meta, state));
+            assert_eq!(flatten_legacy_block(key.0, key.1).as_ref(), Some(state), "{}:{}", id, meta);
+        }
+    }
+
+    #[test]
+    fn renamed_blocks_use_their_1_13_id() {
+        assert_eq!(unflatten_block(&BlockState::new("minecraft:dirt_path")), Some((208, 0)));
+        assert_eq!(unflatten_block(&BlockState::new("minecraft:short_grass")), Some((31, 1)));
+        assert_eq!(unflatten_block(&BlockState::new("minecraft:copper_block")), None);
+        assert_eq!(normalize_legacy_block_entity_id("Chest"), "minecraft:chest");
+        assert_eq!(normalize_legacy_block_entity_id("minecraft:hopper"), "minecraft:hopper");
+    }
+
+    #[test]
+    fn bed_colour_and_door_halves_are_resolved_after_flattening() {
+        let bed = flatten_legacy_block(26, 2).unwrap();
+        let lower = flatten_legacy_block(64, 1).unwrap();
+        let upper = flatten_legacy_block(64, 9).unwrap();
+        let mut region = column(&[bed, lower, upper]);
+        let mut nbt = NbtCompound::new();
+        nbt.insert("color", NbtTag::Int(11));
+        region.block_entities.push(BlockEntity { pos: Vec3i::new(0, 0, 0), id: "Bed".to_string(), nbt });
+        fix_flattened_region(&mut region);
+
+        assert_eq!(region.block(0, 0, 0).name, "minecraft:blue_bed");
+        assert!(region.block_entities[0].nbt.is_empty());
+        for y in [1, 2] {
+            let door = region.block(0, y, 0);
+            assert_eq!((door.property("facing"), door.property("hinge")), (Some("south"), Some("right")), "{}", y);
+        }
+
+        unfix_for_legacy(&mut region);
+        assert_eq!(region.block(0, 0, 0).name, "minecraft:red_bed");
+        assert_eq!(region.block_entities[0].nbt.get_i32("color"), Some(11));
+    }
+
+    #[test]
+    fn unknown_ids_become_placeholders_that_are_counted_and_flagged() {
+        let placeholder = crate::legacy_block_state(253, 3);
+        assert_eq!(placeholder.to_string(), "legacy:253[data=3]");
+        assert_eq!(crate::legacy_id_for(&placeholder), Some((253, 3)));
+
+        let mut schematic = Schematic::new("Old", 0, SchematicFormat::McEdit);
+        schematic.regions.push(column(&[placeholder.clone(), placeholder, BlockState::new("minecraft:stone")]));
+        let list = MaterialList::from_schematic(&schematic, MaterialMode::BlockStates);
+        assert_eq!(list.count_of("legacy:253[data=3]"), 2);
+        assert_eq!(list.warnings.len(), 1);
+        assert_eq!(list.warnings[0].code, "legacy_block");
+        assert!(crate::placeholder_warnings(&schematic, Some(crate::ExportFormat::McEdit)).is_empty());
+    }
+}
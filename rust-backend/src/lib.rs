@@ -1,7 +1,7 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use sha2::{Sha256, Digest};
 use base64::{Engine as _, engine::general_purpose};
 
@@ -9,11 +9,69 @@ mod config;
 mod utils;
 mod auth;
 mod files;
+mod database;
+mod limits;
+mod nbt;
+mod stream;
+mod formats;
+mod litematic;
+mod schematic;
+mod sponge;
+mod mcedit;
+mod legacy;
+mod structure;
+mod bedrock;
+mod mcstructure;
+mod anvil;
+mod verify;
+mod materials;
+mod recipes;
+mod locale;
+mod inventory;
+mod safety;
+mod convert;
+mod upgrade;
+mod transform;
+mod replace;
+mod crop;
+mod merge;
+mod render;
+mod mapart;
+mod diff;
 
 pub use config::*;
 pub use utils::*;
 pub use auth::*;
 pub use files::*;
+pub use database::*;
+pub use limits::*;
+pub use nbt::*;
+pub use stream::*;
+pub use formats::*;
+pub use litematic::*;
+pub use schematic::*;
+pub use sponge::*;
+pub use mcedit::*;
+pub use legacy::*;
+pub use structure::*;
+pub use bedrock::*;
+pub use mcstructure::*;
+pub use anvil::*;
+pub use verify::*;
+pub use materials::*;
+pub use recipes::*;
+pub use locale::*;
+pub use inventory::*;
+pub use safety::*;
+pub use convert::*;
+pub use upgrade::*;
+pub use transform::*;
+pub use replace::*;
+pub use crop::*;
+pub use merge::*;
+pub use render::*;
+pub use mapart::*;
+pub use diff::*;
 
 // 初始化 WASM 模块
 #[wasm_bindgen(start)]
@@ -130,6 +188,18 @@ impl ProjectionFile {
         self.storage_path.clone()
     }
 
+    /// 预览图存储路径，与投影文件同目录分片
+    #[wasm_bindgen(getter)]
+    pub fn preview_path(&self) -> String {
+        format!("previews/{}/{}.png", &self.file_id[0..2], self.file_id)
+    }
+
+    /// 建造进度报告存储路径 `progress/{file_id 前两位}/{file_id}.json`，与预览图一样按 file_id 前缀分片
+    #[wasm_bindgen(getter)]
+    pub fn progress_path(&self) -> String {
+        format!("progress/{}/{}.json", &self.file_id[0..2], self.file_id)
+    }
+
     #[wasm_bindgen(getter)]
     pub fn filename(&self) -> String {
         self.filename.clone()
@@ -0,0 +1,187 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use crate::schematic::read_int_triple;
+use crate::{
+    detect_from_root, NbtCompound, NbtDocument, NeptuniumError, SchematicError, SchematicFormat,
+    ValidationResult, Vec3i, MAX_REGION_VOLUME, MCSTRUCTURE_BLOCK_LIMIT,
+};
+
+// 上传文件的资源上限：在解压、解析 NBT 以及按声明尺寸分配方块数组之前逐级检查，
+// 避免单个恶意文件（解压炸弹、超深嵌套、超大声明尺寸）拖垮整个函数实例
+
+/// Bedrock 结构方块可保存的最大体积
+const MCSTRUCTURE_MAX_VOLUME: usize =
+    (MCSTRUCTURE_BLOCK_LIMIT.x * MCSTRUCTURE_BLOCK_LIMIT.y * MCSTRUCTURE_BLOCK_LIMIT.z) as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestLimits {
+    /// 解压后的 NBT 字节数
+    pub max_decompressed_bytes: usize,
+    /// 复合标签 / 列表的嵌套深度，与游戏本身一致
+    pub max_depth: usize,
+    /// 列表标签的元素个数；每个元素都会展开为独立的标签对象。
+    /// 默认值为最大尺寸 .mcstructure 的体积，其每层方块索引都是一个这么长的列表
+    pub max_list_length: usize,
+    /// 字节 / 整数 / 长整数数组的元素个数
+    pub max_array_length: usize,
+    /// 单个区域的调色板条目数
+    pub max_palette_size: usize,
+    /// 单个区域声明的体积；超过 `MAX_REGION_VOLUME` 的设置不生效
+    pub max_region_volume: i64,
+    /// 所有区域声明体积之和
+    pub max_total_volume: i64,
+}
+
+impl IngestLimits {
+    pub const DEFAULT: IngestLimits = IngestLimits {
+        max_decompressed_bytes: 256 * 1024 * 1024,
+        max_depth: 512,
+        max_list_length: MCSTRUCTURE_MAX_VOLUME,
+        max_array_length: 1 << 27,
+        max_palette_size: 1 << 16,
+        max_region_volume: MAX_REGION_VOLUME,
+        max_total_volume: MAX_REGION_VOLUME,
+    };
+
+    /// 当前生效的上限，由 `set_ingest_limits` 配置
+    pub fn current() -> IngestLimits {
+        *INGEST_LIMITS.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 解析 JSON 配置，未给出的字段使用默认值；空字符串表示全部默认
+    pub fn from_json(json: &str) -> Result<IngestLimits, NeptuniumError> {
+        if json.trim().is_empty() {
+            return Ok(IngestLimits::default());
+        }
+        serde_json::from_str(json)
+            .map_err(|e| NeptuniumError::new(format!("Invalid ingest limits: {}", e), "INVALID_LIMITS".to_string()))
+    }
+}
+
+impl Default for IngestLimits {
+    fn default() -> Self {
+        IngestLimits::DEFAULT
+    }
+}
+
+static INGEST_LIMITS: RwLock<IngestLimits> = RwLock::new(IngestLimits::DEFAULT);
+
+/// 按投影格式检查根标签中声明的区域尺寸与调色板大小，在分配方块数组之前拒绝超限文件
+pub fn check_declared_limits(root: &NbtCompound, limits: &IngestLimits) -> Result<(), SchematicError> {
+    let mut total_volume: i64 = 0;
+    for (region, size, palette_size) in declared_regions(root) {
+        if let Some(size) = size {
+            // 乘积或累加溢出即视为超限，报告中记为 i64::MAX
+            let volume = i64::from(size.x.unsigned_abs())
+                .checked_mul(i64::from(size.y.unsigned_abs()))
+                .and_then(|v| v.checked_mul(i64::from(size.z.unsigned_abs())));
+            let Some(volume) = volume.filter(|v| *v <= limits.max_region_volume) else {
+                let volume = volume.unwrap_or(i64::MAX);
+                return Err(SchematicError::RegionTooLarge { region, volume, limit: limits.max_region_volume });
+            };
+            let total = total_volume.checked_add(volume);
+            let Some(total) = total.filter(|v| *v <= limits.max_total_volume) else {
+                let volume = total.unwrap_or(i64::MAX);
+                return Err(SchematicError::TotalVolumeTooLarge { volume, limit: limits.max_total_volume });
+            };
+            total_volume = total;
+        }
+        if palette_size > limits.max_palette_size {
+            return Err(SchematicError::PaletteTooLarge { region, size: palette_size, limit: limits.max_palette_size });
+        }
+    }
+    Ok(())
+}
+
+/// 各区域声明的 (名称, 尺寸, 调色板条目数)；字段缺失时交给对应格式的读取器报错
+fn declared_regions(root: &NbtCompound) -> Vec<(String, Option<Vec3i>, usize)> {
+    let dimensions = |body: &NbtCompound| -> Option<Vec3i> {
+        let dimension = |key: &str| body.get_i32(key).map(|v| i32::from(v as u16));
+        Some(Vec3i::new(dimension("Width")?, dimension("Height")?, dimension("Length")?))
+    };
+
+    match detect_from_root(root).0 {
+        SchematicFormat::Litematica => root
+            .get_compound("Regions")
+            .map(|regions| {
+                regions
+                    .iter()
+                    .filter_map(|(name, tag)| tag.as_compound().map(|region| (name, region)))
+                    .map(|(name, region)| {
+                        let size = region.get_compound("Size").and_then(Vec3i::from_compound);
+                        let palette = region.get_list("BlockStatePalette").map_or(0, |p| p.len());
+                        (name.to_string(), size, palette)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        SchematicFormat::Sponge => {
+            let body = root.get_compound("Schematic").unwrap_or(root);
+            let palette = body
+                .get_compound("Blocks")
+                .and_then(|blocks| blocks.get_compound("Palette"))
+                .or_else(|| body.get_compound("Palette"))
+                .map_or(0, |p| p.len());
+            vec![("Main".to_string(), dimensions(body), palette)]
+        }
+        SchematicFormat::McEdit => vec![("Main".to_string(), dimensions(root), 0)],
+        SchematicFormat::Structure => {
+            let palette = root.get_list("palette").map(|p| p.len()).unwrap_or_else(|| {
+                root.get_list("palettes")
+                    .map(|palettes| palettes.iter().filter_map(|p| p.as_list()).map(|p| p.len()).max().unwrap_or(0))
+                    .unwrap_or(0)
+            });
+            vec![("Main".to_string(), read_int_triple(root.get("size")), palette)]
+        }
+        SchematicFormat::McStructure => {
+            let palette = root
+                .get_compound("structure")
+                .and_then(|s| s.get_compound("palette"))
+                .and_then(|p| p.get_compound("default"))
+                .and_then(|d| d.get_list("block_palette"))
+                .map_or(0, |p| p.len());
+            vec![("Main".to_string(), read_int_triple(root.get("size")), palette)]
+        }
+        SchematicFormat::Unknown => Vec::new(),
+    }
+}
+
+/// 解压并解析 NBT，再检查声明的尺寸；返回的文档可直接交给各格式读取器
+pub fn read_document_with_limits(data: &[u8], limits: &IngestLimits) -> Result<NbtDocument, SchematicError> {
+    let document = NbtDocument::from_bytes_with_limits(data, limits)?;
+    check_declared_limits(&document.root, limits)?;
+    Ok(document)
+}
+
+// 导出的函数
+/// 配置全局上限（JSON，未给出的字段使用默认值），返回生效后的完整配置
+#[wasm_bindgen]
+pub fn set_ingest_limits(limits_json: &str) -> Result<String, NeptuniumError> {
+    let limits = IngestLimits::from_json(limits_json)?;
+    *INGEST_LIMITS.write().unwrap_or_else(|e| e.into_inner()) = limits;
+    Ok(serde_json::to_string(&limits).unwrap_or_default())
+}
+
+#[wasm_bindgen]
+pub fn get_ingest_limits() -> String {
+    serde_json::to_string(&IngestLimits::current()).unwrap_or_default()
+}
+
+/// 按给定上限（留空使用当前全局配置）检查上传内容，返回具体的拒绝原因
+#[wasm_bindgen]
+pub fn validate_upload_limits(data: &[u8], limits_json: &str) -> ValidationResult {
+    let limits = if limits_json.trim().is_empty() {
+        IngestLimits::current()
+    } else {
+        match IngestLimits::from_json(limits_json) {
+            Ok(limits) => limits,
+            Err(err) => return ValidationResult::new(false, err.message()),
+        }
+    };
+    match read_document_with_limits(data, &limits) {
+        Ok(_) => ValidationResult::new(true, "File is within ingest limits".to_string()),
+        Err(err) => ValidationResult::new(false, err.to_string()),
+    }
+}
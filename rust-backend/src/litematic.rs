@@ -0,0 +1,431 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::schematic::{read_double_triple, required_compound};
+use crate::{
+    checked_volume, detect_from_root, BlockEntity, BlockState, ConversionWarning, Entity, NbtCompound,
+    NbtCompression, NbtDocument, NbtList, NbtTag, NeptuniumError, Region, RgbaImage, Schematic, SchematicError,
+    SchematicFormat, STRUCTURE_VOID, TAG_COMPOUND, TAG_DOUBLE,
+};
+
+/// 写出时使用的 Litematica 格式版本
+const LITEMATIC_VERSION: i32 = 6;
+
+// Litematica (.litematic) 元数据提取与方块数据读取
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Vec3i {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Vec3i {
+    pub fn new(x: i32, y: i32, z: i32) -> Vec3i {
+        Vec3i { x, y, z }
+    }
+
+    /// 读取 {x, y, z} 形式的复合标签
+    pub fn from_compound(compound: &NbtCompound) -> Option<Vec3i> {
+        Some(Vec3i {
+            x: compound.get_i32("x")?,
+            y: compound.get_i32("y")?,
+            z: compound.get_i32("z")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LitematicRegionInfo {
+    pub name: String,
+    pub position: Vec3i,
+    /// 保留原始符号：负值表示区域朝负方向延伸
+    pub size: Vec3i,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LitematicMetadata {
+    pub version: i32,
+    pub sub_version: Option<i32>,
+    pub minecraft_data_version: Option<i32>,
+    pub name: String,
+    pub author: String,
+    pub description: String,
+    pub region_count: i32,
+    pub total_blocks: i64,
+    pub total_volume: i64,
+    pub enclosing_size: Option<Vec3i>,
+    pub time_created: Option<i64>,
+    pub time_modified: Option<i64>,
+    /// 预览图像素数量（正方形，边长为其平方根），不含图像本身
+    pub preview_pixels: Option<usize>,
+    pub regions: Vec<LitematicRegionInfo>,
+}
+
+impl LitematicMetadata {
+    pub fn from_root(root: &NbtCompound) -> Result<LitematicMetadata, SchematicError> {
+        let (format, _, _) = detect_from_root(root);
+        if format != SchematicFormat::Litematica {
+            return Err(SchematicError::WrongFormat {
+                expected: SchematicFormat::Litematica.as_str(),
+                found: format.as_str(),
+            });
+        }
+
+        let metadata = root
+            .get_compound("Metadata")
+            .ok_or_else(|| SchematicError::MissingField("Metadata".to_string()))?;
+        let regions_tag = root
+            .get_compound("Regions")
+            .ok_or_else(|| SchematicError::MissingField("Regions".to_string()))?;
+
+        let mut regions = Vec::with_capacity(regions_tag.len());
+        for (name, region) in regions_tag.iter() {
+            let region = region
+                .as_compound()
+                .ok_or_else(|| SchematicError::MissingField(format!("Regions.{}", name)))?;
+            let position = region.get_compound("Position").and_then(Vec3i::from_compound);
+            let size = region.get_compound("Size").and_then(Vec3i::from_compound);
+            match (position, size) {
+                (Some(position), Some(size)) => regions.push(LitematicRegionInfo {
+                    name: name.to_string(),
+                    position,
+                    size,
+                }),
+                _ => return Err(SchematicError::MissingField(format!("Regions.{}.Position/Size", name))),
+            }
+        }
+
+        Ok(LitematicMetadata {
+            version: root.get_i32("Version").unwrap_or(0),
+            sub_version: root.get_i32("SubVersion"),
+            minecraft_data_version: root.get_i32("MinecraftDataVersion"),
+            name: metadata.get_str("Name").unwrap_or_default().to_string(),
+            author: metadata.get_str("Author").unwrap_or_default().to_string(),
+            description: metadata.get_str("Description").unwrap_or_default().to_string(),
+            region_count: metadata.get_i32("RegionCount").unwrap_or(regions.len() as i32),
+            total_blocks: metadata.get_i64("TotalBlocks").unwrap_or(0),
+            total_volume: metadata.get_i64("TotalVolume").unwrap_or(0),
+            enclosing_size: metadata.get_compound("EnclosingSize").and_then(Vec3i::from_compound),
+            time_created: metadata.get_i64("TimeCreated"),
+            time_modified: metadata.get_i64("TimeModified"),
+            preview_pixels: metadata.get_int_array("PreviewImageData").map(<[i32]>::len),
+            regions,
+        })
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<LitematicMetadata, SchematicError> {
+        let document = NbtDocument::from_bytes(data)?;
+        LitematicMetadata::from_root(&document.root)
+    }
+}
+
+/// 预览图边长上限，Litematica 默认保存 140×140
+const MAX_PREVIEW_DIMENSION: usize = 1024;
+
+/// 解码 Metadata.PreviewImageData（ARGB int 数组，正方形）；文件没有预览图时返回 None
+pub fn read_preview_image(root: &NbtCompound) -> Result<Option<RgbaImage>, SchematicError> {
+    let (format, _, _) = detect_from_root(root);
+    if format != SchematicFormat::Litematica {
+        return Err(SchematicError::WrongFormat {
+            expected: SchematicFormat::Litematica.as_str(),
+            found: format.as_str(),
+        });
+    }
+    let Some(argb) = root.get_compound("Metadata").and_then(|m| m.get_int_array("PreviewImageData")) else {
+        return Ok(None);
+    };
+    if argb.is_empty() {
+        return Ok(None);
+    }
+
+    let side = argb.len().isqrt();
+    if side * side != argb.len() {
+        return Err(SchematicError::Invalid(format!(
+            "Preview image has {} pixels, which is not a square image",
+            argb.len()
+        )));
+    }
+    if side > MAX_PREVIEW_DIMENSION {
+        return Err(SchematicError::Invalid(format!(
+            "Preview image is {0}x{0}, larger than the {1}x{1} limit",
+            side, MAX_PREVIEW_DIMENSION
+        )));
+    }
+
+    let mut pixels = Vec::with_capacity(argb.len() * 4);
+    for pixel in argb {
+        let [a, r, g, b] = pixel.to_be_bytes();
+        pixels.extend_from_slice(&[r, g, b, a]);
+    }
+    Ok(Some(RgbaImage { width: side as u32, height: side as u32, pixels }))
+}
+
+/// 调色板索引所需的位宽，Litematica 最少使用 2 位
+pub(crate) fn bits_for_palette(len: usize) -> u32 {
+    (usize::BITS - len.saturating_sub(1).leading_zeros()).max(2)
+}
+
+/// 解包紧密排列（允许跨越 long 边界）的位数组
+pub(crate) fn unpack_tight(longs: &[i64], bits: u32, count: usize) -> Vec<u32> {
+    let mask = (1u64 << bits) - 1;
+    let bits = bits as usize;
+    (0..count)
+        .map(|i| {
+            let bit = i * bits;
+            let (word, offset) = (bit / 64, bit % 64);
+            let mut value = (longs[word] as u64) >> offset;
+            if offset + bits > 64 {
+                value |= (longs[word + 1] as u64) << (64 - offset);
+            }
+            (value & mask) as u32
+        })
+        .collect()
+}
+
+/// 按紧密排列方式打包位数组
+pub(crate) fn pack_tight(values: &[u32], bits: u32) -> Vec<i64> {
+    let bits = bits as usize;
+    let mut longs = vec![0u64; (values.len() * bits).div_ceil(64)];
+    for (i, value) in values.iter().enumerate() {
+        let bit = i * bits;
+        let (word, offset) = (bit / 64, bit % 64);
+        longs[word] |= u64::from(*value) << offset;
+        if offset + bits > 64 {
+            longs[word + 1] |= u64::from(*value) >> (64 - offset);
+        }
+    }
+    longs.into_iter().map(|v| v as i64).collect()
+}
+
+pub fn read_litematic(root: &NbtCompound) -> Result<Schematic, SchematicError> {
+    let metadata = LitematicMetadata::from_root(root)?;
+    let regions_tag = required_compound(root, "Regions")?;
+
+    let mut schematic = Schematic::new(
+        metadata.name.clone(),
+        metadata.minecraft_data_version.unwrap_or(0),
+        SchematicFormat::Litematica,
+    );
+    schematic.author = metadata.author.clone();
+    schematic.description = metadata.description.clone();
+
+    for info in &metadata.regions {
+        let region_tag = required_compound(regions_tag, &info.name)?;
+        let out_of_range =
+            || SchematicError::Invalid(format!("Region `{}` position or size is out of range", info.name));
+        let extent = |size: i32| size.checked_abs().ok_or_else(out_of_range);
+        let size = Vec3i::new(extent(info.size.x)?, extent(info.size.y)?, extent(info.size.z)?);
+        // 负尺寸表示区域从 Position 向负方向延伸
+        let corner = |position: i32, size: i32| match size < 0 {
+            true => position.checked_add(size + 1).ok_or_else(out_of_range),
+            false => Ok(position),
+        };
+        let origin = Vec3i::new(
+            corner(info.position.x, info.size.x)?,
+            corner(info.position.y, info.size.y)?,
+            corner(info.position.z, info.size.z)?,
+        );
+
+        let palette: Vec<BlockState> = region_tag
+            .get_list("BlockStatePalette")
+            .ok_or_else(|| SchematicError::MissingField(format!("Regions.{}.BlockStatePalette", info.name)))?
+            .compounds()
+            .map(BlockState::from_nbt)
+            .collect::<Option<_>>()
+            .ok_or_else(|| SchematicError::Invalid(format!("Region `{}` has a malformed palette entry", info.name)))?;
+        if palette.is_empty() {
+            return Err(SchematicError::Invalid(format!("Region `{}` has an empty palette", info.name)));
+        }
+
+        let volume = checked_volume(size)?;
+        let bits = bits_for_palette(palette.len());
+        let states = region_tag
+            .get_long_array("BlockStates")
+            .ok_or_else(|| SchematicError::MissingField(format!("Regions.{}.BlockStates", info.name)))?;
+        if states.len() < (volume * bits as usize).div_ceil(64) {
+            return Err(SchematicError::Invalid(format!("Region `{}` block state array is too short", info.name)));
+        }
+
+        let mut region = Region {
+            name: info.name.clone(),
+            origin,
+            size,
+            palette,
+            blocks: unpack_tight(states, bits, volume),
+            block_entities: Vec::new(),
+            entities: Vec::new(),
+        };
+
+        if let Some(tile_entities) = region_tag.get_list("TileEntities") {
+            for tile in tile_entities.compounds() {
+                let mut nbt = tile.clone();
+                let pos = match (nbt.remove("x"), nbt.remove("y"), nbt.remove("z")) {
+                    (Some(x), Some(y), Some(z)) => Vec3i::new(
+                        x.as_i64().unwrap_or(0) as i32,
+                        y.as_i64().unwrap_or(0) as i32,
+                        z.as_i64().unwrap_or(0) as i32,
+                    ),
+                    _ => continue,
+                };
+                let id = nbt.remove("id").and_then(|t| t.as_str().map(str::to_string)).unwrap_or_default();
+                region.block_entities.push(BlockEntity { pos, id, nbt });
+            }
+        }
+
+        if let Some(entities) = region_tag.get_list("Entities") {
+            for entity in entities.compounds() {
+                let mut nbt = entity.clone();
+                let Some(pos) = read_double_triple(nbt.remove("Pos").as_ref()) else { continue };
+                let id = nbt.remove("id").and_then(|t| t.as_str().map(str::to_string)).unwrap_or_default();
+                region.entities.push(Entity { pos, id, nbt });
+            }
+        }
+
+        schematic.regions.push(region);
+    }
+
+    Ok(schematic)
+}
+
+/// 写出 Litematica 格式（格式版本 6）
+pub fn write_litematic(schematic: &Schematic, warnings: &mut Vec<ConversionWarning>) -> Result<NbtDocument, SchematicError> {
+    let air = BlockState::air();
+    let mut regions = NbtCompound::new();
+    let mut total_blocks: i64 = 0;
+    let mut total_volume: i64 = 0;
+    let mut voids = 0u64;
+
+    for region in &schematic.regions {
+        // Litematica 要求调色板第 0 项为空气；它没有结构空位的概念，统一写为空气
+        let mut palette = vec![air.clone()];
+        let remap: Vec<u32> = region
+            .palette
+            .iter()
+            .map(|state| {
+                let state = if state.name == STRUCTURE_VOID { &air } else { state };
+                match palette.iter().position(|s| s == state) {
+                    Some(index) => index as u32,
+                    None => {
+                        palette.push(state.clone());
+                        (palette.len() - 1) as u32
+                    }
+                }
+            })
+            .collect();
+        let counts = region.palette_counts();
+        for (state, count) in region.palette.iter().zip(&counts) {
+            if state.name == STRUCTURE_VOID {
+                voids += count;
+            } else if !state.is_air() {
+                total_blocks += *count as i64;
+            }
+        }
+        total_volume += region.volume() as i64;
+
+        let blocks: Vec<u32> = region.blocks.iter().map(|i| remap[*i as usize]).collect();
+        let bits = bits_for_palette(palette.len());
+
+        let mut tile_entities = NbtList::new(TAG_COMPOUND);
+        for block_entity in &region.block_entities {
+            let mut nbt = NbtCompound::new();
+            nbt.insert("x", NbtTag::Int(block_entity.pos.x));
+            nbt.insert("y", NbtTag::Int(block_entity.pos.y));
+            nbt.insert("z", NbtTag::Int(block_entity.pos.z));
+            if !block_entity.id.is_empty() {
+                nbt.insert("id", NbtTag::String(block_entity.id.clone()));
+            }
+            for (key, value) in block_entity.nbt.iter() {
+                nbt.insert(key, value.clone());
+            }
+            tile_entities.values.push(NbtTag::Compound(nbt));
+        }
+
+        let mut entities = NbtList::new(TAG_COMPOUND);
+        for entity in &region.entities {
+            entities.values.push(NbtTag::Compound(entity_to_nbt(entity)));
+        }
+
+        let mut tag = NbtCompound::new();
+        tag.insert("Position", vec3_to_nbt(region.origin));
+        tag.insert("Size", vec3_to_nbt(region.size));
+        tag.insert(
+            "BlockStatePalette",
+            NbtTag::List(NbtList {
+                element_type: TAG_COMPOUND,
+                values: palette.iter().map(|s| NbtTag::Compound(s.to_nbt())).collect(),
+            }),
+        );
+        tag.insert("BlockStates", NbtTag::LongArray(pack_tight(&blocks, bits)));
+        tag.insert("TileEntities", NbtTag::List(tile_entities));
+        tag.insert("Entities", NbtTag::List(entities));
+        tag.insert("PendingBlockTicks", NbtTag::List(NbtList::new(TAG_COMPOUND)));
+        tag.insert("PendingFluidTicks", NbtTag::List(NbtList::new(TAG_COMPOUND)));
+
+        // 区域名在复合标签中必须唯一
+        let mut name = if region.name.is_empty() { "Main".to_string() } else { region.name.clone() };
+        let mut suffix = 2;
+        while regions.contains_key(&name) {
+            name = format!("{}_{}", region.name, suffix);
+            suffix += 1;
+        }
+        regions.insert(name, NbtTag::Compound(tag));
+    }
+
+    if voids > 0 {
+        warnings.push(ConversionWarning::new(
+            "structure_void_replaced",
+            format!("{} structure void positions were written as air", voids),
+        ));
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let (_, enclosing) = schematic.bounds()?;
+    let mut metadata = NbtCompound::new();
+    metadata.insert("Name", NbtTag::String(schematic.name.clone()));
+    metadata.insert("Author", NbtTag::String(schematic.author.clone()));
+    metadata.insert("Description", NbtTag::String(schematic.description.clone()));
+    metadata.insert("RegionCount", NbtTag::Int(schematic.regions.len() as i32));
+    metadata.insert("TotalVolume", NbtTag::Int(total_volume as i32));
+    metadata.insert("TotalBlocks", NbtTag::Int(total_blocks as i32));
+    metadata.insert("TimeCreated", NbtTag::Long(now));
+    metadata.insert("TimeModified", NbtTag::Long(now));
+    metadata.insert("EnclosingSize", vec3_to_nbt(enclosing));
+
+    let mut root = NbtCompound::new();
+    root.insert("MinecraftDataVersion", NbtTag::Int(schematic.data_version));
+    root.insert("Version", NbtTag::Int(LITEMATIC_VERSION));
+    root.insert("SubVersion", NbtTag::Int(1));
+    root.insert("Metadata", NbtTag::Compound(metadata));
+    root.insert("Regions", NbtTag::Compound(regions));
+    Ok(NbtDocument::new("", root, NbtCompression::Gzip))
+}
+
+fn vec3_to_nbt(v: Vec3i) -> NbtTag {
+    let mut compound = NbtCompound::new();
+    compound.insert("x", NbtTag::Int(v.x));
+    compound.insert("y", NbtTag::Int(v.y));
+    compound.insert("z", NbtTag::Int(v.z));
+    NbtTag::Compound(compound)
+}
+
+/// 实体写回为 {id, Pos, ...} 形式，Litematica / MCEdit / Sponge v2 通用
+pub(crate) fn entity_to_nbt(entity: &Entity) -> NbtCompound {
+    let mut nbt = NbtCompound::new();
+    if !entity.id.is_empty() {
+        nbt.insert("id", NbtTag::String(entity.id.clone()));
+    }
+    nbt.insert(
+        "Pos",
+        NbtTag::List(NbtList { element_type: TAG_DOUBLE, values: entity.pos.iter().map(|v| NbtTag::Double(*v)).collect() }),
+    );
+    for (key, value) in entity.nbt.iter() {
+        nbt.insert(key, value.clone());
+    }
+    nbt
+}
+
+#[wasm_bindgen]
+pub fn extract_litematic_metadata(data: &[u8]) -> Result<String, NeptuniumError> {
+    let metadata = LitematicMetadata::from_bytes(data)?;
+    Ok(serde_json::to_string(&metadata).unwrap_or_default())
+}
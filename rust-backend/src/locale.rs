@@ -0,0 +1,145 @@
+use wasm_bindgen::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{OnceLock, RwLock};
+use crate::NeptuniumError;
+
+// 方块与物品的显示名称：内置 en_us / zh_cn 对照表，表中没有的 id（模组方块等）按路径生成可读名称
+
+const TRANSLATIONS: &str = include_str!("data/translations.txt");
+
+/// 表中第一列语言，其他语言缺少名称时回退到它
+pub const DEFAULT_LOCALE: &str = "en_us";
+
+struct TranslationTable {
+    locales: Vec<String>,
+    /// 路径（省略 minecraft:）→ 各语言名称，顺序与 locales 相同
+    names: HashMap<String, Vec<String>>,
+}
+
+fn translation_table() -> &'static TranslationTable {
+    static TABLE: OnceLock<TranslationTable> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut lines = TRANSLATIONS.lines().filter(|l| !l.trim().is_empty() && !l.starts_with('#'));
+        let locales: Vec<String> =
+            lines.next().map(|header| header.split('\t').skip(1).map(str::to_string).collect()).unwrap_or_default();
+        let mut names = HashMap::new();
+        for line in lines {
+            let mut columns = line.split('\t');
+            let Some(path) = columns.next() else { continue };
+            names.insert(path.to_string(), columns.map(str::to_string).collect());
+        }
+        TranslationTable { locales, names }
+    })
+}
+
+/// 内置表支持的语言代码
+pub fn available_locales() -> &'static [String] {
+    &translation_table().locales
+}
+
+/// 规范化语言代码："zh-CN" / "zh_CN" → "zh_cn"；只给出语言时取表中第一个匹配的地区（"zh" → "zh_cn"）
+pub fn normalize_locale(locale: &str) -> Option<String> {
+    let code = locale.trim().replace('-', "_").to_lowercase();
+    let locales = available_locales();
+    locales
+        .iter()
+        .find(|l| **l == code)
+        .or_else(|| locales.iter().find(|l| l.split('_').next() == Some(code.as_str())))
+        .cloned()
+}
+
+static DISPLAY_LOCALE: RwLock<String> = RwLock::new(String::new());
+
+/// 材料清单等报告使用的显示语言，由 `set_display_locale` 配置，默认 en_us
+pub fn display_locale() -> String {
+    let locale = DISPLAY_LOCALE.read().unwrap_or_else(|e| e.into_inner());
+    if locale.is_empty() { DEFAULT_LOCALE.to_string() } else { locale.clone() }
+}
+
+/// id 的显示名称，支持带属性的方块状态（属性原样附在名称后）。
+/// 指定语言缺少名称时回退到 en_us；表中没有的 id 按路径生成名称，非原版命名空间附在括号中
+pub fn display_name(id: &str, locale: &str) -> String {
+    if id.starts_with('#') {
+        return id.to_string();
+    }
+    let (name, properties) = match id.find('[') {
+        Some(bracket) => (&id[..bracket], Some(&id[bracket..])),
+        None => (id, None),
+    };
+    let (namespace, path) = name.split_once(':').unwrap_or(("minecraft", name));
+
+    let table = translation_table();
+    let translated = (namespace == "minecraft").then(|| table.names.get(path)).flatten().and_then(|names| {
+        let column = table.locales.iter().position(|l| l == locale).unwrap_or(0);
+        names.get(column).filter(|n| !n.is_empty()).or_else(|| names.first())
+    });
+    let mut display = match translated {
+        Some(translated) => translated.clone(),
+        None if namespace == "minecraft" => humanize(path),
+        None => format!("{} ({})", humanize(path), namespace),
+    };
+    if let Some(properties) = properties {
+        display.push(' ');
+        display.push_str(properties);
+    }
+    display
+}
+
+/// "andesite_casing" → "Andesite Casing"
+fn humanize(path: &str) -> String {
+    path.split(['_', '/'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn resolve_locale(locale: &str) -> Result<String, NeptuniumError> {
+    if locale.trim().is_empty() {
+        return Ok(display_locale());
+    }
+    normalize_locale(locale)
+        .ok_or_else(|| NeptuniumError::new(format!("Unsupported locale: {}", locale), "UNSUPPORTED_LOCALE".to_string()))
+}
+
+// 导出的函数
+/// 设置材料清单等报告的显示语言（如 "zh_cn"、"en_us"，留空恢复 en_us），返回规范化后的语言代码
+#[wasm_bindgen]
+pub fn set_display_locale(locale: &str) -> Result<String, NeptuniumError> {
+    let locale = if locale.trim().is_empty() { DEFAULT_LOCALE.to_string() } else { resolve_locale(locale)? };
+    *DISPLAY_LOCALE.write().unwrap_or_else(|e| e.into_inner()) = locale.clone();
+    Ok(locale)
+}
+
+#[wasm_bindgen]
+pub fn get_display_locale() -> String {
+    display_locale()
+}
+
+/// 支持的语言代码（JSON 数组）
+#[wasm_bindgen]
+pub fn get_available_locales() -> String {
+    serde_json::to_string(available_locales()).unwrap_or_default()
+}
+
+/// 单个 id 的显示名称；locale 留空时使用当前显示语言
+#[wasm_bindgen]
+pub fn get_display_name(id: &str, locale: &str) -> Result<String, NeptuniumError> {
+    Ok(display_name(id, &resolve_locale(locale)?))
+}
+
+/// 批量翻译报告中的 id：输入 JSON 字符串数组，返回 id → 显示名称的 JSON 对象
+#[wasm_bindgen]
+pub fn translate_ids(ids_json: &str, locale: &str) -> Result<String, NeptuniumError> {
+    let locale = resolve_locale(locale)?;
+    let ids: Vec<String> = serde_json::from_str(ids_json)
+        .map_err(|e| NeptuniumError::new(format!("Invalid id list: {}", e), "INVALID_ID_LIST".to_string()))?;
+    let names: BTreeMap<&str, String> = ids.iter().map(|id| (id.as_str(), display_name(id, &locale))).collect();
+    Ok(serde_json::to_string(&names).unwrap_or_default())
+}
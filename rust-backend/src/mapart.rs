@@ -0,0 +1,606 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use thiserror::Error;
+use crate::{
+    BlockState, ConversionOutput, ConversionWarning, ExportFormat, MaterialList, MaterialMode, NeptuniumError, Region,
+    RenderError, RgbaImage, Schematic, SchematicError, SchematicFormat, Vec3i, LATEST_DATA_VERSION, MAX_REGION_VOLUME,
+};
+
+// 地图画生成：把图片缩放并量化到原版地图颜色，生成平铺或阶梯式（利用高度差得到三档亮度）的投影
+
+const MAP_COLORS: &str = include_str!("data/map_colors.txt");
+
+/// 一张地图覆盖的方块边长
+pub const MAP_SIZE: u32 = 128;
+/// 每个方向最多的地图数量
+pub const MAX_MAP_GRID: u32 = 16;
+/// 输入图片的像素上限
+const MAX_IMAGE_PIXELS: u64 = 64 * 1024 * 1024;
+/// 主世界可建造的高度范围
+const BUILD_HEIGHT: i32 = 384;
+/// 暗、标准、亮三档亮度的倍率（/255）
+const SHADE_MULTIPLIERS: [u32; 3] = [180, 220, 255];
+const SHADE_STANDARD: u8 = 1;
+const SHADE_LIGHT: u8 = 2;
+
+#[derive(Debug, Error)]
+pub enum MapArtError {
+    #[error(transparent)]
+    Schematic(#[from] SchematicError),
+    #[error(transparent)]
+    Render(#[from] RenderError),
+    #[error("Map grid must be between 1×1 and {max}×{max} maps, got {0}×{1}", max = MAX_MAP_GRID)]
+    InvalidGrid(u32, u32),
+    #[error("Image could not be decoded: {0}")]
+    Decode(String),
+    #[error("Image is too large ({0}×{1} pixels)")]
+    ImageTooLarge(u32, u32),
+    #[error("None of the allowed blocks has a map color")]
+    NoColors,
+    #[error(
+        "Staircase of {width}×{height}×{length} blocks exceeds the region volume limit of {limit}; \
+         use fewer maps or a flatter image"
+    )]
+    TooLarge { width: i32, height: i32, length: i32, limit: i64 },
+}
+
+impl MapArtError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            MapArtError::Schematic(err) => err.code(),
+            MapArtError::Render(err) => err.code(),
+            MapArtError::InvalidGrid(_, _) => "MAP_ART_INVALID_GRID",
+            MapArtError::Decode(_) => "MAP_ART_DECODE_FAILED",
+            MapArtError::ImageTooLarge(_, _) => "MAP_ART_IMAGE_TOO_LARGE",
+            MapArtError::NoColors => "MAP_ART_NO_COLORS",
+            MapArtError::TooLarge { .. } => "MAP_ART_TOO_LARGE",
+        }
+    }
+}
+
+impl From<MapArtError> for NeptuniumError {
+    fn from(err: MapArtError) -> Self {
+        NeptuniumError::new(err.to_string(), err.code().to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapArtMode {
+    /// 所有方块同高，只使用标准亮度
+    Flat,
+    /// 比北侧方块高、同高、低时分别显示为亮、标准、暗三档亮度
+    Staircase,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MapArtOptions {
+    /// 横向（X）的地图数量
+    pub maps_wide: u32,
+    /// 纵向（Z）的地图数量
+    pub maps_tall: u32,
+    pub mode: MapArtMode,
+    /// Floyd–Steinberg 误差扩散抖动
+    pub dither: bool,
+    /// 允许使用的方块（可用 `*` 通配）；为空时每种颜色使用默认方块
+    pub blocks: Vec<String>,
+    /// 北侧参照行的方块，决定第一行像素的亮度，不出现在地图上
+    pub reference_block: String,
+    /// 透明像素混合到的背景色
+    pub background: [u8; 3],
+    pub name: String,
+}
+
+impl Default for MapArtOptions {
+    fn default() -> Self {
+        MapArtOptions {
+            maps_wide: 1,
+            maps_tall: 1,
+            mode: MapArtMode::Flat,
+            dither: false,
+            blocks: Vec::new(),
+            reference_block: "minecraft:cobblestone".to_string(),
+            background: [255, 255, 255],
+            name: "Map Art".to_string(),
+        }
+    }
+}
+
+impl MapArtOptions {
+    pub fn from_json(json: &str) -> Result<MapArtOptions, NeptuniumError> {
+        if json.trim().is_empty() {
+            return Ok(MapArtOptions::default());
+        }
+        serde_json::from_str(json).map_err(|e| {
+            NeptuniumError::new(format!("Invalid map art options: {}", e), "INVALID_MAP_ART_OPTIONS".to_string())
+        })
+    }
+}
+
+struct MapColor {
+    id: u8,
+    rgb: [u8; 3],
+    /// 第一个为默认方块
+    blocks: Vec<BlockState>,
+}
+
+fn map_colors() -> &'static [MapColor] {
+    static COLORS: OnceLock<Vec<MapColor>> = OnceLock::new();
+    COLORS.get_or_init(|| {
+        MAP_COLORS
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let id = fields.next()?.parse().ok()?;
+                let [r, g, b, _] = crate::parse_hex_color(fields.next()?)?;
+                let blocks = fields.filter_map(|block| BlockState::parse(block).ok()).collect();
+                Some(MapColor { id, rgb: [r, g, b], blocks })
+            })
+            .collect()
+    })
+}
+
+/// 量化时的一个候选：地图颜色、选用的方块与亮度
+struct PaletteEntry {
+    color: usize,
+    shade: u8,
+    rgb: [u8; 3],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapColorUsage {
+    /// 原版地图颜色 id
+    pub color_id: u8,
+    pub block: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapArtReport {
+    pub maps_wide: u32,
+    pub maps_tall: u32,
+    pub mode: MapArtMode,
+    pub dither: bool,
+    /// 投影尺寸（含北侧参照行）
+    pub size: Vec3i,
+    /// 最高一列的高度差
+    pub max_height: i32,
+    /// 按数量降序
+    pub colors: Vec<MapColorUsage>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MapArt {
+    pub schematic: Schematic,
+    pub report: MapArtReport,
+    /// 地图上的实际显示效果，每个方块一个像素（不含参照行）
+    pub preview: RgbaImage,
+    pub warnings: Vec<ConversionWarning>,
+}
+
+/// 解码 PNG（任意位深、调色板、灰度）为 RGBA 图像
+pub fn decode_png_image(data: &[u8]) -> Result<RgbaImage, MapArtError> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| MapArtError::Decode(e.to_string()))?;
+    let (width, height) = (reader.info().width, reader.info().height);
+    if u64::from(width) * u64::from(height) > MAX_IMAGE_PIXELS {
+        return Err(MapArtError::ImageTooLarge(width, height));
+    }
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|e| MapArtError::Decode(e.to_string()))?;
+    let buffer = &buffer[..info.buffer_size()];
+
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+    match info.color_type {
+        png::ColorType::Rgba => pixels.extend_from_slice(buffer),
+        png::ColorType::Rgb => buffer.chunks_exact(3).for_each(|p| pixels.extend_from_slice(&[p[0], p[1], p[2], 255])),
+        png::ColorType::GrayscaleAlpha => {
+            buffer.chunks_exact(2).for_each(|p| pixels.extend_from_slice(&[p[0], p[0], p[0], p[1]]))
+        }
+        png::ColorType::Grayscale => buffer.iter().for_each(|&g| pixels.extend_from_slice(&[g, g, g, 255])),
+        png::ColorType::Indexed => return Err(MapArtError::Decode("Unexpanded indexed color".to_string())),
+    }
+    Ok(RgbaImage { width, height, pixels })
+}
+
+/// 把图片转换为地图画投影：缩放到 128×地图数量，量化到可用的地图颜色，阶梯模式下按亮度计算每列高度
+pub fn generate_map_art(image: &RgbaImage, options: &MapArtOptions) -> Result<MapArt, MapArtError> {
+    let (maps_wide, maps_tall) = (options.maps_wide, options.maps_tall);
+    if !(1..=MAX_MAP_GRID).contains(&maps_wide) || !(1..=MAX_MAP_GRID).contains(&maps_tall) {
+        return Err(MapArtError::InvalidGrid(maps_wide, maps_tall));
+    }
+    if image.width == 0 || image.height == 0 {
+        return Err(MapArtError::Decode("Image is empty".to_string()));
+    }
+    let mut warnings = Vec::new();
+    let (width, length) = (maps_wide * MAP_SIZE, maps_tall * MAP_SIZE);
+
+    // 每种颜色选用第一个允许的方块
+    let allowed: Vec<BlockState> = options
+        .blocks
+        .iter()
+        .filter_map(|pattern| BlockState::parse(pattern).ok())
+        .collect();
+    let colors = map_colors();
+    let mut choices: Vec<(usize, &BlockState)> = Vec::new();
+    for (index, color) in colors.iter().enumerate() {
+        let block = if allowed.is_empty() {
+            color.blocks.first()
+        } else {
+            color.blocks.iter().find(|block| allowed.iter().any(|a| block_allowed(a, block)))
+        };
+        if let Some(block) = block {
+            choices.push((index, block));
+        }
+    }
+    for pattern in &allowed {
+        if !colors.iter().flat_map(|c| &c.blocks).any(|block| block_allowed(pattern, block)) {
+            warnings.push(ConversionWarning::new(
+                "unknown_map_block",
+                format!("{} has no map color and was ignored", pattern),
+            ));
+        }
+    }
+    if choices.is_empty() {
+        return Err(MapArtError::NoColors);
+    }
+
+    let shades: &[u8] = match options.mode {
+        MapArtMode::Flat => &[SHADE_STANDARD],
+        MapArtMode::Staircase => &[0, SHADE_STANDARD, SHADE_LIGHT],
+    };
+    let palette: Vec<PaletteEntry> = choices
+        .iter()
+        .enumerate()
+        .flat_map(|(choice, &(color, _))| {
+            let rgb = colors[color].rgb;
+            shades.iter().map(move |&shade| PaletteEntry { color: choice, shade, rgb: shaded(rgb, shade) })
+        })
+        .collect();
+
+    let aspect = f64::from(image.width) * f64::from(length) / (f64::from(image.height) * f64::from(width));
+    if (aspect - 1.0).abs() > 0.01 {
+        warnings.push(ConversionWarning::new(
+            "map_art_stretched",
+            format!("Image of {}×{} pixels was stretched to {}×{} blocks", image.width, image.height, width, length),
+        ));
+    }
+    let pixels = resample(image, width, length, options.background);
+    let quantized = quantize(&pixels, width as usize, &palette, options.dither);
+
+    // 每列高度：第 0 个节点为参照行，之后依次为各像素
+    let mut heights = vec![0i32; width as usize * (length as usize + 1)];
+    let mut max_height = 0;
+    for x in 0..width as usize {
+        let column: Vec<u8> = (0..length as usize).map(|z| palette[quantized[z * width as usize + x]].shade).collect();
+        for (z, height) in staircase_heights(&column).into_iter().enumerate() {
+            heights[z * width as usize + x] = height;
+            max_height = max_height.max(height);
+        }
+    }
+    if max_height + 1 > BUILD_HEIGHT {
+        warnings.push(ConversionWarning::new(
+            "map_art_exceeds_build_height",
+            format!("Staircase is {} blocks tall and does not fit within the world height", max_height + 1),
+        ));
+    }
+
+    let size = Vec3i::new(width as i32, max_height + 1, length as i32 + 1);
+    // 阶梯式地图画为单个稠密区域，大网格配合较大的高度差会超出区域体积上限
+    if i64::from(size.x) * i64::from(size.y) * i64::from(size.z) > MAX_REGION_VOLUME {
+        return Err(MapArtError::TooLarge { width: size.x, height: size.y, length: size.z, limit: MAX_REGION_VOLUME });
+    }
+    let mut region = Region::new(options.name.clone(), Vec3i::new(0, 0, 0), size)?;
+    let reference = BlockState::parse(&options.reference_block)?;
+    let mut preview = RgbaImage::new(width, length, [0, 0, 0, 255]);
+    let mut usage = vec![0u64; choices.len()];
+    for (x, &height) in heights[..width as usize].iter().enumerate() {
+        region.set_block(x as i32, height, 0, &reference);
+    }
+    for z in 0..length as usize {
+        for x in 0..width as usize {
+            let entry = &palette[quantized[z * width as usize + x]];
+            let height = heights[(z + 1) * width as usize + x];
+            region.set_block(x as i32, height, z as i32 + 1, choices[entry.color].1);
+            usage[entry.color] += 1;
+            let i = (z * width as usize + x) * 4;
+            preview.pixels[i..i + 3].copy_from_slice(&entry.rgb);
+        }
+    }
+
+    let mode_name = match options.mode {
+        MapArtMode::Flat => "flat",
+        MapArtMode::Staircase => "staircase",
+    };
+    let mut schematic = Schematic::new(options.name.clone(), LATEST_DATA_VERSION, SchematicFormat::Litematica);
+    schematic.description = format!(
+        "{}×{} map art ({}); the northmost row is the shading reference and lies outside the map",
+        maps_wide, maps_tall, mode_name
+    );
+    schematic.regions.push(region);
+
+    let mut colors_used: Vec<MapColorUsage> = choices
+        .iter()
+        .zip(usage)
+        .filter(|(_, count)| *count > 0)
+        .map(|(&(color, block), count)| MapColorUsage { color_id: colors[color].id, block: block.to_string(), count })
+        .collect();
+    colors_used.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.color_id.cmp(&b.color_id)));
+    Ok(MapArt {
+        schematic,
+        report: MapArtReport {
+            maps_wide,
+            maps_tall,
+            mode: options.mode,
+            dither: options.dither,
+            size,
+            max_height,
+            colors: colors_used,
+        },
+        preview,
+        warnings,
+    })
+}
+
+/// 允许列表条目匹配方块 id（可用通配），给出的属性须全部相同
+fn block_allowed(pattern: &BlockState, block: &BlockState) -> bool {
+    crate::glob_captures(&pattern.name, &block.name).is_some()
+        && pattern.properties.iter().all(|(key, value)| block.property(key) == Some(value.as_str()))
+}
+
+fn shaded(rgb: [u8; 3], shade: u8) -> [u8; 3] {
+    let multiplier = SHADE_MULTIPLIERS[shade as usize];
+    rgb.map(|c| (u32::from(c) * multiplier / 255) as u8)
+}
+
+/// 按面积平均缩放，透明像素先混合到背景色
+fn resample(image: &RgbaImage, width: u32, height: u32, background: [u8; 3]) -> Vec<[f32; 3]> {
+    let (source_width, source_height) = (u64::from(image.width), u64::from(image.height));
+    let span = |target: u32, source: u64, size: u32| {
+        let start = u64::from(target) * source / u64::from(size);
+        let end = (u64::from(target + 1) * source / u64::from(size)).max(start + 1);
+        start as u32..end as u32
+    };
+    let mut pixels = Vec::with_capacity(width as usize * height as usize);
+    for ty in 0..height {
+        let rows = span(ty, source_height, height);
+        for tx in 0..width {
+            let mut sum = [0f32; 3];
+            let mut count = 0f32;
+            for sy in rows.clone() {
+                for sx in span(tx, source_width, width) {
+                    let [r, g, b, a] = image.pixel(sx, sy);
+                    let alpha = f32::from(a) / 255.0;
+                    for (channel, (value, back)) in sum.iter_mut().zip([r, g, b].into_iter().zip(background)) {
+                        *channel += f32::from(value) * alpha + f32::from(back) * (1.0 - alpha);
+                    }
+                    count += 1.0;
+                }
+            }
+            pixels.push(sum.map(|c| c / count));
+        }
+    }
+    pixels
+}
+
+/// 加权欧氏距离（redmean），比 RGB 直线距离更接近人眼感受
+fn color_distance(a: [f32; 3], b: [u8; 3]) -> f32 {
+    let (r, g, bl) = (f32::from(b[0]), f32::from(b[1]), f32::from(b[2]));
+    let mean = (a[0] + r) / 2.0;
+    let (dr, dg, db) = (a[0] - r, a[1] - g, a[2] - bl);
+    (2.0 + mean / 256.0) * dr * dr + 4.0 * dg * dg + (2.0 + (255.0 - mean) / 256.0) * db * db
+}
+
+/// 为每个像素选择最接近的候选，可选 Floyd–Steinberg 抖动；返回候选下标
+fn quantize(pixels: &[[f32; 3]], width: usize, palette: &[PaletteEntry], dither: bool) -> Vec<usize> {
+    let mut cache: HashMap<[u8; 3], usize> = HashMap::new();
+    let mut nearest = |color: [f32; 3]| {
+        let key = color.map(|c| c.round().clamp(0.0, 255.0) as u8);
+        *cache.entry(key).or_insert_with(|| {
+            let key = key.map(f32::from);
+            (0..palette.len())
+                .min_by(|&a, &b| color_distance(key, palette[a].rgb).total_cmp(&color_distance(key, palette[b].rgb)))
+                .unwrap_or(0)
+        })
+    };
+    if !dither {
+        return pixels.iter().map(|&color| nearest(color)).collect();
+    }
+
+    let mut result = Vec::with_capacity(pixels.len());
+    // 当前行与下一行累积的误差，两端各留一格
+    let mut current = vec![[0f32; 3]; width + 2];
+    let mut next = vec![[0f32; 3]; width + 2];
+    for row in pixels.chunks(width) {
+        for (x, pixel) in row.iter().enumerate() {
+            let mut color = [0f32; 3];
+            for c in 0..3 {
+                color[c] = (pixel[c] + current[x + 1][c]).clamp(0.0, 255.0);
+            }
+            let chosen = nearest(color);
+            result.push(chosen);
+            for c in 0..3 {
+                let error = color[c] - f32::from(palette[chosen].rgb[c]);
+                current[x + 2][c] += error * 7.0 / 16.0;
+                next[x][c] += error * 3.0 / 16.0;
+                next[x + 1][c] += error * 5.0 / 16.0;
+                next[x + 2][c] += error / 16.0;
+            }
+        }
+        std::mem::swap(&mut current, &mut next);
+        next.iter_mut().for_each(|e| *e = [0.0; 3]);
+    }
+    result
+}
+
+/// 由一列像素（北 → 南）的亮度计算各节点高度，第 0 个节点为参照行。
+/// 相邻同高的像素合并为一段，每段取"连续上升的步数"与"之后连续下降的步数"中的较大者，得到最低的阶梯
+fn staircase_heights(shades: &[u8]) -> Vec<i32> {
+    // 每个节点所在的段，以及段与段之间是否升高
+    let mut segments = vec![0usize];
+    let mut rises = Vec::new();
+    for &shade in shades {
+        let segment = segments[segments.len() - 1];
+        if shade == SHADE_STANDARD {
+            segments.push(segment);
+        } else {
+            rises.push(shade == SHADE_LIGHT);
+            segments.push(segment + 1);
+        }
+    }
+    let count = rises.len() + 1;
+    let mut up = vec![0i32; count];
+    for k in 1..count {
+        if rises[k - 1] {
+            up[k] = up[k - 1] + 1;
+        }
+    }
+    let mut down = vec![0i32; count];
+    for k in (0..count - 1).rev() {
+        if !rises[k] {
+            down[k] = down[k + 1] + 1;
+        }
+    }
+    segments.into_iter().map(|segment| up[segment].max(down[segment])).collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[wasm_bindgen]
+pub struct MapArtOutput {
+    output: ConversionOutput,
+    report: MapArtReport,
+    materials: MaterialList,
+    preview: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl MapArtOutput {
+    /// .litematic 文件字节
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.output.data()
+    }
+
+    /// .litematic 文件与生成、转换警告
+    #[wasm_bindgen(getter)]
+    pub fn output(&self) -> ConversionOutput {
+        self.output.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn report_json(&self) -> String {
+        serde_json::to_string(&self.report).unwrap_or_default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn materials_json(&self) -> String {
+        serde_json::to_string(&self.materials).unwrap_or_default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn materials_csv(&self) -> String {
+        self.materials.to_csv()
+    }
+
+    /// 地图显示效果的 PNG
+    #[wasm_bindgen(getter)]
+    pub fn preview_png(&self) -> Vec<u8> {
+        self.preview.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn warnings_json(&self) -> String {
+        self.output.warnings_json()
+    }
+}
+
+impl MapArtOutput {
+    pub fn report(&self) -> &MapArtReport {
+        &self.report
+    }
+
+    pub fn materials(&self) -> &MaterialList {
+        &self.materials
+    }
+
+    pub fn warnings(&self) -> &[ConversionWarning] {
+        self.output.warnings()
+    }
+}
+
+// 导出的函数
+/// 把 PNG 图片转换为地图画 .litematic，同时给出材料清单与显示效果预览
+#[wasm_bindgen]
+pub fn generate_map_art_litematic(png: &[u8], options_json: &str) -> Result<MapArtOutput, NeptuniumError> {
+    let options = MapArtOptions::from_json(options_json)?;
+    let image = decode_png_image(png)?;
+    let map_art = generate_map_art(&image, &options)?;
+    let materials = MaterialList::from_schematic(&map_art.schematic, MaterialMode::Items);
+    let target = ExportFormat::Litematica;
+    let (data, mut warnings) = crate::write_schematic(&map_art.schematic, target)?;
+    warnings.splice(0..0, map_art.warnings);
+    Ok(MapArtOutput {
+        output: ConversionOutput::new(data, target, warnings),
+        report: map_art.report,
+        materials,
+        preview: map_art.preview.to_png().map_err(MapArtError::from)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 全部为某方块亮色的图片：阶梯模式下每列逐格升高，高度等于地图长度
+    fn rising_image(block: &str) -> RgbaImage {
+        let color = map_colors().iter().find(|c| c.blocks.iter().any(|b| b.name == block)).unwrap();
+        let [r, g, b] = shaded(color.rgb, SHADE_LIGHT);
+        RgbaImage::new(4, 4, [r, g, b, 255])
+    }
+
+    fn options(maps: u32, mode: MapArtMode) -> MapArtOptions {
+        MapArtOptions {
+            maps_wide: maps,
+            maps_tall: maps,
+            mode,
+            blocks: vec!["minecraft:stone".to_string()],
+            ..MapArtOptions::default()
+        }
+    }
+
+    #[test]
+    fn rejects_grids_outside_the_limit() {
+        let image = rising_image("minecraft:stone");
+        for maps in [0, MAX_MAP_GRID + 1] {
+            let err = generate_map_art(&image, &options(maps, MapArtMode::Flat)).unwrap_err();
+            assert!(matches!(err, MapArtError::InvalidGrid(_, _)));
+        }
+    }
+
+    #[test]
+    fn flat_art_fills_the_largest_grid() {
+        let art = generate_map_art(&rising_image("minecraft:stone"), &options(MAX_MAP_GRID, MapArtMode::Flat)).unwrap();
+        let side = (MAP_SIZE * MAX_MAP_GRID) as i32;
+        assert_eq!(art.report.size, Vec3i::new(side, 1, side + 1));
+    }
+
+    #[test]
+    fn staircase_rises_one_block_per_light_pixel() {
+        let art = generate_map_art(&rising_image("minecraft:stone"), &options(1, MapArtMode::Staircase)).unwrap();
+        let side = MAP_SIZE as i32;
+        assert_eq!(art.report.max_height, side);
+        assert_eq!(art.report.size, Vec3i::new(side, side + 1, side + 1));
+    }
+
+    #[test]
+    fn rejects_staircases_beyond_the_region_volume() {
+        let err = generate_map_art(&rising_image("minecraft:stone"), &options(4, MapArtMode::Staircase)).unwrap_err();
+        assert!(matches!(err, MapArtError::TooLarge { limit: MAX_REGION_VOLUME, .. }));
+        assert_eq!(err.code(), "MAP_ART_TOO_LARGE");
+    }
+}
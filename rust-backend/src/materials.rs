@@ -0,0 +1,591 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::{display_locale, display_name, BlockState, ConversionWarning, NeptuniumError, Schematic, SchematicError};
+
+// 材料清单：按方块状态统计，或折算为需要收集的物品
+
+/// 潜影盒与大箱子的格数
+pub const SHULKER_BOX_SLOTS: u64 = 27;
+pub const DOUBLE_CHEST_SLOTS: u64 = 54;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaterialMode {
+    /// 每个完整方块状态单独计数
+    BlockStates,
+    /// 折算为物品：门/床只计一次，液体与空气不计
+    Items,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaterialEntry {
+    pub id: String,
+    /// 当前显示语言下的名称
+    #[serde(default)]
+    pub name: String,
+    pub count: u64,
+}
+
+impl MaterialEntry {
+    /// 名称使用当前显示语言，见 `set_display_locale`
+    pub fn new(id: String, count: u64) -> MaterialEntry {
+        let name = display_name(&id, &display_locale());
+        MaterialEntry { id, name, count }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaterialList {
+    pub mode: MaterialMode,
+    pub total: u64,
+    pub entries: Vec<MaterialEntry>,
+    /// 无法识别的占位方块（未知旧版 id、Bedrock 专有方块）仍按原样计数，并在此提示
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<ConversionWarning>,
+}
+
+impl MaterialList {
+    pub fn from_schematic(schematic: &Schematic, mode: MaterialMode) -> MaterialList {
+        let mut list = MaterialList::from_state_counts(block_state_counts(schematic), mode);
+        list.warnings = crate::placeholder_warnings(schematic, None);
+        list
+    }
+
+    /// 由方块状态计数生成清单
+    pub fn from_state_counts(counts: HashMap<BlockState, u64>, mode: MaterialMode) -> MaterialList {
+        let mut totals: HashMap<String, u64> = HashMap::new();
+        for (state, count) in counts {
+            match mode {
+                MaterialMode::BlockStates => {
+                    if !state.is_air() && !is_ignored_block(&state) {
+                        *totals.entry(state.to_string()).or_default() += count;
+                    }
+                }
+                MaterialMode::Items => {
+                    for (item, per_block) in block_to_items(&state) {
+                        *totals.entry(item).or_default() += count * u64::from(per_block);
+                    }
+                }
+            }
+        }
+        MaterialList::from_counts(totals, mode)
+    }
+
+    /// 按数量降序、id 升序排列
+    pub fn from_counts(counts: HashMap<String, u64>, mode: MaterialMode) -> MaterialList {
+        let locale = display_locale();
+        let mut entries: Vec<MaterialEntry> = counts
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(id, count)| MaterialEntry { name: display_name(&id, &locale), id, count })
+            .collect();
+        entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.id.cmp(&b.id)));
+        let total = entries.iter().fold(0u64, |total, e| total.saturating_add(e.count));
+        MaterialList { mode, total, entries, warnings: Vec::new() }
+    }
+
+    pub fn count_of(&self, id: &str) -> u64 {
+        self.entries.iter().find(|e| e.id == id).map(|e| e.count).unwrap_or(0)
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("id,name,count\n");
+        for entry in &self.entries {
+            csv.push_str(&format!("{},{},{}\n", csv_field(&entry.id), csv_field(&entry.name), entry.count));
+        }
+        csv
+    }
+}
+
+// 最大堆叠数为 16 的物品
+const STACK_16_ITEMS: &[&str] =
+    &["ender_pearl", "snowball", "egg", "blue_egg", "brown_egg", "bucket", "honey_bottle", "armor_stand", "written_book"];
+const STACK_16_SUFFIXES: &[&str] = &["_sign", "_banner"];
+
+// 不可堆叠的物品
+const UNSTACKABLE_ITEMS: &[&str] = &[
+    "cake", "saddle", "totem_of_undying", "potion", "splash_potion", "lingering_potion", "enchanted_book",
+    "writable_book", "shulker_box", "minecart", "bow", "crossbow", "trident", "shield", "elytra", "flint_and_steel",
+    "shears", "fishing_rod", "carrot_on_a_stick", "warped_fungus_on_a_stick", "spyglass", "brush", "mace", "bundle",
+    "goat_horn", "debug_stick", "knowledge_book", "wolf_armor", "mushroom_stew", "rabbit_stew", "beetroot_soup",
+    "suspicious_stew",
+];
+const UNSTACKABLE_SUFFIXES: &[&str] = &[
+    "_bucket", "_shulker_box", "_bed", "_boat", "_raft", "_minecart", "_sword", "_pickaxe", "_axe", "_shovel", "_hoe",
+    "_helmet", "_chestplate", "_leggings", "_boots", "_horse_armor", "_bundle",
+];
+
+/// 物品的最大堆叠数：64、16 或 1
+pub fn max_stack_size(item: &str) -> u32 {
+    let path = item.split_once(':').map(|(_, path)| path).unwrap_or(item);
+    let unstackable = path.starts_with("music_disc_")
+        || UNSTACKABLE_ITEMS.contains(&path)
+        || UNSTACKABLE_SUFFIXES.iter().any(|s| path.ends_with(s));
+    if unstackable {
+        1
+    } else if STACK_16_ITEMS.contains(&path) || STACK_16_SUFFIXES.iter().any(|s| path.ends_with(s)) {
+        16
+    } else {
+        64
+    }
+}
+
+/// 清单条目的堆叠数；按方块状态统计时取该方块对应的物品
+fn entry_stack_size(id: &str, mode: MaterialMode) -> u32 {
+    let item = match mode {
+        MaterialMode::Items => None,
+        MaterialMode::BlockStates => {
+            BlockState::parse(id).ok().and_then(|state| block_to_items(&state).into_iter().next())
+        }
+    };
+    max_stack_size(item.as_ref().map_or(id, |(item, _)| item.as_str()))
+}
+
+/// 数量拆分为装满的潜影盒、剩余整组与零头；另给出全部装进大箱子所需的箱数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StackBreakdown {
+    pub stack_size: u32,
+    pub shulker_boxes: u64,
+    pub stacks: u64,
+    pub remainder: u64,
+    /// 占用的格数
+    pub slots: u64,
+    pub double_chests: u64,
+}
+
+impl StackBreakdown {
+    pub fn new(count: u64, stack_size: u32) -> StackBreakdown {
+        let stack_size = stack_size.max(1);
+        let size = u64::from(stack_size);
+        let full_stacks = count / size;
+        let slots = count.div_ceil(size);
+        StackBreakdown {
+            stack_size,
+            shulker_boxes: full_stacks / SHULKER_BOX_SLOTS,
+            stacks: full_stacks % SHULKER_BOX_SLOTS,
+            remainder: count % size,
+            slots,
+            double_chests: slots.div_ceil(DOUBLE_CHEST_SLOTS),
+        }
+    }
+
+    /// 如 `2 SB + 5×64 + 12`
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.shulker_boxes > 0 {
+            parts.push(format!("{} SB", self.shulker_boxes));
+        }
+        if self.stacks > 0 {
+            parts.push(format!("{}\u{d7}{}", self.stacks, self.stack_size));
+        }
+        if self.remainder > 0 || parts.is_empty() {
+            parts.push(self.remainder.to_string());
+        }
+        parts.join(" + ")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StackedMaterialEntry {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub count: u64,
+    #[serde(flatten)]
+    pub breakdown: StackBreakdown,
+    pub summary: String,
+}
+
+/// 带堆叠拆分的材料清单，合计给出全部材料需要的格数、潜影盒与大箱子数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackedMaterialList {
+    pub mode: MaterialMode,
+    pub total: u64,
+    pub total_slots: u64,
+    pub total_shulker_boxes: u64,
+    pub total_double_chests: u64,
+    pub entries: Vec<StackedMaterialEntry>,
+}
+
+const STACKED_CSV_HEADER: &str = "id,name,count,stack_size,shulker_boxes,stacks,remainder,double_chests,summary";
+
+impl StackedMaterialList {
+    pub fn from_list(list: &MaterialList) -> StackedMaterialList {
+        let entries: Vec<StackedMaterialEntry> = list
+            .entries
+            .iter()
+            .map(|entry| {
+                let breakdown = StackBreakdown::new(entry.count, entry_stack_size(&entry.id, list.mode));
+                StackedMaterialEntry {
+                    id: entry.id.clone(),
+                    name: entry.name.clone(),
+                    count: entry.count,
+                    breakdown,
+                    summary: breakdown.describe(),
+                }
+            })
+            .collect();
+        let total_slots = entries.iter().map(|e| e.breakdown.slots).sum::<u64>();
+        StackedMaterialList {
+            mode: list.mode,
+            total: list.total,
+            total_slots,
+            total_shulker_boxes: total_slots.div_ceil(SHULKER_BOX_SLOTS),
+            total_double_chests: total_slots.div_ceil(DOUBLE_CHEST_SLOTS),
+            entries,
+        }
+    }
+
+    fn csv_rows(&self, prefix: &str, csv: &mut String) {
+        for entry in &self.entries {
+            let b = &entry.breakdown;
+            csv.push_str(&format!(
+                "{}{},{},{},{},{},{},{},{},{}\n",
+                prefix,
+                csv_field(&entry.id),
+                csv_field(&entry.name),
+                entry.count,
+                b.stack_size,
+                b.shulker_boxes,
+                b.stacks,
+                b.remainder,
+                b.double_chests,
+                csv_field(&entry.summary)
+            ));
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", STACKED_CSV_HEADER);
+        self.csv_rows("", &mut csv);
+        csv
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StageOptions {
+    /// 每一阶段包含的层数
+    pub layers_per_stage: i32,
+    /// 显式给出的层范围（含两端，投影最底层为 0）；非空时忽略 layers_per_stage
+    pub ranges: Vec<[i32; 2]>,
+}
+
+impl Default for StageOptions {
+    fn default() -> Self {
+        StageOptions { layers_per_stage: 16, ranges: Vec::new() }
+    }
+}
+
+impl StageOptions {
+    pub fn from_json(json: &str) -> Result<StageOptions, NeptuniumError> {
+        if json.trim().is_empty() {
+            return Ok(StageOptions::default());
+        }
+        serde_json::from_str(json).map_err(|e| {
+            NeptuniumError::new(format!("Invalid stage options: {}", e), "INVALID_STAGE_OPTIONS".to_string())
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaterialStage {
+    /// 层范围，含两端
+    pub min_layer: i32,
+    pub max_layer: i32,
+    pub materials: StackedMaterialList,
+}
+
+/// 按 Y 层范围分阶段的材料清单，便于分批备料
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagedMaterialList {
+    pub mode: MaterialMode,
+    pub layer_count: i32,
+    pub stages: Vec<MaterialStage>,
+}
+
+impl StagedMaterialList {
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("stage,layers,{}\n", STACKED_CSV_HEADER);
+        for (i, stage) in self.stages.iter().enumerate() {
+            let prefix = format!("{},{}-{},", i + 1, stage.min_layer, stage.max_layer);
+            stage.materials.csv_rows(&prefix, &mut csv);
+        }
+        csv
+    }
+}
+
+/// 按层范围统计材料；层号从投影包围盒的最底层起算
+pub fn staged_material_lists(
+    schematic: &Schematic,
+    mode: MaterialMode,
+    options: &StageOptions,
+) -> Result<StagedMaterialList, SchematicError> {
+    let (min, size) = schematic.bounds()?;
+    let layer_count = size.y;
+    let ranges: Vec<[i32; 2]> = if options.ranges.is_empty() {
+        if options.layers_per_stage <= 0 {
+            return Err(SchematicError::Invalid(format!("Invalid stage height {}", options.layers_per_stage)));
+        }
+        (0..layer_count)
+            .step_by(options.layers_per_stage as usize)
+            .map(|start| [start, (start + options.layers_per_stage - 1).min(layer_count - 1)])
+            .collect()
+    } else {
+        options.ranges.clone()
+    };
+    for [from, to] in &ranges {
+        if from > to || *from < 0 || *to >= layer_count {
+            return Err(SchematicError::Invalid(format!(
+                "Layer range {}-{} is outside the schematic (0-{})",
+                from,
+                to,
+                layer_count - 1
+            )));
+        }
+    }
+
+    let mut stages = Vec::new();
+    for [from, to] in ranges {
+        let mut counts: HashMap<BlockState, u64> = HashMap::new();
+        for region in &schematic.regions {
+            let base = region.origin.y - min.y;
+            let (start, end) = ((from - base).max(0), (to - base).min(region.size.y - 1));
+            if start > end {
+                continue;
+            }
+            // 方块按 y → z → x 排列，每层是连续的一段
+            let layer = (region.size.x * region.size.z) as usize;
+            let mut palette_counts = vec![0u64; region.palette.len()];
+            for index in &region.blocks[start as usize * layer..(end as usize + 1) * layer] {
+                palette_counts[*index as usize] += 1;
+            }
+            for (state, count) in region.palette.iter().zip(palette_counts) {
+                if count > 0 {
+                    *counts.entry(state.clone()).or_default() += count;
+                }
+            }
+        }
+        let list = MaterialList::from_state_counts(counts, mode);
+        stages.push(MaterialStage { min_layer: from, max_layer: to, materials: StackedMaterialList::from_list(&list) });
+    }
+    Ok(StagedMaterialList { mode, layer_count, stages })
+}
+
+/// 以 `=`、`+`、`-`、`@`、制表符或回车开头的值会被电子表格当作公式执行，先加 `'` 前缀使其成为普通文本
+pub(crate) fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// 统计所有区域中每个方块状态的数量
+pub fn block_state_counts(schematic: &Schematic) -> HashMap<BlockState, u64> {
+    let mut totals: HashMap<BlockState, u64> = HashMap::new();
+    for region in &schematic.regions {
+        for (state, count) in region.palette.iter().zip(region.palette_counts()) {
+            if count > 0 {
+                *totals.entry(state.clone()).or_default() += count;
+            }
+        }
+    }
+    totals
+}
+
+/// 无法作为材料收集的方块
+fn is_ignored_block(state: &BlockState) -> bool {
+    matches!(
+        state.path(),
+        "structure_void" | "water" | "lava" | "bubble_column" | "fire" | "soul_fire" | "nether_portal"
+            | "end_portal" | "end_gateway" | "moving_piston" | "piston_head" | "frosted_ice"
+    )
+}
+
+// 方块 id 与物品 id 不同的情况
+const BLOCK_ITEM_RENAMES: &[(&str, &str)] = &[
+    ("wall_torch", "torch"),
+    ("soul_wall_torch", "soul_torch"),
+    ("redstone_wall_torch", "redstone_torch"),
+    ("redstone_wire", "redstone"),
+    ("tripwire", "string"),
+    ("wheat", "wheat_seeds"),
+    ("carrots", "carrot"),
+    ("potatoes", "potato"),
+    ("beetroots", "beetroot_seeds"),
+    ("cocoa", "cocoa_beans"),
+    ("sweet_berry_bush", "sweet_berries"),
+    ("pumpkin_stem", "pumpkin_seeds"),
+    ("attached_pumpkin_stem", "pumpkin_seeds"),
+    ("melon_stem", "melon_seeds"),
+    ("attached_melon_stem", "melon_seeds"),
+    ("torchflower_crop", "torchflower_seeds"),
+    ("pitcher_crop", "pitcher_pod"),
+    ("bamboo_sapling", "bamboo"),
+    ("kelp_plant", "kelp"),
+    ("tall_seagrass", "seagrass"),
+    ("cave_vines", "glow_berries"),
+    ("cave_vines_plant", "glow_berries"),
+    ("twisting_vines_plant", "twisting_vines"),
+    ("weeping_vines_plant", "weeping_vines"),
+    ("big_dripleaf_stem", "big_dripleaf"),
+    ("farmland", "dirt"),
+    ("dirt_path", "dirt"),
+    ("water_cauldron", "cauldron"),
+    ("lava_cauldron", "cauldron"),
+    ("powder_snow_cauldron", "cauldron"),
+    ("powder_snow", "powder_snow_bucket"),
+];
+
+/// 一个方块状态折算成的物品及数量；空气、液体等返回空列表
+pub fn block_to_items(state: &BlockState) -> Vec<(String, u32)> {
+    if state.is_air() || is_ignored_block(state) {
+        return Vec::new();
+    }
+
+    let (namespace, path) = state.name.split_once(':').unwrap_or(("minecraft", &state.name));
+    let item = |path: &str| format!("{}:{}", namespace, path);
+
+    // 多格方块只计算其中一格
+    if state.property("half") == Some("upper") && (path.ends_with("_door") || is_tall_plant(path)) {
+        return Vec::new();
+    }
+    if path.ends_with("_bed") && state.property("part") == Some("head") {
+        return Vec::new();
+    }
+
+    // 单个方块位置可容纳多个物品
+    let stacked = |key: &str| state.property(key).and_then(|v| v.parse::<u32>().ok()).unwrap_or(1);
+    if path.ends_with("_slab") && state.property("type") == Some("double") {
+        return vec![(item(path), 2)];
+    }
+    match path {
+        "candle" => return vec![(item(path), stacked("candles"))],
+        p if p.ends_with("_candle") => return vec![(item(path), stacked("candles"))],
+        "sea_pickle" => return vec![(item(path), stacked("pickles"))],
+        "turtle_egg" => return vec![(item(path), stacked("eggs"))],
+        "snow" => return vec![(item(path), stacked("layers"))],
+        "pink_petals" | "wildflowers" => return vec![(item(path), stacked("flower_amount"))],
+        "leaf_litter" => return vec![(item(path), stacked("segment_amount"))],
+        _ => {}
+    }
+
+    // 花盆与插蜡烛的蛋糕由两种物品组成
+    if let Some(plant) = path.strip_prefix("potted_") {
+        let plant = match plant {
+            "azalea_bush" => "azalea",
+            "flowering_azalea_bush" => "flowering_azalea",
+            other => other,
+        };
+        return vec![(item("flower_pot"), 1), (item(plant), 1)];
+    }
+    if path == "candle_cake" {
+        return vec![(item("cake"), 1), (item("candle"), 1)];
+    }
+    if let Some(color) = path.strip_suffix("_candle_cake") {
+        return vec![(item("cake"), 1), (item(&format!("{}_candle", color)), 1)];
+    }
+
+    if let Some((_, renamed)) = BLOCK_ITEM_RENAMES.iter().find(|(block, _)| *block == path) {
+        return vec![(item(renamed), 1)];
+    }
+
+    // 挂在墙上的变体与普通变体是同一物品
+    let path = path
+        .replacen("_wall_hanging_sign", "_hanging_sign", 1)
+        .replacen("_wall_sign", "_sign", 1)
+        .replacen("_wall_banner", "_banner", 1)
+        .replacen("_wall_head", "_head", 1)
+        .replacen("_wall_skull", "_skull", 1)
+        .replacen("_wall_fan", "_fan", 1);
+    vec![(item(&path), 1)]
+}
+
+fn is_tall_plant(path: &str) -> bool {
+    matches!(
+        path,
+        "sunflower" | "lilac" | "rose_bush" | "peony" | "tall_grass" | "large_fern" | "tall_seagrass"
+            | "pitcher_plant" | "small_dripleaf"
+    )
+}
+
+#[wasm_bindgen]
+pub fn generate_material_list(data: &[u8], as_items: bool) -> Result<String, NeptuniumError> {
+    let schematic = Schematic::from_bytes(data)?;
+    let mode = if as_items { MaterialMode::Items } else { MaterialMode::BlockStates };
+    let list = MaterialList::from_schematic(&schematic, mode);
+    Ok(serde_json::to_string(&list).unwrap_or_default())
+}
+
+#[wasm_bindgen]
+pub fn generate_material_list_csv(data: &[u8], as_items: bool) -> Result<String, NeptuniumError> {
+    let schematic = Schematic::from_bytes(data)?;
+    let mode = if as_items { MaterialMode::Items } else { MaterialMode::BlockStates };
+    Ok(MaterialList::from_schematic(&schematic, mode).to_csv())
+}
+
+/// 带堆叠、潜影盒与大箱子拆分的材料清单（JSON）
+#[wasm_bindgen]
+pub fn generate_stacked_material_list(data: &[u8], as_items: bool) -> Result<String, NeptuniumError> {
+    let schematic = Schematic::from_bytes(data)?;
+    let mode = if as_items { MaterialMode::Items } else { MaterialMode::BlockStates };
+    let list = StackedMaterialList::from_list(&MaterialList::from_schematic(&schematic, mode));
+    Ok(serde_json::to_string(&list).unwrap_or_default())
+}
+
+#[wasm_bindgen]
+pub fn generate_stacked_material_list_csv(data: &[u8], as_items: bool) -> Result<String, NeptuniumError> {
+    let schematic = Schematic::from_bytes(data)?;
+    let mode = if as_items { MaterialMode::Items } else { MaterialMode::BlockStates };
+    Ok(StackedMaterialList::from_list(&MaterialList::from_schematic(&schematic, mode)).to_csv())
+}
+
+/// 按层范围分阶段的材料清单（JSON）；options_json 见 `StageOptions`，留空时每 16 层为一阶段
+#[wasm_bindgen]
+pub fn generate_staged_material_lists(data: &[u8], as_items: bool, options_json: &str) -> Result<String, NeptuniumError> {
+    let schematic = Schematic::from_bytes(data)?;
+    let mode = if as_items { MaterialMode::Items } else { MaterialMode::BlockStates };
+    let staged = staged_material_lists(&schematic, mode, &StageOptions::from_json(options_json)?)?;
+    Ok(serde_json::to_string(&staged).unwrap_or_default())
+}
+
+#[wasm_bindgen]
+pub fn generate_staged_material_lists_csv(data: &[u8], as_items: bool, options_json: &str) -> Result<String, NeptuniumError> {
+    let schematic = Schematic::from_bytes(data)?;
+    let mode = if as_items { MaterialMode::Items } else { MaterialMode::BlockStates };
+    Ok(staged_material_lists(&schematic, mode, &StageOptions::from_json(options_json)?)?.to_csv())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_and_formulas_neutralised() {
+        assert_eq!(csv_field("minecraft:stone"), "minecraft:stone");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_field("+1"), "'+1");
+        assert_eq!(csv_field("-1+2"), "'-1+2");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("\tcmd"), "'\tcmd");
+        assert_eq!(csv_field("a=b"), "a=b");
+    }
+
+    #[test]
+    fn material_csv_escapes_every_text_column() {
+        let entry = MaterialEntry { id: "minecraft:stone".to_string(), name: "=1+1".to_string(), count: 3 };
+        let list = MaterialList { mode: MaterialMode::Items, total: 3, entries: vec![entry], warnings: Vec::new() };
+        assert_eq!(list.to_csv(), "id,name,count\nminecraft:stone,'=1+1,3\n");
+        let stacked = StackedMaterialList::from_list(&list).to_csv();
+        assert!(stacked.lines().nth(1).unwrap().starts_with("minecraft:stone,'=1+1,3,"), "{}", stacked);
+    }
+}
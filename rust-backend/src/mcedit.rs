@@ -0,0 +1,200 @@
+use crate::legacy::{fix_flattened_region, unfix_for_legacy};
+use crate::litematic::entity_to_nbt;
+use crate::schematic::{read_double_triple, required_i32};
+use crate::{
+    checked_volume, flatten_legacy_block, unflatten_block, BlockEntity, BlockState, ConversionWarning, Entity, NbtCompound, NbtCompression, NbtDocument,
+    NbtList, NbtTag, Region, Schematic, SchematicError, SchematicFormat, Vec3i, FLATTENED_DATA_VERSION, TAG_COMPOUND,
+};
+use std::collections::{BTreeMap, HashMap};
+
+// MCEdit / Schematica (.schematic) 读写，方块以 1.12 及以前的数字 id + 数据值存储，读写时经扁平化表转换
+
+pub fn read_mcedit(root: &NbtCompound) -> Result<Schematic, SchematicError> {
+    let dimension = |key: &str| required_i32(root, key).map(|v| i32::from(v as u16));
+    let size = Vec3i::new(dimension("Width")?, dimension("Height")?, dimension("Length")?);
+    let volume = checked_volume(size)?;
+
+    let blocks = root
+        .get_byte_array("Blocks")
+        .ok_or_else(|| SchematicError::MissingField("Blocks".to_string()))?;
+    let data = root
+        .get_byte_array("Data")
+        .ok_or_else(|| SchematicError::MissingField("Data".to_string()))?;
+    if blocks.len() < volume || data.len() < volume {
+        return Err(SchematicError::Invalid("Blocks/Data arrays are shorter than the schematic volume".to_string()));
+    }
+    // AddBlocks 以半字节存储 id 的高 4 位
+    let add_blocks = root.get_byte_array("AddBlocks");
+
+    let mut palette = Vec::new();
+    let mut lookup: HashMap<(u16, u8), u32> = HashMap::new();
+    let mut indices = Vec::with_capacity(volume);
+    for i in 0..volume {
+        let mut id = u16::from(blocks[i] as u8);
+        if let Some(add) = add_blocks.and_then(|add| add.get(i >> 1)) {
+            let nibble = if i & 1 == 0 { (*add as u8) >> 4 } else { (*add as u8) & 0x0f };
+            id |= u16::from(nibble) << 8;
+        }
+        let meta = (data[i] as u8) & 0x0f;
+        let index = *lookup.entry((id, meta)).or_insert_with(|| {
+            palette.push(legacy_block_state(id, meta));
+            (palette.len() - 1) as u32
+        });
+        indices.push(index);
+    }
+
+    // 方块已按扁平化表转换，文件本身不记录 DataVersion
+    let mut schematic = Schematic::new("", FLATTENED_DATA_VERSION, SchematicFormat::McEdit);
+    let origin = match (root.get_i32("WEOffsetX"), root.get_i32("WEOffsetY"), root.get_i32("WEOffsetZ")) {
+        (Some(x), Some(y), Some(z)) => Vec3i::new(x, y, z),
+        _ => Vec3i::default(),
+    };
+    let mut region = Region {
+        name: "Main".to_string(),
+        origin,
+        size,
+        palette,
+        blocks: indices,
+        block_entities: Vec::new(),
+        entities: Vec::new(),
+    };
+
+    if let Some(list) = root.get_list("TileEntities") {
+        for tag in list.compounds() {
+            let mut nbt = tag.clone();
+            let (Some(x), Some(y), Some(z)) = (nbt.remove("x"), nbt.remove("y"), nbt.remove("z")) else { continue };
+            let pos = Vec3i::new(
+                x.as_i64().unwrap_or(0) as i32,
+                y.as_i64().unwrap_or(0) as i32,
+                z.as_i64().unwrap_or(0) as i32,
+            );
+            let id = nbt.remove("id").and_then(|t| t.as_str().map(str::to_string)).unwrap_or_default();
+            region.block_entities.push(BlockEntity { pos, id, nbt });
+        }
+    }
+
+    if let Some(list) = root.get_list("Entities") {
+        for tag in list.compounds() {
+            let mut nbt = tag.clone();
+            let Some(pos) = read_double_triple(nbt.remove("Pos").as_ref()) else { continue };
+            let id = nbt.remove("id").and_then(|t| t.as_str().map(str::to_string)).unwrap_or_default();
+            region.entities.push(Entity { pos, id, nbt });
+        }
+    }
+
+    fix_flattened_region(&mut region);
+    schematic.regions.push(region);
+    Ok(schematic)
+}
+
+/// 写出 MCEdit 格式；无法对应到旧版数字 id 的方块写为空气并给出警告
+pub fn write_mcedit(schematic: &Schematic, warnings: &mut Vec<ConversionWarning>) -> Result<NbtDocument, SchematicError> {
+    let mut region = crate::single_region(schematic, &BlockState::air(), warnings)?;
+    if region.size.x > i32::from(u16::MAX) || region.size.y > i32::from(u16::MAX) || region.size.z > i32::from(u16::MAX) {
+        return Err(SchematicError::Invalid("MCEdit schematics are limited to 65535 blocks per axis".to_string()));
+    }
+
+    unfix_for_legacy(&mut region);
+
+    let mut unmapped: BTreeMap<String, u64> = BTreeMap::new();
+    let counts = region.palette_counts();
+    let ids: Vec<(u16, u8)> = region
+        .palette
+        .iter()
+        .zip(&counts)
+        .map(|(state, count)| {
+            legacy_id_for(state).unwrap_or_else(|| {
+                if *count > 0 {
+                    *unmapped.entry(state.to_string()).or_default() += count;
+                }
+                (0, 0)
+            })
+        })
+        .collect();
+
+    let volume = region.volume();
+    let mut blocks = Vec::with_capacity(volume);
+    let mut data = Vec::with_capacity(volume);
+    let mut add_blocks = vec![0u8; volume.div_ceil(2)];
+    let mut needs_add = false;
+    for (i, palette_index) in region.blocks.iter().enumerate() {
+        let (id, meta) = ids[*palette_index as usize];
+        blocks.push((id & 0xff) as u8 as i8);
+        data.push(meta as i8);
+        if id > 0xff {
+            needs_add = true;
+            let nibble = ((id >> 8) & 0x0f) as u8;
+            add_blocks[i >> 1] |= if i & 1 == 0 { nibble << 4 } else { nibble };
+        }
+    }
+
+    for (state, count) in unmapped {
+        warnings.push(ConversionWarning::new(
+            "unmappable_block",
+            format!("{} has no legacy block id and was written as air ({} blocks)", state, count),
+        ));
+    }
+    if !region.block_entities.is_empty() || !region.entities.is_empty() {
+        warnings.push(ConversionWarning::new(
+            "legacy_nbt",
+            "Block entity and entity data was copied without downgrading and may not load in pre-1.13 versions"
+                .to_string(),
+        ));
+    }
+
+    let mut tile_entities = NbtList::new(TAG_COMPOUND);
+    for block_entity in &region.block_entities {
+        let mut nbt = NbtCompound::new();
+        nbt.insert("x", NbtTag::Int(block_entity.pos.x));
+        nbt.insert("y", NbtTag::Int(block_entity.pos.y));
+        nbt.insert("z", NbtTag::Int(block_entity.pos.z));
+        nbt.insert("id", NbtTag::String(block_entity.id.clone()));
+        for (key, value) in block_entity.nbt.iter() {
+            nbt.insert(key, value.clone());
+        }
+        tile_entities.values.push(NbtTag::Compound(nbt));
+    }
+    let mut entities = NbtList::new(TAG_COMPOUND);
+    for entity in &region.entities {
+        entities.values.push(NbtTag::Compound(entity_to_nbt(entity)));
+    }
+
+    let mut root = NbtCompound::new();
+    root.insert("Width", NbtTag::Short(region.size.x as u16 as i16));
+    root.insert("Height", NbtTag::Short(region.size.y as u16 as i16));
+    root.insert("Length", NbtTag::Short(region.size.z as u16 as i16));
+    root.insert("Materials", NbtTag::String("Alpha".to_string()));
+    root.insert("Blocks", NbtTag::ByteArray(blocks));
+    root.insert("Data", NbtTag::ByteArray(data));
+    if needs_add {
+        root.insert("AddBlocks", NbtTag::ByteArray(add_blocks.into_iter().map(|b| b as i8).collect()));
+    }
+    root.insert("WEOffsetX", NbtTag::Int(region.origin.x));
+    root.insert("WEOffsetY", NbtTag::Int(region.origin.y));
+    root.insert("WEOffsetZ", NbtTag::Int(region.origin.z));
+    root.insert("TileEntities", NbtTag::List(tile_entities));
+    root.insert("Entities", NbtTag::List(entities));
+    Ok(NbtDocument::new("Schematic", root, NbtCompression::Gzip))
+}
+
+/// 方块状态对应的旧版 (id, 数据值)；未知 id 的占位方块原样写回
+pub fn legacy_id_for(state: &BlockState) -> Option<(u16, u8)> {
+    if state.is_air() {
+        return Some((0, 0));
+    }
+    if let Some(id) = state.name.strip_prefix("legacy:") {
+        let id = id.parse::<u16>().ok()?;
+        let meta = state.property("data").and_then(|d| d.parse::<u8>().ok()).unwrap_or(0);
+        return Some((id, meta & 0x0f));
+    }
+    unflatten_block(state)
+}
+
+/// 旧版数字 id 转为扁平化方块状态；表中没有的 id 以 `legacy:<id>` 占位，保留原始 id 与数据值
+pub fn legacy_block_state(id: u16, meta: u8) -> BlockState {
+    if id == 0 {
+        return BlockState::air();
+    }
+    flatten_legacy_block(id, meta)
+        .unwrap_or_else(|| BlockState::new(format!("legacy:{}", id)).with_property("data", meta.to_string()))
+}
@@ -0,0 +1,477 @@
+use std::collections::{BTreeMap, HashMap};
+use crate::schematic::{read_double_triple, read_int_triple, required_compound};
+use crate::{
+    bedrock_block_entity_id, bedrock_placeholder, bedrock_to_java, checked_volume, java_block_entity_id, java_to_bedrock,
+    upgrade_block_state, BedrockBlock, BlockEntity, BlockState, ConversionWarning, Entity, NbtCompound, NbtDocument,
+    NbtList, NbtTag, Region, Schematic, SchematicError, SchematicFormat, Vec3i, LATEST_DATA_VERSION, STRUCTURE_VOID,
+    TAG_COMPOUND, TAG_INT, TAG_LIST,
+};
+
+// Bedrock 版结构方块 (.mcstructure) 读写：未压缩的小端序 NBT，方块索引分为两层，
+// 第一层为方块本身，第二层通常是含水方块中的水；索引按 x → y → z 顺序排列，-1 表示结构空位
+
+/// Bedrock 结构方块可保存的最大尺寸
+pub const MCSTRUCTURE_BLOCK_LIMIT: Vec3i = Vec3i { x: 64, y: 384, z: 64 };
+
+/// 写出时使用的结构格式版本
+pub const MCSTRUCTURE_FORMAT_VERSION: i32 = 1;
+
+/// 方块实体中可以在两个版本之间转换的字段，其余字段丢弃
+const PORTABLE_BLOCK_ENTITY_KEYS: &[&str] = &["Items", "CustomName"];
+
+/// .mcstructure 的内存模型，保持文件中的两层索引结构
+#[derive(Debug, Clone, PartialEq)]
+pub struct McStructure {
+    pub size: Vec3i,
+    /// 保存结构时所在的世界坐标，实体坐标以此为基准
+    pub world_origin: Vec3i,
+    pub palette: Vec<BedrockBlock>,
+    /// 两层调色板索引，按 x → y → z 排列，-1 表示该层为空
+    pub layers: [Vec<i32>; 2],
+    /// 线性索引 → 方块实体数据
+    pub block_entities: BTreeMap<usize, NbtCompound>,
+    pub entities: Vec<NbtCompound>,
+}
+
+impl McStructure {
+    pub fn index(&self, x: i32, y: i32, z: i32) -> usize {
+        ((x as usize * self.size.y as usize) + y as usize) * self.size.z as usize + z as usize
+    }
+
+    /// 由线性索引还原坐标
+    pub fn position(&self, index: usize) -> Vec3i {
+        let sy = self.size.y as usize;
+        let sz = self.size.z as usize;
+        Vec3i::new((index / (sy * sz)) as i32, ((index / sz) % sy) as i32, (index % sz) as i32)
+    }
+
+    pub fn from_root(root: &NbtCompound) -> Result<McStructure, SchematicError> {
+        let size = read_int_triple(root.get("size")).ok_or_else(|| SchematicError::MissingField("size".to_string()))?;
+        let volume = checked_volume(size)?;
+        let structure = required_compound(root, "structure")?;
+
+        let default = structure
+            .get_compound("palette")
+            .and_then(|p| p.get_compound("default"))
+            .ok_or_else(|| SchematicError::MissingField("structure.palette.default".to_string()))?;
+        let palette: Vec<BedrockBlock> = default
+            .get_list("block_palette")
+            .ok_or_else(|| SchematicError::MissingField("structure.palette.default.block_palette".to_string()))?
+            .compounds()
+            .map(BedrockBlock::from_nbt)
+            .collect::<Option<_>>()
+            .ok_or_else(|| SchematicError::Invalid("Malformed block palette entry".to_string()))?;
+
+        let indices = structure
+            .get_list("block_indices")
+            .ok_or_else(|| SchematicError::MissingField("structure.block_indices".to_string()))?;
+        let mut layers = [vec![-1; volume], vec![-1; volume]];
+        for (layer, tag) in layers.iter_mut().zip(indices.iter()) {
+            let values = tag.as_list().ok_or_else(|| SchematicError::Invalid("Block index layer is not a list".to_string()))?;
+            if values.len() != volume {
+                return Err(SchematicError::Invalid(format!(
+                    "Block index layer has {} entries but the structure volume is {}",
+                    values.len(),
+                    volume
+                )));
+            }
+            for (slot, value) in layer.iter_mut().zip(values.iter()) {
+                *slot = value
+                    .as_i64()
+                    .filter(|v| (-1..palette.len() as i64).contains(v))
+                    .ok_or_else(|| SchematicError::Invalid("Block index references an unknown palette entry".to_string()))?
+                    as i32;
+            }
+        }
+
+        let mut block_entities = BTreeMap::new();
+        if let Some(position_data) = default.get_compound("block_position_data") {
+            for (key, value) in position_data.iter() {
+                let (Ok(index), Some(data)) = (
+                    key.parse::<usize>(),
+                    value.as_compound().and_then(|v| v.get_compound("block_entity_data")),
+                ) else {
+                    continue;
+                };
+                if index < volume {
+                    block_entities.insert(index, data.clone());
+                }
+            }
+        }
+
+        let entities = structure.get_list("entities").map(|list| list.compounds().cloned().collect()).unwrap_or_default();
+
+        Ok(McStructure {
+            size,
+            world_origin: read_int_triple(root.get("structure_world_origin")).unwrap_or_default(),
+            palette,
+            layers,
+            block_entities,
+            entities,
+        })
+    }
+
+    pub fn to_root(&self) -> NbtCompound {
+        let int_list = |values: &[i32]| NbtTag::List(NbtList { element_type: TAG_INT, values: values.iter().map(|v| NbtTag::Int(*v)).collect() });
+
+        let mut block_palette = NbtList::new(TAG_COMPOUND);
+        block_palette.values.extend(self.palette.iter().map(|block| NbtTag::Compound(block.to_nbt())));
+        let mut position_data = NbtCompound::new();
+        for (index, data) in &self.block_entities {
+            let mut entry = NbtCompound::new();
+            entry.insert("block_entity_data", NbtTag::Compound(data.clone()));
+            position_data.insert(index.to_string(), NbtTag::Compound(entry));
+        }
+        let mut default = NbtCompound::new();
+        default.insert("block_palette", NbtTag::List(block_palette));
+        default.insert("block_position_data", NbtTag::Compound(position_data));
+        let mut palette = NbtCompound::new();
+        palette.insert("default", NbtTag::Compound(default));
+
+        let mut entities = NbtList::new(TAG_COMPOUND);
+        entities.values.extend(self.entities.iter().cloned().map(NbtTag::Compound));
+
+        let mut structure = NbtCompound::new();
+        structure.insert(
+            "block_indices",
+            NbtTag::List(NbtList { element_type: TAG_LIST, values: self.layers.iter().map(|layer| int_list(layer)).collect() }),
+        );
+        structure.insert("entities", NbtTag::List(entities));
+        structure.insert("palette", NbtTag::Compound(palette));
+
+        let mut root = NbtCompound::new();
+        root.insert("format_version", NbtTag::Int(MCSTRUCTURE_FORMAT_VERSION));
+        root.insert("size", int_list(&[self.size.x, self.size.y, self.size.z]));
+        root.insert("structure", NbtTag::Compound(structure));
+        root.insert("structure_world_origin", int_list(&[self.world_origin.x, self.world_origin.y, self.world_origin.z]));
+        root
+    }
+}
+
+pub fn read_mcstructure(root: &NbtCompound) -> Result<Schematic, SchematicError> {
+    read_mcstructure_with_warnings(root, &mut Vec::new())
+}
+
+/// 读取 .mcstructure 并映射为 Java 方块；无法映射的方块以 `bedrock:` 占位并写入警告
+pub fn read_mcstructure_with_warnings(
+    root: &NbtCompound,
+    warnings: &mut Vec<ConversionWarning>,
+) -> Result<Schematic, SchematicError> {
+    let structure = McStructure::from_root(root)?;
+    let is_water = |index: i32| {
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| structure.palette.get(i))
+            .is_some_and(|block| matches!(block.path(), "water" | "flowing_water"))
+    };
+
+    let mut region = Region::new("Main", Vec3i::default(), structure.size)?;
+    region.palette.clear();
+    // 每个 Java 调色板条目的来源：(Bedrock 方块, 丢弃的状态, 是否为占位方块)
+    let mut sources: Vec<Option<(String, Vec<String>, bool)>> = Vec::new();
+    let mut lookup: HashMap<(i32, bool), u32> = HashMap::new();
+    let mut secondary_dropped = 0u64;
+    for index in 0..region.volume() {
+        let primary = structure.layers[0][index];
+        let secondary = structure.layers[1][index];
+        let waterlogged = is_water(secondary);
+        if secondary >= 0 && !waterlogged {
+            secondary_dropped += 1;
+        }
+        let palette_index = *lookup.entry((primary, waterlogged)).or_insert_with(|| {
+            let (state, source) = match usize::try_from(primary).ok().and_then(|i| structure.palette.get(i)) {
+                None => (BlockState::new(STRUCTURE_VOID), None),
+                Some(block) => match bedrock_to_java(block, waterlogged) {
+                    Some(mapping) => (mapping.state, Some((block.to_string(), mapping.dropped, false))),
+                    None => (bedrock_placeholder(block), Some((block.to_string(), Vec::new(), true))),
+                },
+            };
+            region.palette.push(state);
+            sources.push(source);
+            (region.palette.len() - 1) as u32
+        });
+        let pos = structure.position(index);
+        let target = region.index(pos.x, pos.y, pos.z);
+        region.blocks[target] = palette_index;
+    }
+
+    let mut unmapped: BTreeMap<String, u64> = BTreeMap::new();
+    let mut dropped: BTreeMap<String, (Vec<String>, u64)> = BTreeMap::new();
+    for (source, count) in sources.into_iter().zip(region.palette_counts()) {
+        match source {
+            Some((block, _, true)) => *unmapped.entry(block).or_default() += count,
+            Some((block, keys, false)) if !keys.is_empty() => dropped.entry(block).or_insert((keys, 0)).1 += count,
+            _ => {}
+        }
+    }
+
+    let mut data_dropped = 0;
+    for (index, data) in &structure.block_entities {
+        let bedrock_id = data.get_str("id").unwrap_or_default();
+        let id = java_block_entity_id(bedrock_id).map(str::to_string).unwrap_or_else(|| crate::normalize_id(bedrock_id));
+        let (nbt, complete) = convert_block_entity(data, bedrock_item_to_java);
+        if !complete {
+            data_dropped += 1;
+        }
+        let pos = structure.position(*index);
+        region.block_entities.push(BlockEntity { pos, id, nbt });
+    }
+
+    let origin = structure.world_origin;
+    for entity in &structure.entities {
+        let Some(id) = entity.get_str("identifier") else { continue };
+        let Some(pos) = read_double_triple(entity.get("Pos")) else { continue };
+        let pos = [pos[0] - f64::from(origin.x), pos[1] - f64::from(origin.y), pos[2] - f64::from(origin.z)];
+        region.entities.push(Entity { pos, id: id.to_string(), nbt: NbtCompound::new() });
+    }
+
+    for (block, count) in unmapped {
+        warnings.push(ConversionWarning::new(
+            "unmappable_block",
+            format!("{} has no Java equivalent and was kept as a bedrock: placeholder ({} blocks)", block, count),
+        ));
+    }
+    report_dropped_states(dropped, "Java", warnings);
+    if secondary_dropped > 0 {
+        warnings.push(ConversionWarning::new(
+            "secondary_layer_dropped",
+            format!("{} non-water blocks in the second block layer have no Java equivalent and were dropped", secondary_dropped),
+        ));
+    }
+    if data_dropped > 0 {
+        warnings.push(ConversionWarning::new(
+            "block_entity_data_dropped",
+            format!("{} block entities had data other than items and custom names, which was not converted", data_dropped),
+        ));
+    }
+    if !region.entities.is_empty() {
+        warnings.push(ConversionWarning::new(
+            "entity_data_dropped",
+            format!("{} entities were imported with their type and position only", region.entities.len()),
+        ));
+    }
+
+    // 映射表以当前 Java 版本的方块名为准
+    let mut schematic = Schematic::new("", LATEST_DATA_VERSION, SchematicFormat::McStructure);
+    schematic.regions.push(region);
+    Ok(schematic)
+}
+
+/// 写出 .mcstructure；无法映射到 Bedrock 的方块写为空气，区域之间的空隙写为结构空位
+pub fn write_mcstructure(schematic: &Schematic, warnings: &mut Vec<ConversionWarning>) -> Result<NbtDocument, SchematicError> {
+    let mut region = crate::single_region(schematic, &BlockState::new(STRUCTURE_VOID), warnings)?;
+    let limit = MCSTRUCTURE_BLOCK_LIMIT;
+    if region.size.x > limit.x || region.size.y > limit.y || region.size.z > limit.z {
+        warnings.push(ConversionWarning::new(
+            "exceeds_structure_block_limit",
+            format!(
+                "Size {}x{}x{} exceeds the Bedrock structure block limit of {}x{}x{}",
+                region.size.x, region.size.y, region.size.z, limit.x, limit.y, limit.z
+            ),
+        ));
+    }
+
+    // 映射表按当前 Java 版本的方块名编写，旧版本的方块名先升级
+    if schematic.data_version > 0 && schematic.data_version < LATEST_DATA_VERSION {
+        for state in region.palette.iter_mut() {
+            *state = upgrade_block_state(state, schematic.data_version, LATEST_DATA_VERSION);
+        }
+    }
+
+    let mut structure = McStructure {
+        size: region.size,
+        world_origin: Vec3i::default(),
+        palette: Vec::new(),
+        layers: [Vec::new(), Vec::new()],
+        block_entities: BTreeMap::new(),
+        entities: Vec::new(),
+    };
+    let mut lookup: HashMap<BedrockBlock, i32> = HashMap::new();
+    let mut palette_index = |block: BedrockBlock, palette: &mut Vec<BedrockBlock>| {
+        *lookup.entry(block).or_insert_with_key(|block| {
+            palette.push(block.clone());
+            (palette.len() - 1) as i32
+        })
+    };
+
+    let counts = region.palette_counts();
+    let mut unmapped: BTreeMap<String, u64> = BTreeMap::new();
+    let mut dropped: BTreeMap<String, (Vec<String>, u64)> = BTreeMap::new();
+    // 每个 Java 调色板条目对应的 (第一层, 第二层) 索引
+    let mut remap: Vec<(i32, i32)> = Vec::with_capacity(region.palette.len());
+    for (state, count) in region.palette.iter().zip(&counts) {
+        if state.name == STRUCTURE_VOID {
+            remap.push((-1, -1));
+            continue;
+        }
+        match java_to_bedrock(state) {
+            Some(mapping) => {
+                if !mapping.dropped.is_empty() && *count > 0 {
+                    dropped.insert(state.to_string(), (mapping.dropped, *count));
+                }
+                let primary = palette_index(mapping.block, &mut structure.palette);
+                let secondary = if mapping.waterlogged { palette_index(BedrockBlock::water(), &mut structure.palette) } else { -1 };
+                remap.push((primary, secondary));
+            }
+            None => {
+                if *count > 0 {
+                    *unmapped.entry(state.to_string()).or_default() += count;
+                }
+                remap.push((palette_index(BedrockBlock::air(), &mut structure.palette), -1));
+            }
+        }
+    }
+
+    let volume = region.volume();
+    let mut primary = vec![-1; volume];
+    let mut secondary = vec![-1; volume];
+    for (index, palette_index) in region.blocks.iter().enumerate() {
+        let pos = region.position(index);
+        let target = structure.index(pos.x, pos.y, pos.z);
+        (primary[target], secondary[target]) = remap[*palette_index as usize];
+    }
+    structure.layers = [primary, secondary];
+
+    let mut data_dropped = 0;
+    let mut block_entities_dropped = 0;
+    for block_entity in &region.block_entities {
+        let pos = block_entity.pos;
+        let Some(bedrock_id) = bedrock_block_entity_id(&block_entity.id).filter(|_| region.contains(pos.x, pos.y, pos.z)) else {
+            block_entities_dropped += 1;
+            continue;
+        };
+        let (mut data, complete) = convert_block_entity(&block_entity.nbt, java_item_to_bedrock);
+        if !complete {
+            data_dropped += 1;
+        }
+        data.insert("id", NbtTag::String(bedrock_id.to_string()));
+        data.insert("x", NbtTag::Int(pos.x));
+        data.insert("y", NbtTag::Int(pos.y));
+        data.insert("z", NbtTag::Int(pos.z));
+        data.insert("isMovable", NbtTag::Byte(1));
+        structure.block_entities.insert(structure.index(pos.x, pos.y, pos.z), data);
+    }
+
+    for (state, count) in unmapped {
+        warnings.push(ConversionWarning::new(
+            "unmappable_block",
+            format!("{} has no Bedrock equivalent and was written as air ({} blocks)", state, count),
+        ));
+    }
+    report_dropped_states(dropped, "Bedrock", warnings);
+    if block_entities_dropped > 0 {
+        warnings.push(ConversionWarning::new(
+            "block_entity_dropped",
+            format!("{} block entities have no Bedrock equivalent and were dropped", block_entities_dropped),
+        ));
+    }
+    if data_dropped > 0 {
+        warnings.push(ConversionWarning::new(
+            "block_entity_data_dropped",
+            format!("{} block entities had data other than items and custom names, which was not converted", data_dropped),
+        ));
+    }
+    if !region.entities.is_empty() {
+        warnings.push(ConversionWarning::new(
+            "entities_dropped",
+            format!("{} entities cannot be converted to Bedrock and were dropped", region.entities.len()),
+        ));
+    }
+
+    Ok(NbtDocument::new_little_endian("", structure.to_root()))
+}
+
+fn report_dropped_states(
+    dropped: BTreeMap<String, (Vec<String>, u64)>,
+    target: &str,
+    warnings: &mut Vec<ConversionWarning>,
+) {
+    for (block, (keys, count)) in dropped {
+        warnings.push(ConversionWarning::new(
+            "dropped_block_state",
+            format!("{}: {} have no {} equivalent and were dropped ({} blocks)", block, keys.join(", "), target, count),
+        ));
+    }
+}
+
+/// 只保留两个版本通用的方块实体字段，返回转换后的数据以及是否没有丢弃任何字段
+fn convert_block_entity(data: &NbtCompound, convert_item: fn(&NbtCompound) -> Option<NbtCompound>) -> (NbtCompound, bool) {
+    let mut out = NbtCompound::new();
+    let mut complete = true;
+    for (key, value) in data.iter() {
+        match key {
+            "id" | "x" | "y" | "z" | "isMovable" | "keepPacked" => {}
+            "Items" => {
+                let mut items = NbtList::new(TAG_COMPOUND);
+                for item in value.as_list().into_iter().flat_map(NbtList::compounds) {
+                    // 附魔、耐久等物品数据两个版本结构不同，只转换物品种类与数量
+                    if item.contains_key("components") || item.contains_key("tag") {
+                        complete = false;
+                    }
+                    match convert_item(item) {
+                        Some(item) => items.values.push(NbtTag::Compound(item)),
+                        None => complete = false,
+                    }
+                }
+                out.insert("Items", NbtTag::List(items));
+            }
+            key if PORTABLE_BLOCK_ENTITY_KEYS.contains(&key) => {
+                out.insert(key, value.clone());
+            }
+            _ => complete = false,
+        }
+    }
+    (out, complete)
+}
+
+/// Java 物品 `{id, count|Count, Slot}` → Bedrock 物品 `{Name, Count, Damage, Slot}`
+fn java_item_to_bedrock(item: &NbtCompound) -> Option<NbtCompound> {
+    let id = item.get_str("id")?;
+    let count = item.get_i64("count").or_else(|| item.get_i64("Count")).unwrap_or(1);
+    let mut out = NbtCompound::new();
+    out.insert("Name", NbtTag::String(crate::normalize_id(id)));
+    out.insert("Count", NbtTag::Byte(count.clamp(0, 127) as i8));
+    out.insert("Damage", NbtTag::Short(0));
+    out.insert("WasPickedUp", NbtTag::Byte(0));
+    if let Some(slot) = item.get_i64("Slot") {
+        out.insert("Slot", NbtTag::Byte(slot as i8));
+    }
+    Some(out)
+}
+
+/// Bedrock 物品 → 1.20.5 之后的 Java 物品 `{id, count, Slot}`
+fn bedrock_item_to_java(item: &NbtCompound) -> Option<NbtCompound> {
+    let name = item.get_str("Name")?;
+    let mut out = NbtCompound::new();
+    out.insert("id", NbtTag::String(crate::normalize_id(name)));
+    out.insert("count", NbtTag::Int(item.get_i32("Count").unwrap_or(1)));
+    if let Some(slot) = item.get_i64("Slot") {
+        out.insert("Slot", NbtTag::Byte(slot as i8));
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExportFormat, IngestLimits};
+
+    #[test]
+    fn round_trips_a_structure_at_the_block_limit() {
+        let limit = MCSTRUCTURE_BLOCK_LIMIT;
+        let mut region = Region::new("Main", Vec3i::default(), limit).unwrap();
+        region.set_block(0, 0, 0, &BlockState::new("minecraft:stone"));
+        region.set_block(limit.x - 1, limit.y - 1, limit.z - 1, &BlockState::new("minecraft:oak_planks"));
+        let mut schematic = Schematic::new("limit", LATEST_DATA_VERSION, SchematicFormat::McStructure);
+        schematic.regions.push(region);
+
+        let (bytes, warnings) = crate::write_schematic(&schematic, ExportFormat::McStructure).unwrap();
+        assert!(warnings.iter().all(|w| w.code != "exceeds_structure_block_limit"));
+        let read = Schematic::from_bytes_with_limits(&bytes, &IngestLimits::DEFAULT).unwrap();
+        let region = &read.regions[0];
+        assert_eq!(region.size, limit);
+        assert_eq!(region.block(0, 0, 0).name, "minecraft:stone");
+        assert_eq!(region.block(limit.x - 1, limit.y - 1, limit.z - 1).name, "minecraft:oak_planks");
+        assert!(region.block(1, 0, 0).is_air());
+    }
+}
@@ -0,0 +1,416 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use crate::{
+    BlockEntity, ConversionOutput, ConversionWarning, Entity, ExportFormat, NeptuniumError, Region, Schematic,
+    SchematicError, SchematicFormat, Vec3i, STRUCTURE_VOID,
+};
+
+// 组合投影：把分别上传的模块按放置偏移拼成一个多区域 Litematica 文件，或合并为单个区域
+
+/// 合并为单个区域时，两个输入在同一位置都有非空气方块的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// 后加入的模块覆盖先加入的模块
+    #[default]
+    Overwrite,
+    /// 保留先加入的模块
+    KeepFirst,
+    /// 出现冲突即失败
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MergeOptions {
+    pub name: String,
+    pub author: String,
+    pub description: String,
+    /// 合并为单个区域，否则每个输入区域各自成为一个区域
+    pub flatten: bool,
+    pub conflict_policy: ConflictPolicy,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        MergeOptions {
+            name: "Merged".to_string(),
+            author: String::new(),
+            description: String::new(),
+            flatten: false,
+            conflict_policy: ConflictPolicy::default(),
+        }
+    }
+}
+
+impl MergeOptions {
+    pub fn from_json(json: &str) -> Result<MergeOptions, NeptuniumError> {
+        if json.trim().is_empty() {
+            return Ok(MergeOptions::default());
+        }
+        serde_json::from_str(json)
+            .map_err(|e| NeptuniumError::new(format!("Invalid merge options: {}", e), "INVALID_MERGE_OPTIONS".to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeInput {
+    /// 模块名，通常为文件名或 file_id；用作区域名
+    pub name: String,
+    pub schematic: Schematic,
+    /// 加到模块所有区域位置上的放置偏移
+    pub offset: Vec3i,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergedRegion {
+    pub name: String,
+    /// 来源模块在输入中的序号
+    pub input: usize,
+    pub origin: Vec3i,
+    pub size: Vec3i,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeReport {
+    pub regions: Vec<MergedRegion>,
+    pub data_version: i32,
+    /// 两个模块在同一位置都有方块（空气与结构空位除外）的次数，只在合并为单个区域时统计
+    pub conflicts: u64,
+    pub warnings: Vec<ConversionWarning>,
+}
+
+fn unique_name(name: &str, used: &mut HashSet<String>) -> String {
+    let base = if name.trim().is_empty() { "Region" } else { name.trim() };
+    let mut candidate = base.to_string();
+    let mut suffix = 2;
+    while !used.insert(candidate.clone()) {
+        candidate = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    candidate
+}
+
+/// 放置后的区域：名称与原点已按模块调整，区域内容尽量借用输入
+struct Placed<'a> {
+    input: usize,
+    name: String,
+    origin: Vec3i,
+    region: Cow<'a, Region>,
+}
+
+/// 按输入顺序组合；DataVersion 取所有输入中最新的，较旧的模块先升级
+pub fn merge_schematics(inputs: &[MergeInput], options: &MergeOptions) -> Result<(Schematic, MergeReport), SchematicError> {
+    if inputs.is_empty() {
+        return Err(SchematicError::Invalid("No schematics to merge".to_string()));
+    }
+    let mut report = MergeReport {
+        data_version: inputs.iter().map(|i| i.schematic.data_version).max().unwrap_or_default(),
+        ..MergeReport::default()
+    };
+
+    let mut used = HashSet::new();
+    let mut placed: Vec<Placed> = Vec::new();
+    for (index, input) in inputs.iter().enumerate() {
+        // 只有需要升级的模块才复制
+        let mut schematic = Cow::Borrowed(&input.schematic);
+        if schematic.data_version > 0 && schematic.data_version < report.data_version {
+            match crate::upgrade_schematic(schematic.to_mut(), report.data_version) {
+                Ok(upgrade) if upgrade.total_replaced() > 0 => report.warnings.push(ConversionWarning::new(
+                    "module_upgraded",
+                    format!(
+                        "`{}` was upgraded from DataVersion {} ({} blocks changed)",
+                        input.name, upgrade.from_data_version, upgrade.total_replaced()
+                    ),
+                )),
+                Ok(_) => {}
+                Err(e) => report.warnings.push(ConversionWarning::new(
+                    "module_not_upgraded",
+                    format!("`{}` could not be upgraded: {}", input.name, e),
+                )),
+            }
+        }
+
+        let single = schematic.regions.len() == 1;
+        let module = if input.name.is_empty() { schematic.name.clone() } else { input.name.clone() };
+        let regions: Vec<Cow<Region>> = match schematic {
+            Cow::Borrowed(schematic) => schematic.regions.iter().map(Cow::Borrowed).collect(),
+            Cow::Owned(schematic) => schematic.regions.into_iter().map(Cow::Owned).collect(),
+        };
+        for region in regions {
+            let name = if single { module.clone() } else { format!("{}/{}", module, region.name) };
+            let out_of_range = || {
+                SchematicError::Invalid(format!(
+                    "`{}` placed at offset {}, {}, {} extends beyond the coordinate range",
+                    name, input.offset.x, input.offset.y, input.offset.z
+                ))
+            };
+            // 原点与区域末端都必须仍在 i32 范围内
+            let axis = |origin: i32, offset: i32, size: i32| {
+                origin.checked_add(offset).filter(|o| o.checked_add(size).is_some()).ok_or_else(out_of_range)
+            };
+            let origin = Vec3i::new(
+                axis(region.origin.x, input.offset.x, region.size.x)?,
+                axis(region.origin.y, input.offset.y, region.size.y)?,
+                axis(region.origin.z, input.offset.z, region.size.z)?,
+            );
+            placed.push(Placed { input: index, name: unique_name(&name, &mut used), origin, region });
+        }
+    }
+
+    let mut merged = Schematic::new(options.name.clone(), report.data_version, SchematicFormat::Litematica);
+    merged.author = options.author.clone();
+    merged.description = options.description.clone();
+    if options.flatten {
+        let (region, conflicts) = flatten_regions(&placed, options.conflict_policy)?;
+        report.conflicts = conflicts;
+        report.regions.push(MergedRegion { name: region.name.clone(), input: 0, origin: region.origin, size: region.size });
+        merged.regions.push(region);
+    } else {
+        for Placed { input, name, origin, region } in placed {
+            let region = Region { name, origin, ..region.into_owned() };
+            report.regions.push(MergedRegion { name: region.name.clone(), input, origin: region.origin, size: region.size });
+            merged.regions.push(region);
+        }
+    }
+    Ok((merged, report))
+}
+
+/// 合并为覆盖整体包围盒的单个区域；空气与结构空位不参与冲突，方块实体跟随最终保留的方块
+fn flatten_regions(placed: &[Placed], policy: ConflictPolicy) -> Result<(Region, u64), SchematicError> {
+    let min = Vec3i::new(
+        placed.iter().map(|p| p.origin.x).min().unwrap_or_default(),
+        placed.iter().map(|p| p.origin.y).min().unwrap_or_default(),
+        placed.iter().map(|p| p.origin.z).min().unwrap_or_default(),
+    );
+    // 放置时已确认各区域末端不溢出
+    let max = Vec3i::new(
+        placed.iter().map(|p| p.origin.x + p.region.size.x).max().unwrap_or_default(),
+        placed.iter().map(|p| p.origin.y + p.region.size.y).max().unwrap_or_default(),
+        placed.iter().map(|p| p.origin.z + p.region.size.z).max().unwrap_or_default(),
+    );
+    let span = |max: i32, min: i32| {
+        max.checked_sub(min).ok_or_else(|| SchematicError::Invalid("Merged modules are too far apart".to_string()))
+    };
+    let size = Vec3i::new(span(max.x, min.x)?, span(max.y, min.y)?, span(max.z, min.z)?);
+    let mut merged = Region::new("Main", min, size)?;
+    let mut filled = vec![false; merged.volume()];
+    let mut conflicts = 0;
+
+    for Placed { name, origin, region, .. } in placed {
+        let remap: Vec<u32> = region.palette.iter().map(|state| merged.palette_index(state)).collect();
+        // 结构文件用结构空位表示"此处没有方块"，与空气一样不覆盖其他模块
+        let empty: Vec<bool> = region.palette.iter().map(|s| s.is_air() || s.name == STRUCTURE_VOID).collect();
+        let offset = Vec3i::new(origin.x - min.x, origin.y - min.y, origin.z - min.z);
+        let mut written = vec![false; region.volume()];
+        let mut overwritten = HashSet::new();
+        for (i, palette_index) in region.blocks.iter().enumerate() {
+            if empty[*palette_index as usize] {
+                continue;
+            }
+            let pos = region.position(i);
+            let target = merged.index(pos.x + offset.x, pos.y + offset.y, pos.z + offset.z);
+            if filled[target] {
+                conflicts += 1;
+                match policy {
+                    ConflictPolicy::Overwrite => {}
+                    ConflictPolicy::KeepFirst => continue,
+                    ConflictPolicy::Error => {
+                        return Err(SchematicError::Invalid(format!(
+                            "`{}` overlaps an earlier module at {}, {}, {}",
+                            name,
+                            min.x + pos.x + offset.x,
+                            min.y + pos.y + offset.y,
+                            min.z + pos.z + offset.z
+                        )))
+                    }
+                }
+            }
+            if filled[target] {
+                overwritten.insert(Vec3i::new(pos.x + offset.x, pos.y + offset.y, pos.z + offset.z));
+            }
+            merged.blocks[target] = remap[*palette_index as usize];
+            filled[target] = true;
+            written[i] = true;
+        }
+
+        // 被覆盖的方块不再保留先前模块的方块实体
+        merged.block_entities.retain(|be| !overwritten.contains(&be.pos));
+        let kept = |be: &&BlockEntity| region.contains(be.pos.x, be.pos.y, be.pos.z) && written[region.index(be.pos.x, be.pos.y, be.pos.z)];
+        for block_entity in region.block_entities.iter().filter(kept) {
+            let pos = Vec3i::new(block_entity.pos.x + offset.x, block_entity.pos.y + offset.y, block_entity.pos.z + offset.z);
+            merged.block_entities.push(BlockEntity { pos, ..block_entity.clone() });
+        }
+        merged.entities.extend(region.entities.iter().map(|entity| {
+            let mut entity = Entity {
+                pos: [
+                    entity.pos[0] + f64::from(offset.x),
+                    entity.pos[1] + f64::from(offset.y),
+                    entity.pos[2] + f64::from(offset.z),
+                ],
+                ..entity.clone()
+            };
+            crate::map_entity_tile(&mut entity, |p| Vec3i::new(p.x + offset.x, p.y + offset.y, p.z + offset.z));
+            entity
+        }));
+    }
+    merged.compact_palette();
+    Ok((merged, conflicts))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[wasm_bindgen]
+pub struct MergeOutput {
+    output: ConversionOutput,
+    report: MergeReport,
+}
+
+#[wasm_bindgen]
+impl MergeOutput {
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.output.data()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn format(&self) -> String {
+        self.output.format()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn extension(&self) -> String {
+        self.output.extension()
+    }
+
+    /// 组合后的 `.litematic` 与转换警告
+    #[wasm_bindgen(getter)]
+    pub fn output(&self) -> ConversionOutput {
+        self.output.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn report_json(&self) -> String {
+        serde_json::to_string(&self.report).unwrap_or_default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn warnings_json(&self) -> String {
+        self.output.warnings_json()
+    }
+}
+
+impl MergeOutput {
+    pub fn report(&self) -> &MergeReport {
+        &self.report
+    }
+
+    pub fn warnings(&self) -> &[ConversionWarning] {
+        self.output.warnings()
+    }
+}
+
+/// 供 JS 端逐个加入模块（各自按 file_id 读取），最后写出组合后的 `.litematic`
+#[wasm_bindgen]
+pub struct SchematicComposer {
+    options: MergeOptions,
+    inputs: Vec<MergeInput>,
+}
+
+#[wasm_bindgen]
+impl SchematicComposer {
+    /// options_json 见 `MergeOptions`，留空使用默认值
+    #[wasm_bindgen(constructor)]
+    pub fn new(options_json: &str) -> Result<SchematicComposer, NeptuniumError> {
+        Ok(SchematicComposer { options: MergeOptions::from_json(options_json)?, inputs: Vec::new() })
+    }
+
+    /// 加入一个模块，(x, y, z) 为放置偏移；name 为空时使用投影自身的名称
+    pub fn add(&mut self, data: &[u8], name: &str, x: i32, y: i32, z: i32) -> Result<(), NeptuniumError> {
+        let schematic = Schematic::from_bytes(data)?;
+        self.inputs.push(MergeInput { name: name.to_string(), schematic, offset: Vec3i::new(x, y, z) });
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn finish(&self) -> Result<MergeOutput, NeptuniumError> {
+        let (schematic, report) = merge_schematics(&self.inputs, &self.options)?;
+        let target = ExportFormat::Litematica;
+        let (data, warnings) = crate::write_schematic(&schematic, target)?;
+        Ok(MergeOutput { output: ConversionOutput::new(data, target, warnings), report })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BlockState;
+
+    /// 2×1×1 的模块，(0, 0, 0) 处放置给定方块
+    fn module(name: &str, block: &str, offset: Vec3i) -> MergeInput {
+        let mut region = Region::new("Main", Vec3i::new(0, 0, 0), Vec3i::new(2, 1, 1)).unwrap();
+        region.set_block(0, 0, 0, &BlockState::new(block));
+        let mut schematic = Schematic::new(name, 3700, SchematicFormat::Litematica);
+        schematic.regions.push(region);
+        MergeInput { name: name.to_string(), schematic, offset }
+    }
+
+    fn flatten(policy: ConflictPolicy) -> MergeOptions {
+        MergeOptions { flatten: true, conflict_policy: policy, ..MergeOptions::default() }
+    }
+
+    #[test]
+    fn offsets_move_each_module_and_names_stay_unique() {
+        let inputs = [
+            module("a", "minecraft:stone", Vec3i::new(0, 0, 0)),
+            module("a", "minecraft:dirt", Vec3i::new(-5, 10, 3)),
+        ];
+        let (merged, report) = merge_schematics(&inputs, &MergeOptions::default()).unwrap();
+        let placed: Vec<_> = merged.regions.iter().map(|r| (r.name.as_str(), r.origin)).collect();
+        assert_eq!(placed, [("a", Vec3i::new(0, 0, 0)), ("a_2", Vec3i::new(-5, 10, 3))]);
+        assert_eq!(report.regions[1].input, 1);
+        assert_eq!(merged.regions[1].block(0, 0, 0).name, "minecraft:dirt");
+    }
+
+    #[test]
+    fn flattening_applies_the_conflict_policy() {
+        let inputs = [
+            module("a", "minecraft:stone", Vec3i::new(0, 0, 0)),
+            module("b", "minecraft:dirt", Vec3i::new(0, 0, 0)),
+            module("c", "minecraft:glass", Vec3i::new(3, 0, 0)),
+        ];
+        let (merged, report) = merge_schematics(&inputs, &flatten(ConflictPolicy::Overwrite)).unwrap();
+        let region = &merged.regions[0];
+        assert_eq!(region.size, Vec3i::new(5, 1, 1));
+        assert_eq!(report.conflicts, 1);
+        assert_eq!(region.block(0, 0, 0).name, "minecraft:dirt");
+        assert_eq!(region.block(3, 0, 0).name, "minecraft:glass");
+
+        let (merged, _) = merge_schematics(&inputs, &flatten(ConflictPolicy::KeepFirst)).unwrap();
+        assert_eq!(merged.regions[0].block(0, 0, 0).name, "minecraft:stone");
+        assert!(merge_schematics(&inputs, &flatten(ConflictPolicy::Error)).is_err());
+    }
+
+    #[test]
+    fn offsets_beyond_the_coordinate_range_are_an_error() {
+        for offset in [Vec3i::new(i32::MAX, 0, 0), Vec3i::new(i32::MAX - 1, 0, 0)] {
+            let inputs = [module("a", "minecraft:stone", Vec3i::new(0, 0, 0)), module("b", "minecraft:dirt", offset)];
+            let result = merge_schematics(&inputs, &MergeOptions::default());
+            assert!(matches!(result, Err(SchematicError::Invalid(_))), "{:?}", offset);
+        }
+
+        // 单独放置合法，但合并后的包围盒跨度超出范围
+        let inputs = [
+            module("a", "minecraft:stone", Vec3i::new(0, 0, 0)),
+            module("b", "minecraft:dirt", Vec3i::new(i32::MIN, 0, 0)),
+        ];
+        assert!(merge_schematics(&inputs, &MergeOptions::default()).is_ok());
+        let result = merge_schematics(&inputs, &flatten(ConflictPolicy::Overwrite));
+        assert!(matches!(result, Err(SchematicError::Invalid(_))));
+    }
+}
@@ -0,0 +1,1051 @@
+use wasm_bindgen::prelude::*;
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::io::{Read, Write};
+use thiserror::Error;
+use crate::{IngestLimits, NeptuniumError, ValidationResult};
+
+// NBT 读写：Java 版为大端序，支持 gzip / zlib / 未压缩三种封装；Bedrock 版 .mcstructure 为未压缩的小端序
+
+pub const TAG_END: u8 = 0;
+pub const TAG_BYTE: u8 = 1;
+pub const TAG_SHORT: u8 = 2;
+pub const TAG_INT: u8 = 3;
+pub const TAG_LONG: u8 = 4;
+pub const TAG_FLOAT: u8 = 5;
+pub const TAG_DOUBLE: u8 = 6;
+pub const TAG_BYTE_ARRAY: u8 = 7;
+pub const TAG_STRING: u8 = 8;
+pub const TAG_LIST: u8 = 9;
+pub const TAG_COMPOUND: u8 = 10;
+pub const TAG_INT_ARRAY: u8 = 11;
+pub const TAG_LONG_ARRAY: u8 = 12;
+
+#[derive(Debug, Error)]
+pub enum NbtError {
+    #[error("Unexpected end of NBT data at offset {0}")]
+    UnexpectedEof(usize),
+    #[error("Invalid NBT tag type {tag_type} at offset {offset}")]
+    InvalidTagType { tag_type: u8, offset: usize },
+    #[error("Root tag must be a compound, found tag type {0}")]
+    InvalidRootTag(u8),
+    #[error("Negative length {length} at offset {offset}")]
+    NegativeLength { length: i32, offset: usize },
+    #[error("Invalid string encoding at offset {0}")]
+    InvalidString(usize),
+    #[error("NBT nesting deeper than {0} levels")]
+    TooDeep(usize),
+    #[error("List of {length} elements at offset {offset} exceeds the limit of {limit}")]
+    ListTooLong { length: usize, limit: usize, offset: usize },
+    #[error("Decompressed data exceeds the limit of {0} bytes")]
+    DecompressedTooLarge(usize),
+    #[error("{0} bytes of trailing data after root tag")]
+    TrailingData(usize),
+    #[error("List contains {found} tag but was declared as {expected}")]
+    ListTypeMismatch { expected: u8, found: u8 },
+    #[error("String of {0} bytes is too long to encode")]
+    StringTooLong(usize),
+    #[error("Failed to decompress data: {0}")]
+    Decompression(String),
+    #[error("Failed to compress data: {0}")]
+    Compression(String),
+    #[error("Invalid NBT JSON: {0}")]
+    Json(String),
+}
+
+impl NbtError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            NbtError::UnexpectedEof(_) => "NBT_UNEXPECTED_EOF",
+            NbtError::InvalidTagType { .. } => "NBT_INVALID_TAG_TYPE",
+            NbtError::InvalidRootTag(_) => "NBT_INVALID_ROOT",
+            NbtError::NegativeLength { .. } => "NBT_NEGATIVE_LENGTH",
+            NbtError::InvalidString(_) => "NBT_INVALID_STRING",
+            NbtError::TooDeep(_) => "NBT_TOO_DEEP",
+            NbtError::ListTooLong { .. } => "NBT_LIST_TOO_LONG",
+            NbtError::DecompressedTooLarge(_) => "NBT_DECOMPRESSED_TOO_LARGE",
+            NbtError::TrailingData(_) => "NBT_TRAILING_DATA",
+            NbtError::ListTypeMismatch { .. } => "NBT_LIST_TYPE_MISMATCH",
+            NbtError::StringTooLong(_) => "NBT_STRING_TOO_LONG",
+            NbtError::Decompression(_) => "NBT_DECOMPRESSION_FAILED",
+            NbtError::Compression(_) => "NBT_COMPRESSION_FAILED",
+            NbtError::Json(_) => "NBT_INVALID_JSON",
+        }
+    }
+}
+
+impl From<NbtError> for NeptuniumError {
+    fn from(err: NbtError) -> Self {
+        NeptuniumError::new(err.to_string(), err.code().to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NbtCompression {
+    None,
+    Gzip,
+    Zlib,
+}
+
+impl NbtCompression {
+    /// 根据魔数判断压缩方式
+    pub fn detect(data: &[u8]) -> NbtCompression {
+        match data {
+            [0x1f, 0x8b, ..] => NbtCompression::Gzip,
+            [cmf, flg, ..] if cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0 => {
+                NbtCompression::Zlib
+            }
+            _ => NbtCompression::None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NbtCompression::None => "none",
+            NbtCompression::Gzip => "gzip",
+            NbtCompression::Zlib => "zlib",
+        }
+    }
+}
+
+/// 数值与长度前缀的字节序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NbtByteOrder {
+    /// Java 版
+    #[default]
+    Big,
+    /// Bedrock 版
+    Little,
+}
+
+impl NbtByteOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NbtByteOrder::Big => "big",
+            NbtByteOrder::Little => "little",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum NbtTag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(NbtList),
+    Compound(NbtCompound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl NbtTag {
+    pub fn tag_type(&self) -> u8 {
+        match self {
+            NbtTag::Byte(_) => TAG_BYTE,
+            NbtTag::Short(_) => TAG_SHORT,
+            NbtTag::Int(_) => TAG_INT,
+            NbtTag::Long(_) => TAG_LONG,
+            NbtTag::Float(_) => TAG_FLOAT,
+            NbtTag::Double(_) => TAG_DOUBLE,
+            NbtTag::ByteArray(_) => TAG_BYTE_ARRAY,
+            NbtTag::String(_) => TAG_STRING,
+            NbtTag::List(_) => TAG_LIST,
+            NbtTag::Compound(_) => TAG_COMPOUND,
+            NbtTag::IntArray(_) => TAG_INT_ARRAY,
+            NbtTag::LongArray(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    /// 任意数值类型按整数读取（Minecraft 对数值类型的读取同样宽松）
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            NbtTag::Byte(v) => Some(i64::from(*v)),
+            NbtTag::Short(v) => Some(i64::from(*v)),
+            NbtTag::Int(v) => Some(i64::from(*v)),
+            NbtTag::Long(v) => Some(*v),
+            NbtTag::Float(v) => Some(*v as i64),
+            NbtTag::Double(v) => Some(*v as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            NbtTag::Float(v) => Some(f64::from(*v)),
+            NbtTag::Double(v) => Some(*v),
+            other => other.as_i64().map(|v| v as f64),
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            NbtTag::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&NbtCompound> {
+        match self {
+            NbtTag::Compound(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&NbtList> {
+        match self {
+            NbtTag::List(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// 列表标签。保留元素类型，使空列表也能按原样写回
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NbtList {
+    pub element_type: u8,
+    pub values: Vec<NbtTag>,
+}
+
+impl NbtList {
+    pub fn new(element_type: u8) -> NbtList {
+        NbtList { element_type, values: Vec::new() }
+    }
+
+    /// 由同类型元素构造列表，元素为空时类型记为 TAG_End
+    pub fn from_values(values: Vec<NbtTag>) -> Result<NbtList, NbtError> {
+        let element_type = values.first().map(NbtTag::tag_type).unwrap_or(TAG_END);
+        if let Some(bad) = values.iter().find(|v| v.tag_type() != element_type) {
+            return Err(NbtError::ListTypeMismatch { expected: element_type, found: bad.tag_type() });
+        }
+        Ok(NbtList { element_type, values })
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, NbtTag> {
+        self.values.iter()
+    }
+
+    pub fn compounds(&self) -> impl Iterator<Item = &NbtCompound> {
+        self.values.iter().filter_map(NbtTag::as_compound)
+    }
+}
+
+/// 复合标签。按插入顺序保存键值对，保证逐字节往返
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NbtCompound {
+    entries: Vec<(String, NbtTag)>,
+}
+
+impl NbtCompound {
+    pub fn new() -> NbtCompound {
+        NbtCompound { entries: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&NbtTag> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut NbtTag> {
+        self.entries.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// 插入或替换，已存在的键保持原有位置
+    pub fn insert(&mut self, key: impl Into<String>, value: NbtTag) -> Option<NbtTag> {
+        let key = key.into();
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => Some(std::mem::replace(existing, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<NbtTag> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &NbtTag)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(k, _)| k.as_str())
+    }
+
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get(key).and_then(NbtTag::as_i64)
+    }
+
+    pub fn get_i32(&self, key: &str) -> Option<i32> {
+        self.get_i64(key).map(|v| v as i32)
+    }
+
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.get(key).and_then(NbtTag::as_f64)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(NbtTag::as_str)
+    }
+
+    pub fn get_compound(&self, key: &str) -> Option<&NbtCompound> {
+        self.get(key).and_then(NbtTag::as_compound)
+    }
+
+    pub fn get_list(&self, key: &str) -> Option<&NbtList> {
+        self.get(key).and_then(NbtTag::as_list)
+    }
+
+    pub fn get_byte_array(&self, key: &str) -> Option<&[i8]> {
+        match self.get(key) {
+            Some(NbtTag::ByteArray(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn get_int_array(&self, key: &str) -> Option<&[i32]> {
+        match self.get(key) {
+            Some(NbtTag::IntArray(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn get_long_array(&self, key: &str) -> Option<&[i64]> {
+        match self.get(key) {
+            Some(NbtTag::LongArray(v)) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl FromIterator<(String, NbtTag)> for NbtCompound {
+    fn from_iter<I: IntoIterator<Item = (String, NbtTag)>>(iter: I) -> Self {
+        let mut compound = NbtCompound::new();
+        for (key, value) in iter {
+            compound.insert(key, value);
+        }
+        compound
+    }
+}
+
+impl Serialize for NbtCompound {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+        for (key, value) in &self.entries {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for NbtCompound {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CompoundVisitor;
+
+        impl<'de> Visitor<'de> for CompoundVisitor {
+            type Value = NbtCompound;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an NBT compound object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+                let mut compound = NbtCompound::new();
+                while let Some((key, value)) = access.next_entry::<String, NbtTag>()? {
+                    compound.insert(key, value);
+                }
+                Ok(compound)
+            }
+        }
+
+        deserializer.deserialize_map(CompoundVisitor)
+    }
+}
+
+/// 一个完整的 NBT 文件：根复合标签及其名称、原始压缩方式与字节序
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NbtDocument {
+    pub name: String,
+    pub compression: NbtCompression,
+    #[serde(default)]
+    pub byte_order: NbtByteOrder,
+    pub root: NbtCompound,
+}
+
+impl NbtDocument {
+    pub fn new(name: impl Into<String>, root: NbtCompound, compression: NbtCompression) -> NbtDocument {
+        NbtDocument { name: name.into(), compression, byte_order: NbtByteOrder::Big, root }
+    }
+
+    /// 未压缩的小端序文档（Bedrock 版）
+    pub fn new_little_endian(name: impl Into<String>, root: NbtCompound) -> NbtDocument {
+        NbtDocument { name: name.into(), compression: NbtCompression::None, byte_order: NbtByteOrder::Little, root }
+    }
+
+    /// 自动识别压缩方式并解析，使用当前配置的上限
+    pub fn from_bytes(data: &[u8]) -> Result<NbtDocument, NbtError> {
+        NbtDocument::from_bytes_with_limits(data, &IngestLimits::current())
+    }
+
+    /// 未压缩数据按大端序解析失败时再尝试小端序，两者都失败时返回大端序的错误
+    pub fn from_bytes_with_limits(data: &[u8], limits: &IngestLimits) -> Result<NbtDocument, NbtError> {
+        let compression = NbtCompression::detect(data);
+        let raw = decompress_with_limit(data, compression, limits.max_decompressed_bytes)?;
+        let (byte_order, (name, root)) = match read_root_with_limits(&raw, limits) {
+            Ok(parsed) => (NbtByteOrder::Big, parsed),
+            Err(err) if compression == NbtCompression::None => match read_root_le_with_limits(&raw, limits) {
+                Ok(parsed) => (NbtByteOrder::Little, parsed),
+                Err(_) => return Err(err),
+            },
+            Err(err) => return Err(err),
+        };
+        Ok(NbtDocument { name, compression, byte_order, root })
+    }
+
+    /// 按原压缩方式与字节序序列化
+    pub fn to_bytes(&self) -> Result<Vec<u8>, NbtError> {
+        let raw = match self.byte_order {
+            NbtByteOrder::Big => write_root(&self.name, &self.root)?,
+            NbtByteOrder::Little => write_root_le(&self.name, &self.root)?,
+        };
+        compress(&raw, self.compression)
+    }
+}
+
+pub fn decompress(data: &[u8], compression: NbtCompression) -> Result<Vec<u8>, NbtError> {
+    decompress_with_limit(data, compression, IngestLimits::current().max_decompressed_bytes)
+}
+
+/// 解压时最多读取 limit + 1 字节，超出即视为解压炸弹，不会把整个输出读入内存
+pub fn decompress_with_limit(data: &[u8], compression: NbtCompression, limit: usize) -> Result<Vec<u8>, NbtError> {
+    let mut out = Vec::new();
+    let cap = limit.saturating_add(1) as u64;
+    let result = match compression {
+        NbtCompression::None if data.len() > limit => return Err(NbtError::DecompressedTooLarge(limit)),
+        NbtCompression::None => return Ok(data.to_vec()),
+        NbtCompression::Gzip => flate2::read::MultiGzDecoder::new(data).take(cap).read_to_end(&mut out),
+        NbtCompression::Zlib => flate2::read::ZlibDecoder::new(data).take(cap).read_to_end(&mut out),
+    };
+    result.map_err(|e| NbtError::Decompression(e.to_string()))?;
+    if out.len() > limit {
+        return Err(NbtError::DecompressedTooLarge(limit));
+    }
+    Ok(out)
+}
+
+pub fn compress(raw: &[u8], compression: NbtCompression) -> Result<Vec<u8>, NbtError> {
+    let level = flate2::Compression::default();
+    let result = match compression {
+        NbtCompression::None => return Ok(raw.to_vec()),
+        NbtCompression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), level);
+            encoder.write_all(raw).and_then(|_| encoder.finish())
+        }
+        NbtCompression::Zlib => {
+            let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), level);
+            encoder.write_all(raw).and_then(|_| encoder.finish())
+        }
+    };
+    result.map_err(|e| NbtError::Compression(e.to_string()))
+}
+
+/// 解析未压缩的 NBT 数据，返回根名称与根复合标签
+pub fn read_root(data: &[u8]) -> Result<(String, NbtCompound), NbtError> {
+    read_root_with_limits(data, &IngestLimits::current())
+}
+
+pub fn read_root_with_limits(data: &[u8], limits: &IngestLimits) -> Result<(String, NbtCompound), NbtError> {
+    read_root_ordered(data, limits, NbtByteOrder::Big)
+}
+
+/// 解析小端序（Bedrock 版）NBT 数据
+pub fn read_root_le_with_limits(data: &[u8], limits: &IngestLimits) -> Result<(String, NbtCompound), NbtError> {
+    read_root_ordered(data, limits, NbtByteOrder::Little)
+}
+
+fn read_root_ordered(data: &[u8], limits: &IngestLimits, order: NbtByteOrder) -> Result<(String, NbtCompound), NbtError> {
+    let mut reader = NbtReader { data, pos: 0, limits, order };
+    let tag_type = reader.read_u8()?;
+    if tag_type != TAG_COMPOUND {
+        return Err(NbtError::InvalidRootTag(tag_type));
+    }
+    let name = reader.read_string()?;
+    let root = reader.read_compound(0)?;
+    if reader.remaining() > 0 {
+        return Err(NbtError::TrailingData(reader.remaining()));
+    }
+    Ok((name, root))
+}
+
+/// 序列化为未压缩的 NBT 数据
+pub fn write_root(name: &str, root: &NbtCompound) -> Result<Vec<u8>, NbtError> {
+    write_root_ordered(name, root, NbtByteOrder::Big)
+}
+
+/// 序列化为小端序（Bedrock 版）NBT 数据
+pub fn write_root_le(name: &str, root: &NbtCompound) -> Result<Vec<u8>, NbtError> {
+    write_root_ordered(name, root, NbtByteOrder::Little)
+}
+
+fn write_root_ordered(name: &str, root: &NbtCompound, order: NbtByteOrder) -> Result<Vec<u8>, NbtError> {
+    let mut writer = NbtWriter { buf: Vec::new(), order };
+    writer.buf.push(TAG_COMPOUND);
+    writer.write_string(name)?;
+    writer.write_compound(root)?;
+    Ok(writer.buf)
+}
+
+struct NbtReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    limits: &'a IngestLimits,
+    order: NbtByteOrder,
+}
+
+impl<'a> NbtReader<'a> {
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], NbtError> {
+        if self.remaining() < len {
+            return Err(NbtError::UnexpectedEof(self.data.len()));
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], NbtError> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, NbtError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_i16(&mut self) -> Result<i16, NbtError> {
+        let bytes = self.read_array()?;
+        Ok(match self.order {
+            NbtByteOrder::Big => i16::from_be_bytes(bytes),
+            NbtByteOrder::Little => i16::from_le_bytes(bytes),
+        })
+    }
+
+    fn read_i32(&mut self) -> Result<i32, NbtError> {
+        let bytes = self.read_array()?;
+        Ok(match self.order {
+            NbtByteOrder::Big => i32::from_be_bytes(bytes),
+            NbtByteOrder::Little => i32::from_le_bytes(bytes),
+        })
+    }
+
+    fn read_i64(&mut self) -> Result<i64, NbtError> {
+        let bytes = self.read_array()?;
+        Ok(match self.order {
+            NbtByteOrder::Big => i64::from_be_bytes(bytes),
+            NbtByteOrder::Little => i64::from_le_bytes(bytes),
+        })
+    }
+
+    /// 读取数组/列表长度，检查上限并确认剩余数据足够，避免按恶意长度预分配
+    fn read_length(&mut self, element_size: usize, limit: usize) -> Result<usize, NbtError> {
+        let offset = self.pos;
+        let length = self.read_i32()?;
+        if length < 0 {
+            return Err(NbtError::NegativeLength { length, offset });
+        }
+        let length = length as usize;
+        if length > limit {
+            return Err(NbtError::ListTooLong { length, limit, offset });
+        }
+        if length.saturating_mul(element_size) > self.remaining() {
+            return Err(NbtError::UnexpectedEof(self.data.len()));
+        }
+        Ok(length)
+    }
+
+    fn read_string(&mut self) -> Result<String, NbtError> {
+        let offset = self.pos;
+        let len = self.read_i16()? as u16 as usize;
+        let bytes = self.take(len)?;
+        let decoded = match self.order {
+            NbtByteOrder::Big => decode_mutf8(bytes),
+            // Bedrock 版字符串为标准 UTF-8
+            NbtByteOrder::Little => std::str::from_utf8(bytes).ok().map(str::to_string),
+        };
+        decoded.ok_or(NbtError::InvalidString(offset))
+    }
+
+    fn read_payload(&mut self, tag_type: u8, depth: usize) -> Result<NbtTag, NbtError> {
+        Ok(match tag_type {
+            TAG_BYTE => NbtTag::Byte(self.read_u8()? as i8),
+            TAG_SHORT => NbtTag::Short(self.read_i16()?),
+            TAG_INT => NbtTag::Int(self.read_i32()?),
+            TAG_LONG => NbtTag::Long(self.read_i64()?),
+            TAG_FLOAT => NbtTag::Float(f32::from_bits(self.read_i32()? as u32)),
+            TAG_DOUBLE => NbtTag::Double(f64::from_bits(self.read_i64()? as u64)),
+            TAG_BYTE_ARRAY => {
+                let len = self.read_length(1, self.limits.max_array_length)?;
+                NbtTag::ByteArray(self.take(len)?.iter().map(|b| *b as i8).collect())
+            }
+            TAG_STRING => NbtTag::String(self.read_string()?),
+            TAG_LIST => NbtTag::List(self.read_list(depth + 1)?),
+            TAG_COMPOUND => NbtTag::Compound(self.read_compound(depth + 1)?),
+            TAG_INT_ARRAY => {
+                let len = self.read_length(4, self.limits.max_array_length)?;
+                NbtTag::IntArray((0..len).map(|_| self.read_i32()).collect::<Result<_, _>>()?)
+            }
+            TAG_LONG_ARRAY => {
+                let len = self.read_length(8, self.limits.max_array_length)?;
+                NbtTag::LongArray((0..len).map(|_| self.read_i64()).collect::<Result<_, _>>()?)
+            }
+            other => {
+                return Err(NbtError::InvalidTagType { tag_type: other, offset: self.pos.saturating_sub(1) })
+            }
+        })
+    }
+
+    fn read_list(&mut self, depth: usize) -> Result<NbtList, NbtError> {
+        if depth > self.limits.max_depth {
+            return Err(NbtError::TooDeep(self.limits.max_depth));
+        }
+        let offset = self.pos;
+        let element_type = self.read_u8()?;
+        if element_type > TAG_LONG_ARRAY {
+            return Err(NbtError::InvalidTagType { tag_type: element_type, offset });
+        }
+        // 每个元素至少占 1 字节（TAG_End 列表除外，其长度不消耗数据）
+        let len = self.read_length(if element_type == TAG_END { 0 } else { 1 }, self.limits.max_list_length)?;
+        if element_type == TAG_END && len > 0 {
+            return Err(NbtError::InvalidTagType { tag_type: TAG_END, offset });
+        }
+        let mut values = Vec::with_capacity(len.min(self.remaining()));
+        for _ in 0..len {
+            values.push(self.read_payload(element_type, depth)?);
+        }
+        Ok(NbtList { element_type, values })
+    }
+
+    fn read_compound(&mut self, depth: usize) -> Result<NbtCompound, NbtError> {
+        if depth > self.limits.max_depth {
+            return Err(NbtError::TooDeep(self.limits.max_depth));
+        }
+        let mut compound = NbtCompound::new();
+        loop {
+            let tag_type = self.read_u8()?;
+            if tag_type == TAG_END {
+                return Ok(compound);
+            }
+            if tag_type > TAG_LONG_ARRAY {
+                return Err(NbtError::InvalidTagType { tag_type, offset: self.pos - 1 });
+            }
+            let name = self.read_string()?;
+            let value = self.read_payload(tag_type, depth)?;
+            // 重复键直接追加，保持逐字节往返
+            compound.entries.push((name, value));
+        }
+    }
+}
+
+struct NbtWriter {
+    buf: Vec<u8>,
+    order: NbtByteOrder,
+}
+
+impl NbtWriter {
+    fn write_i16(&mut self, value: i16) {
+        match self.order {
+            NbtByteOrder::Big => self.buf.extend_from_slice(&value.to_be_bytes()),
+            NbtByteOrder::Little => self.buf.extend_from_slice(&value.to_le_bytes()),
+        }
+    }
+
+    fn write_i32(&mut self, value: i32) {
+        match self.order {
+            NbtByteOrder::Big => self.buf.extend_from_slice(&value.to_be_bytes()),
+            NbtByteOrder::Little => self.buf.extend_from_slice(&value.to_le_bytes()),
+        }
+    }
+
+    fn write_i64(&mut self, value: i64) {
+        match self.order {
+            NbtByteOrder::Big => self.buf.extend_from_slice(&value.to_be_bytes()),
+            NbtByteOrder::Little => self.buf.extend_from_slice(&value.to_le_bytes()),
+        }
+    }
+
+    fn write_length(&mut self, len: usize) -> Result<(), NbtError> {
+        let len = i32::try_from(len).map_err(|_| NbtError::NegativeLength { length: -1, offset: self.buf.len() })?;
+        self.write_i32(len);
+        Ok(())
+    }
+
+    fn write_string(&mut self, value: &str) -> Result<(), NbtError> {
+        let encoded = match self.order {
+            NbtByteOrder::Big => encode_mutf8(value),
+            NbtByteOrder::Little => value.as_bytes().to_vec(),
+        };
+        let len = u16::try_from(encoded.len()).map_err(|_| NbtError::StringTooLong(encoded.len()))?;
+        self.write_i16(len as i16);
+        self.buf.extend_from_slice(&encoded);
+        Ok(())
+    }
+
+    fn write_payload(&mut self, tag: &NbtTag) -> Result<(), NbtError> {
+        match tag {
+            NbtTag::Byte(v) => self.buf.push(*v as u8),
+            NbtTag::Short(v) => self.write_i16(*v),
+            NbtTag::Int(v) => self.write_i32(*v),
+            NbtTag::Long(v) => self.write_i64(*v),
+            NbtTag::Float(v) => self.write_i32(v.to_bits() as i32),
+            NbtTag::Double(v) => self.write_i64(v.to_bits() as i64),
+            NbtTag::ByteArray(values) => {
+                self.write_length(values.len())?;
+                self.buf.extend(values.iter().map(|v| *v as u8));
+            }
+            NbtTag::String(v) => self.write_string(v)?,
+            NbtTag::List(list) => self.write_list(list)?,
+            NbtTag::Compound(compound) => self.write_compound(compound)?,
+            NbtTag::IntArray(values) => {
+                self.write_length(values.len())?;
+                for v in values {
+                    self.write_i32(*v);
+                }
+            }
+            NbtTag::LongArray(values) => {
+                self.write_length(values.len())?;
+                for v in values {
+                    self.write_i64(*v);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_list(&mut self, list: &NbtList) -> Result<(), NbtError> {
+        if let Some(bad) = list.values.iter().find(|v| v.tag_type() != list.element_type) {
+            return Err(NbtError::ListTypeMismatch { expected: list.element_type, found: bad.tag_type() });
+        }
+        self.buf.push(list.element_type);
+        self.write_length(list.values.len())?;
+        for value in &list.values {
+            self.write_payload(value)?;
+        }
+        Ok(())
+    }
+
+    fn write_compound(&mut self, compound: &NbtCompound) -> Result<(), NbtError> {
+        for (name, value) in &compound.entries {
+            self.buf.push(value.tag_type());
+            self.write_string(name)?;
+            self.write_payload(value)?;
+        }
+        self.buf.push(TAG_END);
+        Ok(())
+    }
+}
+
+/// 解码 Java 的 Modified UTF-8（`\0` 为 `C0 80`，增补平面字符为两个三字节代理项）。
+/// 标准 UTF-8 的四字节序列、裸 `\0` 与超长编码一律拒绝，保证解码后能逐字节写回
+pub(crate) fn decode_mutf8(bytes: &[u8]) -> Option<String> {
+    if bytes.iter().all(|b| *b != 0 && *b < 0x80) {
+        return std::str::from_utf8(bytes).ok().map(str::to_string);
+    }
+
+    let continuation = |i: usize| bytes.get(i).filter(|b| *b & 0xc0 == 0x80).map(|b| u16::from(b & 0x3f));
+    let mut units: Vec<u16> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b0 = bytes[i];
+        if b0 != 0 && b0 < 0x80 {
+            units.push(u16::from(b0));
+            i += 1;
+        } else if b0 & 0xe0 == 0xc0 {
+            let unit = (u16::from(b0 & 0x1f) << 6) | continuation(i + 1)?;
+            if unit != 0 && unit < 0x80 {
+                return None;
+            }
+            units.push(unit);
+            i += 2;
+        } else if b0 & 0xf0 == 0xe0 {
+            let unit = (u16::from(b0 & 0x0f) << 12) | (continuation(i + 1)? << 6) | continuation(i + 2)?;
+            if unit < 0x800 {
+                return None;
+            }
+            units.push(unit);
+            i += 3;
+        } else {
+            return None;
+        }
+    }
+    String::from_utf16(&units).ok()
+}
+
+fn encode_mutf8(value: &str) -> Vec<u8> {
+    if value.bytes().all(|b| b != 0 && b < 0x80) {
+        return value.as_bytes().to_vec();
+    }
+
+    let mut out = Vec::with_capacity(value.len() + 8);
+    for unit in value.encode_utf16() {
+        match unit {
+            0x0001..=0x007f => out.push(unit as u8),
+            0x0000 | 0x0080..=0x07ff => {
+                out.push(0xc0 | ((unit >> 6) & 0x1f) as u8);
+                out.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                out.push(0xe0 | ((unit >> 12) & 0x0f) as u8);
+                out.push(0x80 | ((unit >> 6) & 0x3f) as u8);
+                out.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+    out
+}
+
+// 导出的函数
+#[wasm_bindgen]
+pub fn detect_nbt_compression(data: &[u8]) -> String {
+    NbtCompression::detect(data).as_str().to_string()
+}
+
+#[wasm_bindgen]
+pub fn validate_nbt_data(data: &[u8]) -> ValidationResult {
+    match NbtDocument::from_bytes(data) {
+        Ok(_) => ValidationResult::new(true, "Valid NBT data".to_string()),
+        Err(err) => ValidationResult::new(false, err.to_string()),
+    }
+}
+
+/// 将 NBT 文件解析为带类型信息的 JSON，可由 `nbt_from_json` 原样写回
+#[wasm_bindgen]
+pub fn nbt_to_json(data: &[u8]) -> Result<String, NeptuniumError> {
+    let document = NbtDocument::from_bytes(data)?;
+    serde_json::to_string(&document).map_err(|e| NbtError::Json(e.to_string()).into())
+}
+
+#[wasm_bindgen]
+pub fn nbt_from_json(json: &str) -> Result<Vec<u8>, NeptuniumError> {
+    let document: NbtDocument = serde_json::from_str(json).map_err(|e| NbtError::Json(e.to_string()))?;
+    Ok(document.to_bytes()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_root() -> NbtCompound {
+        let mut nested = NbtCompound::new();
+        nested.insert("id", NbtTag::String("minecraft:chest".to_string()));
+        nested.insert("Count", NbtTag::Byte(64));
+        let mut root = NbtCompound::new();
+        root.insert("byte", NbtTag::Byte(-128));
+        root.insert("short", NbtTag::Short(-12345));
+        root.insert("int", NbtTag::Int(i32::MIN));
+        root.insert("long", NbtTag::Long(i64::MAX));
+        root.insert("float", NbtTag::Float(-1.5e-3));
+        root.insert("double", NbtTag::Double(std::f64::consts::PI));
+        root.insert("byte_array", NbtTag::ByteArray(vec![0, -1, 127, -128]));
+        root.insert("string", NbtTag::String("nul\0 é 中文 😀".to_string()));
+        root.insert("empty_list", NbtTag::List(NbtList::new(TAG_END)));
+        root.insert("int_list", NbtTag::List(NbtList::from_values(vec![NbtTag::Int(1), NbtTag::Int(-2)]).unwrap()));
+        let compounds = NbtList::from_values(vec![NbtTag::Compound(nested.clone())]).unwrap();
+        root.insert("compound_list", NbtTag::List(compounds));
+        root.insert(
+            "list_list",
+            NbtTag::List(NbtList::from_values(vec![NbtTag::List(NbtList::new(TAG_STRING))]).unwrap()),
+        );
+        root.insert("compound", NbtTag::Compound(nested));
+        root.insert("int_array", NbtTag::IntArray(vec![i32::MAX, 0, -7]));
+        root.insert("long_array", NbtTag::LongArray(vec![i64::MIN, 42]));
+        root
+    }
+
+    fn parse(data: &[u8], order: NbtByteOrder) -> Result<(String, NbtCompound), NbtError> {
+        read_root_ordered(data, &IngestLimits::DEFAULT, order)
+    }
+
+    /// 大端序根复合标签：类型、空名称、内容、TAG_End
+    fn big_endian_root(payload: &[u8]) -> Vec<u8> {
+        let mut data = vec![TAG_COMPOUND, 0, 0];
+        data.extend_from_slice(payload);
+        data.push(TAG_END);
+        data
+    }
+
+    #[test]
+    fn round_trips_every_tag_type_in_both_byte_orders() {
+        let root = sample_root();
+        for order in [NbtByteOrder::Big, NbtByteOrder::Little] {
+            let bytes = write_root_ordered("Schematic", &root, order).unwrap();
+            let (name, parsed) = parse(&bytes, order).unwrap();
+            assert_eq!(name, "Schematic");
+            assert_eq!(parsed, root);
+            assert_eq!(write_root_ordered(&name, &parsed, order).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn round_trips_hand_written_bytes_exactly() {
+        // 重复键、TAG_End 空列表与非零元素类型的空列表都须原样保留
+        let data = big_endian_root(&[
+            TAG_BYTE, 0, 1, b'a', 1, //
+            TAG_BYTE, 0, 1, b'a', 2, //
+            TAG_LIST, 0, 1, b'e', TAG_END, 0, 0, 0, 0, //
+            TAG_LIST, 0, 1, b'l', TAG_LONG, 0, 0, 0, 0, //
+            TAG_STRING, 0, 1, b's', 0, 2, 0xc0, 0x80,
+        ]);
+        let (name, root) = parse(&data, NbtByteOrder::Big).unwrap();
+        assert_eq!(root.len(), 5);
+        assert_eq!(write_root(&name, &root).unwrap(), data);
+    }
+
+    #[test]
+    fn round_trips_compressed_documents() {
+        for compression in [NbtCompression::None, NbtCompression::Gzip, NbtCompression::Zlib] {
+            let document = NbtDocument::new("", sample_root(), compression);
+            let bytes = document.to_bytes().unwrap();
+            assert_eq!(NbtDocument::from_bytes_with_limits(&bytes, &IngestLimits::DEFAULT).unwrap(), document);
+        }
+        let document = NbtDocument::new_little_endian("", sample_root());
+        let bytes = document.to_bytes().unwrap();
+        assert_eq!(NbtDocument::from_bytes_with_limits(&bytes, &IngestLimits::DEFAULT).unwrap(), document);
+    }
+
+    #[test]
+    fn rejects_truncation_at_every_offset() {
+        let root = sample_root();
+        for order in [NbtByteOrder::Big, NbtByteOrder::Little] {
+            let bytes = write_root_ordered("", &root, order).unwrap();
+            for end in 0..bytes.len() {
+                assert!(parse(&bytes[..end], order).is_err(), "{:?} prefix of {} bytes was accepted", order, end);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_data() {
+        let mut data = big_endian_root(&[]);
+        data.push(0);
+        assert!(matches!(parse(&data, NbtByteOrder::Big), Err(NbtError::TrailingData(1))));
+    }
+
+    #[test]
+    fn rejects_bad_tag_ids() {
+        assert!(matches!(parse(&[TAG_LIST, 0, 0], NbtByteOrder::Big), Err(NbtError::InvalidRootTag(TAG_LIST))));
+        let entry = big_endian_root(&[13, 0, 1, b'x', 0]);
+        assert!(matches!(parse(&entry, NbtByteOrder::Big), Err(NbtError::InvalidTagType { tag_type: 13, .. })));
+        let element = big_endian_root(&[TAG_LIST, 0, 1, b'x', 0xff, 0, 0, 0, 0]);
+        assert!(matches!(parse(&element, NbtByteOrder::Big), Err(NbtError::InvalidTagType { tag_type: 0xff, .. })));
+        let end_list = big_endian_root(&[TAG_LIST, 0, 1, b'x', TAG_END, 0, 0, 0, 1]);
+        assert!(matches!(parse(&end_list, NbtByteOrder::Big), Err(NbtError::InvalidTagType { tag_type: TAG_END, .. })));
+    }
+
+    #[test]
+    fn rejects_negative_lengths() {
+        let cases = [(TAG_BYTE_ARRAY, None), (TAG_INT_ARRAY, None), (TAG_LONG_ARRAY, None), (TAG_LIST, Some(TAG_INT))];
+        for (tag_type, element) in cases {
+            let mut payload = vec![tag_type, 0, 1, b'x'];
+            payload.extend(element);
+            payload.extend_from_slice(&(-5i32).to_be_bytes());
+            let result = parse(&big_endian_root(&payload), NbtByteOrder::Big);
+            assert!(matches!(result, Err(NbtError::NegativeLength { length: -5, .. })), "tag type {}", tag_type);
+        }
+    }
+
+    #[test]
+    fn rejects_lengths_beyond_the_remaining_data() {
+        let data = big_endian_root(&[TAG_LONG_ARRAY, 0, 1, b'x', 0x7f, 0xff, 0xff, 0xff]);
+        let limits = IngestLimits { max_array_length: usize::MAX, ..IngestLimits::DEFAULT };
+        assert!(matches!(read_root_ordered(&data, &limits, NbtByteOrder::Big), Err(NbtError::UnexpectedEof(_))));
+    }
+
+    #[test]
+    fn enforces_the_depth_limit() {
+        let limits = IngestLimits { max_depth: 4, ..IngestLimits::DEFAULT };
+        let nest = |levels: usize| {
+            let mut compound = NbtCompound::new();
+            for _ in 0..levels {
+                let mut parent = NbtCompound::new();
+                parent.insert("c", NbtTag::Compound(compound));
+                compound = parent;
+            }
+            write_root("", &compound).unwrap()
+        };
+        assert!(read_root_with_limits(&nest(4), &limits).is_ok());
+        assert!(matches!(read_root_with_limits(&nest(5), &limits), Err(NbtError::TooDeep(4))));
+
+        let mut list = NbtTag::Int(0);
+        for _ in 0..5 {
+            list = NbtTag::List(NbtList::from_values(vec![list]).unwrap());
+        }
+        let mut root = NbtCompound::new();
+        root.insert("l", list);
+        let data = write_root("", &root).unwrap();
+        assert!(matches!(read_root_with_limits(&data, &limits), Err(NbtError::TooDeep(4))));
+    }
+
+    #[test]
+    fn encodes_modified_utf8() {
+        assert_eq!(encode_mutf8("\0"), [0xc0, 0x80]);
+        assert_eq!(encode_mutf8("😀"), [0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80]);
+        assert_eq!(decode_mutf8(&[0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80]).as_deref(), Some("😀"));
+        assert_eq!(decode_mutf8(&[0xc0, 0x80]).as_deref(), Some("\0"));
+    }
+
+    #[test]
+    fn little_endian_strings_are_plain_utf8() {
+        let mut root = NbtCompound::new();
+        root.insert("s", NbtTag::String("\0😀".to_string()));
+        let bytes = write_root_le("", &root).unwrap();
+        assert!(bytes.ends_with(&[5, 0, 0, 0xf0, 0x9f, 0x98, 0x80, TAG_END]));
+        assert_eq!(parse(&bytes, NbtByteOrder::Little).unwrap().1, root);
+    }
+
+    #[test]
+    fn rejects_strings_that_would_not_round_trip() {
+        // 标准 UTF-8 的四字节字符、裸 NUL、超长编码、孤立代理项
+        for bytes in [&b"\xf0\x9f\x98\x80"[..], b"a\0b", b"\xc1\x81", b"\xe0\x81\x81", b"\xed\xa0\xbd", b"\xc3"] {
+            assert_eq!(decode_mutf8(bytes), None, "{:02x?}", bytes);
+            let mut payload = vec![TAG_STRING, 0, 1, b's'];
+            payload.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+            payload.extend_from_slice(bytes);
+            assert!(matches!(parse(&big_endian_root(&payload), NbtByteOrder::Big), Err(NbtError::InvalidString(_))));
+        }
+    }
+}
@@ -0,0 +1,445 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::OnceLock;
+use crate::{
+    display_locale, display_name, glob_captures, normalize_id, MaterialEntry, MaterialList, MaterialMode, NeptuniumError,
+    Schematic,
+};
+
+// 原材料估算：按内置原版配方把物品清单逐级展开到基础资源（原木、圆石、矿物等）
+
+const RECIPES: &str = include_str!("data/recipes.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipeMethod {
+    Crafting,
+    /// 烧炼，不计燃料
+    Smelting,
+    Stonecutting,
+    /// 游戏内转换：斧剥皮、混凝土粉遇水等
+    Conversion,
+    /// 把存储方块拆回原物品，只在没有其他配方时使用
+    Unpacking,
+}
+
+impl RecipeMethod {
+    pub const ALL: [RecipeMethod; 5] = [
+        RecipeMethod::Crafting,
+        RecipeMethod::Smelting,
+        RecipeMethod::Stonecutting,
+        RecipeMethod::Conversion,
+        RecipeMethod::Unpacking,
+    ];
+
+    pub fn from_name(name: &str) -> Option<RecipeMethod> {
+        RecipeMethod::ALL.into_iter().find(|m| m.as_str() == name)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecipeMethod::Crafting => "crafting",
+            RecipeMethod::Smelting => "smelting",
+            RecipeMethod::Stonecutting => "stonecutting",
+            RecipeMethod::Conversion => "conversion",
+            RecipeMethod::Unpacking => "unpacking",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recipe {
+    pub method: RecipeMethod,
+    pub output: String,
+    /// 每次合成的产量
+    pub count: u32,
+    /// 原料 id 与数量；以 # 开头的是标签
+    pub inputs: Vec<(String, u32)>,
+}
+
+impl Recipe {
+    /// 例如 "crafting: 6 minecraft:stone"
+    pub fn describe(&self) -> String {
+        let inputs: Vec<String> = self.inputs.iter().map(|(id, count)| format!("{} {}", count, id)).collect();
+        format!("{}: {}", self.method.as_str(), inputs.join(" + "))
+    }
+
+    fn input_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self.inputs.iter().map(|(id, _)| id.as_str()).collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+struct RecipeBook {
+    /// 产物 → 配方，保持表中顺序
+    recipes: HashMap<String, Vec<Recipe>>,
+    /// "#minecraft:planks" → 成员
+    tags: HashMap<String, Vec<String>>,
+}
+
+fn ingredient_id(id: &str) -> String {
+    match id.strip_prefix('#') {
+        Some(tag) => format!("#{}", normalize_id(tag)),
+        None => normalize_id(id),
+    }
+}
+
+fn recipe_book() -> &'static RecipeBook {
+    static BOOK: OnceLock<RecipeBook> = OnceLock::new();
+    BOOK.get_or_init(|| {
+        let mut recipes: HashMap<String, Vec<Recipe>> = HashMap::new();
+        let mut tags = HashMap::new();
+        for line in RECIPES.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if tokens[0] == "tag" && tokens.len() > 2 {
+                let members = tokens[2..].iter().map(|t| normalize_id(t)).collect();
+                tags.insert(ingredient_id(&format!("#{}", tokens[1])), members);
+                continue;
+            }
+            let (Some(method), Some(count), Some(&"<-")) =
+                (RecipeMethod::from_name(tokens[0]), tokens.get(2).and_then(|c| c.parse::<u32>().ok()), tokens.get(3))
+            else {
+                continue;
+            };
+            let inputs: Vec<(String, u32)> = tokens[4..]
+                .chunks_exact(2)
+                .filter_map(|pair| Some((ingredient_id(pair[0]), pair[1].parse::<u32>().ok()?)))
+                .collect();
+            if count == 0 || inputs.is_empty() {
+                continue;
+            }
+            let output = normalize_id(tokens[1]);
+            recipes.entry(output.clone()).or_default().push(Recipe { method, output, count, inputs });
+        }
+        RecipeBook { recipes, tags }
+    })
+}
+
+/// 内置表中产出该物品的配方，按优先顺序排列
+pub fn recipes_for(item: &str) -> &'static [Recipe] {
+    recipe_book().recipes.get(&normalize_id(item)).map(Vec::as_slice).unwrap_or(&[])
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceOptions {
+    /// 视为基础资源、不再展开的物品，可用 `*` 通配（如 "*_ingot"）；没有配方的物品总是基础资源
+    pub base_items: Vec<String>,
+    /// 允许使用的配方方式
+    pub methods: Vec<RecipeMethod>,
+    /// 有多种配方或标签时的首选原料：物品或 "#标签" → 原料 id
+    pub prefer: BTreeMap<String, String>,
+}
+
+impl Default for ResourceOptions {
+    fn default() -> Self {
+        ResourceOptions { base_items: Vec::new(), methods: RecipeMethod::ALL.to_vec(), prefer: BTreeMap::new() }
+    }
+}
+
+impl ResourceOptions {
+    pub fn from_json(json: &str) -> Result<ResourceOptions, NeptuniumError> {
+        if json.trim().is_empty() {
+            return Ok(ResourceOptions::default());
+        }
+        serde_json::from_str(json).map_err(|e| {
+            NeptuniumError::new(format!("Invalid resource options: {}", e), "INVALID_RESOURCE_OPTIONS".to_string())
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CraftStep {
+    pub item: String,
+    pub name: String,
+    pub method: RecipeMethod,
+    /// 合成次数
+    pub crafts: u64,
+    pub produced: u64,
+    pub needed: u64,
+    /// 按产量向上取整后多出的数量
+    pub surplus: u64,
+    /// 本步骤消耗的原料总数
+    pub inputs: Vec<MaterialEntry>,
+}
+
+/// 配方依赖成环的物品链（首尾相同）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecipeCycle {
+    pub items: Vec<String>,
+    /// 环在此处断开，按基础资源计；环上有拆解存储方块的一步时在那里断开
+    pub base_item: String,
+}
+
+/// 存在多种可选配方（或标签成员）时实际采用的一种
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecipeChoice {
+    pub item: String,
+    pub chosen: String,
+    pub alternatives: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceEstimate {
+    /// 需要收集的基础资源
+    pub base: MaterialList,
+    /// 按执行顺序排列，先做的在前
+    pub steps: Vec<CraftStep>,
+    pub cycles: Vec<RecipeCycle>,
+    /// 未通过 prefer 指定、按表中顺序取了第一种的物品与标签
+    pub ambiguous: Vec<RecipeChoice>,
+}
+
+impl ResourceEstimate {
+    pub fn from_schematic(schematic: &Schematic, options: &ResourceOptions) -> ResourceEstimate {
+        ResourceEstimate::from_list(&MaterialList::from_schematic(schematic, MaterialMode::Items), options)
+    }
+
+    /// 展开物品清单；先汇总同一中间产物的全部需求再按产量取整，避免逐条取整造成的浪费
+    pub fn from_list(list: &MaterialList, options: &ResourceOptions) -> ResourceEstimate {
+        let mut resolver = Resolver::new(options);
+        for entry in &list.entries {
+            resolver.visit(&entry.id);
+        }
+
+        let mut demand: HashMap<String, u64> = list.entries.iter().map(|e| (e.id.clone(), e.count)).collect();
+        let mut base: HashMap<String, u64> = HashMap::new();
+        let mut steps = Vec::new();
+        let locale = display_locale();
+        // 后序的逆序：所有消费者都先于其原料处理
+        for item in resolver.order.iter().rev() {
+            let needed = demand.get(item).copied().unwrap_or(0);
+            if needed == 0 {
+                continue;
+            }
+            let Some(Some(choice)) = resolver.chosen.get(item) else {
+                *base.entry(item.clone()).or_default() += needed;
+                continue;
+            };
+            let per_craft = u64::from(choice.recipe.count);
+            let crafts = needed.div_ceil(per_craft);
+            let mut inputs = Vec::new();
+            for (input, count) in &choice.inputs {
+                let total = crafts * u64::from(*count);
+                *demand.entry(input.clone()).or_default() += total;
+                inputs.push(MaterialEntry::new(input.clone(), total));
+            }
+            steps.push(CraftStep {
+                item: item.clone(),
+                name: display_name(item, &locale),
+                method: choice.recipe.method,
+                crafts,
+                produced: crafts * per_craft,
+                needed,
+                surplus: crafts * per_craft - needed,
+                inputs,
+            });
+        }
+        steps.reverse();
+
+        ResourceEstimate {
+            base: MaterialList::from_counts(base, MaterialMode::Items),
+            steps,
+            cycles: resolver.cycles,
+            ambiguous: resolver.ambiguous,
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        self.base.to_csv()
+    }
+}
+
+struct Choice {
+    recipe: &'static Recipe,
+    /// 标签已替换为具体物品
+    inputs: Vec<(String, u32)>,
+}
+
+struct Resolver<'a> {
+    book: &'static RecipeBook,
+    options: &'a ResourceOptions,
+    base_patterns: Vec<String>,
+    prefer: HashMap<String, String>,
+    /// 物品 → 采用的配方；None 为基础资源
+    chosen: HashMap<String, Option<Choice>>,
+    /// 当前展开路径及各物品采用的配方方式，用于发现环
+    path: Vec<(String, Option<RecipeMethod>)>,
+    /// 路径上因成环而改按基础资源计的物品
+    broken: HashSet<String>,
+    /// 后序：原料先于产物
+    order: Vec<String>,
+    reported_tags: HashSet<String>,
+    cycles: Vec<RecipeCycle>,
+    ambiguous: Vec<RecipeChoice>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(options: &'a ResourceOptions) -> Resolver<'a> {
+        Resolver {
+            book: recipe_book(),
+            options,
+            base_patterns: options.base_items.iter().map(|p| ingredient_id(p)).collect(),
+            prefer: options.prefer.iter().map(|(k, v)| (ingredient_id(k), normalize_id(v))).collect(),
+            chosen: HashMap::new(),
+            path: Vec::new(),
+            broken: HashSet::new(),
+            order: Vec::new(),
+            reported_tags: HashSet::new(),
+            cycles: Vec::new(),
+            ambiguous: Vec::new(),
+        }
+    }
+
+    fn visit(&mut self, item: &str) {
+        if self.chosen.contains_key(item) {
+            return;
+        }
+        self.path.push((item.to_string(), None));
+        let mut choice = self.choose(item);
+        let current = self.path.len() - 1;
+        self.path[current].1 = choice.as_ref().map(|c| c.recipe.method);
+        let inputs: Vec<String> = choice.iter().flat_map(|c| c.inputs.iter().map(|(id, _)| id.clone())).collect();
+        for input in inputs {
+            let Some(start) = self.path.iter().position(|(p, _)| *p == input) else {
+                self.visit(&input);
+                continue;
+            };
+            let unpacking = (start..=current).find(|&i| self.path[i].1 == Some(RecipeMethod::Unpacking));
+            let breaking = unpacking.unwrap_or(current);
+            self.record_cycle(start, &input, breaking);
+            if breaking == current {
+                choice = None;
+                break;
+            }
+            let ancestor = self.path[breaking].0.clone();
+            self.broken.insert(ancestor);
+        }
+        self.path.pop();
+        if self.broken.remove(item) {
+            choice = None;
+        }
+        self.chosen.insert(item.to_string(), choice);
+        self.order.push(item.to_string());
+    }
+
+    fn record_cycle(&mut self, start: usize, closing: &str, breaking: usize) {
+        let mut items: Vec<String> = self.path[start..].iter().map(|(p, _)| p.clone()).collect();
+        items.push(closing.to_string());
+        let cycle = RecipeCycle { items, base_item: self.path[breaking].0.clone() };
+        if !self.cycles.contains(&cycle) {
+            self.cycles.push(cycle);
+        }
+    }
+
+    fn is_base(&self, item: &str) -> bool {
+        self.base_patterns.iter().any(|pattern| glob_captures(pattern, item).is_some())
+    }
+
+    fn tag_member(&self, tag: &str) -> String {
+        let Some(members) = self.book.tags.get(tag) else {
+            return tag.trim_start_matches('#').to_string();
+        };
+        match self.prefer.get(tag) {
+            Some(preferred) if members.contains(preferred) => preferred.clone(),
+            _ => members[0].clone(),
+        }
+    }
+
+    fn resolve_inputs(&self, recipe: &Recipe) -> Vec<(String, u32)> {
+        recipe
+            .inputs
+            .iter()
+            .map(|(id, count)| if id.starts_with('#') { (self.tag_member(id), *count) } else { (id.clone(), *count) })
+            .collect()
+    }
+
+    /// 选择配方：跳过会立即回到当前路径的配方，拆解存储方块排在最后，prefer 指定的原料优先
+    fn choose(&mut self, item: &str) -> Option<Choice> {
+        if self.is_base(item) {
+            return None;
+        }
+        let book = self.book;
+        let mut allowed: Vec<&'static Recipe> = book
+            .recipes
+            .get(item)
+            .map(|recipes| recipes.iter().filter(|r| self.options.methods.contains(&r.method)).collect())
+            .unwrap_or_default();
+        allowed.sort_by_key(|r| r.method == RecipeMethod::Unpacking);
+
+        let mut usable = Vec::new();
+        let mut blocked = None;
+        for recipe in allowed {
+            let inputs = self.resolve_inputs(recipe);
+            // 经过拆解存储方块的环在拆解处断开，本配方仍可使用
+            let cycle_start = inputs.iter().find_map(|(id, _)| self.path.iter().position(|(p, _)| p == id));
+            let unpacks = |start: usize| self.path[start..].iter().any(|(_, m)| *m == Some(RecipeMethod::Unpacking));
+            match cycle_start.filter(|&start| !unpacks(start)) {
+                Some(start) => {
+                    blocked.get_or_insert((start, self.path[start].0.clone()));
+                }
+                None => usable.push(Choice { recipe, inputs }),
+            }
+        }
+        if usable.is_empty() {
+            if let Some((start, closing)) = blocked {
+                self.record_cycle(start, &closing, self.path.len() - 1);
+            }
+            return None;
+        }
+
+        let preferred = self.prefer.get(item).and_then(|preferred| {
+            usable.iter().position(|c| c.inputs.iter().any(|(id, _)| id == preferred))
+        });
+        let choice = usable.remove(preferred.unwrap_or(0));
+        if preferred.is_none() {
+            let chosen_ids = choice.recipe.input_ids();
+            let mut seen = vec![chosen_ids];
+            let mut alternatives = Vec::new();
+            for other in usable.iter().filter(|c| c.recipe.method != RecipeMethod::Unpacking) {
+                let ids = other.recipe.input_ids();
+                if !seen.contains(&ids) {
+                    seen.push(ids);
+                    alternatives.push(other.recipe.describe());
+                }
+            }
+            if !alternatives.is_empty() {
+                let chosen = choice.recipe.describe();
+                self.ambiguous.push(RecipeChoice { item: item.to_string(), chosen, alternatives });
+            }
+        }
+        self.report_tags(choice.recipe);
+        Some(choice)
+    }
+
+    fn report_tags(&mut self, recipe: &Recipe) {
+        for (tag, _) in recipe.inputs.iter().filter(|(id, _)| id.starts_with('#')) {
+            if self.prefer.contains_key(tag) || !self.reported_tags.insert(tag.clone()) {
+                continue;
+            }
+            let Some(members) = self.book.tags.get(tag).filter(|m| m.len() > 1) else { continue };
+            let chosen = self.tag_member(tag);
+            let alternatives = members.iter().filter(|m| **m != chosen).cloned().collect();
+            self.ambiguous.push(RecipeChoice { item: tag.clone(), chosen, alternatives });
+        }
+    }
+}
+
+// 导出的函数
+
+/// 把投影的物品清单展开到基础资源（JSON，见 `ResourceEstimate`）；options_json 见 `ResourceOptions`，可留空
+#[wasm_bindgen]
+pub fn estimate_raw_resources(data: &[u8], options_json: &str) -> Result<String, NeptuniumError> {
+    let schematic = Schematic::from_bytes(data)?;
+    let estimate = ResourceEstimate::from_schematic(&schematic, &ResourceOptions::from_json(options_json)?);
+    Ok(serde_json::to_string(&estimate).unwrap_or_default())
+}
+
+/// 基础资源清单（CSV）
+#[wasm_bindgen]
+pub fn estimate_raw_resources_csv(data: &[u8], options_json: &str) -> Result<String, NeptuniumError> {
+    let schematic = Schematic::from_bytes(data)?;
+    Ok(ResourceEstimate::from_schematic(&schematic, &ResourceOptions::from_json(options_json)?).to_csv())
+}
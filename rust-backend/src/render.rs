@@ -0,0 +1,487 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use thiserror::Error;
+use crate::{BlockState, NeptuniumError, Region, Schematic, SchematicError, STRUCTURE_VOID};
+
+// 纯 CPU 的投影预览渲染：等距视图与俯视图，每种方块使用一种平均颜色
+
+pub const DEFAULT_PREVIEW_SIZE: u32 = 256;
+pub const MAX_PREVIEW_SIZE: u32 = 2048;
+
+/// 中间光栅的像素上限，超出时拒绝渲染
+const MAX_RASTER_PIXELS: u64 = 1 << 26;
+
+const BLOCK_COLORS: &str = include_str!("data/block_colors.txt");
+
+const DYE_COLORS: [&str; 16] = [
+    "white", "orange", "magenta", "light_blue", "yellow", "lime", "pink", "gray",
+    "light_gray", "cyan", "purple", "blue", "brown", "green", "red", "black",
+];
+
+const WOOD_TYPES: [&str; 12] = [
+    "oak", "spruce", "birch", "jungle", "acacia", "dark_oak", "mangrove", "cherry", "bamboo", "crimson",
+    "warped", "pale_oak",
+];
+
+// 去掉形状后缀后按基础方块取色，如 stone_brick_stairs → stone_bricks
+const SHAPE_SUFFIXES: [&str; 13] = [
+    "_wall_hanging_sign", "_hanging_sign", "_wall_sign", "_sign", "_stairs", "_slab", "_wall", "_fence_gate",
+    "_fence", "_pressure_plate", "_button", "_trapdoor", "_door",
+];
+
+#[derive(Debug, Error)]
+pub enum RenderError {
+    #[error(transparent)]
+    Schematic(#[from] SchematicError),
+    #[error("Preview size must be between 16 and {max}, got {0}", max = MAX_PREVIEW_SIZE)]
+    InvalidSize(u32),
+    #[error("Schematic is too large to render a preview")]
+    TooLarge,
+    #[error("PNG encoding failed: {0}")]
+    Encode(String),
+}
+
+impl RenderError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RenderError::Schematic(err) => err.code(),
+            RenderError::InvalidSize(_) => "RENDER_INVALID_SIZE",
+            RenderError::TooLarge => "RENDER_TOO_LARGE",
+            RenderError::Encode(_) => "RENDER_ENCODE_FAILED",
+        }
+    }
+}
+
+impl From<RenderError> for NeptuniumError {
+    fn from(err: RenderError) -> Self {
+        NeptuniumError::new(err.to_string(), err.code().to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewView {
+    Isometric,
+    TopDown,
+}
+
+impl PreviewView {
+    pub fn from_name(name: &str) -> Option<PreviewView> {
+        match name.to_lowercase().as_str() {
+            "" | "isometric" | "iso" => Some(PreviewView::Isometric),
+            "top_down" | "topdown" | "top" => Some(PreviewView::TopDown),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RenderOptions {
+    pub view: PreviewView,
+    /// 输出图像的最大宽/高（像素）
+    pub size: u32,
+    /// 背景色 RGBA，默认透明
+    pub background: [u8; 4],
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions { view: PreviewView::Isometric, size: DEFAULT_PREVIEW_SIZE, background: [0, 0, 0, 0] }
+    }
+}
+
+/// 8 位 RGBA 图像，按行存储
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn new(width: u32, height: u32, background: [u8; 4]) -> RgbaImage {
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for _ in 0..width as usize * height as usize {
+            pixels.extend_from_slice(&background);
+        }
+        RgbaImage { width, height, pixels }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    /// 以 source-over 方式叠加一个像素
+    pub fn blend(&mut self, x: u32, y: u32, color: [u8; 4]) {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        let dst = &mut self.pixels[i..i + 4];
+        if color[3] == 255 || dst[3] == 0 {
+            dst.copy_from_slice(&color);
+            return;
+        }
+        let sa = f32::from(color[3]) / 255.0;
+        let da = f32::from(dst[3]) / 255.0;
+        let out_a = sa + da * (1.0 - sa);
+        for c in 0..3 {
+            let value = (f32::from(color[c]) * sa + f32::from(dst[c]) * da * (1.0 - sa)) / out_a;
+            dst[c] = value.round() as u8;
+        }
+        dst[3] = (out_a * 255.0).round() as u8;
+    }
+
+    /// 按整数倍缩小，颜色按透明度加权平均
+    pub fn downscale(&self, factor: u32) -> RgbaImage {
+        if factor <= 1 {
+            return self.clone();
+        }
+        let width = self.width.div_ceil(factor);
+        let height = self.height.div_ceil(factor);
+        let mut out = RgbaImage::new(width, height, [0, 0, 0, 0]);
+        for oy in 0..height {
+            for ox in 0..width {
+                let mut sum = [0u64; 4];
+                let mut samples = 0u64;
+                for y in oy * factor..((oy + 1) * factor).min(self.height) {
+                    for x in ox * factor..((ox + 1) * factor).min(self.width) {
+                        let p = self.pixel(x, y);
+                        let a = u64::from(p[3]);
+                        for c in 0..3 {
+                            sum[c] += u64::from(p[c]) * a;
+                        }
+                        sum[3] += a;
+                        samples += 1;
+                    }
+                }
+                let i = (oy as usize * width as usize + ox as usize) * 4;
+                for c in 0..3 {
+                    out.pixels[i + c] = sum[c].checked_div(sum[3]).unwrap_or(0) as u8;
+                }
+                out.pixels[i + 3] = (sum[3] / samples) as u8;
+            }
+        }
+        out
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, RenderError> {
+        encode_png(self.width, self.height, &self.pixels)
+    }
+}
+
+/// 将 RGBA 像素编码为 PNG
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>, RenderError> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| RenderError::Encode(e.to_string()))?;
+        writer.write_image_data(rgba).map_err(|e| RenderError::Encode(e.to_string()))?;
+    }
+    Ok(out)
+}
+
+fn color_table() -> &'static HashMap<&'static str, [u8; 4]> {
+    static TABLE: OnceLock<HashMap<&'static str, [u8; 4]>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        BLOCK_COLORS
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .filter_map(|line| {
+                let (name, hex) = line.split_once(' ')?;
+                Some((name, parse_hex_color(hex.trim())?))
+            })
+            .collect()
+    })
+}
+
+pub(crate) fn parse_hex_color(hex: &str) -> Option<[u8; 4]> {
+    if hex.len() != 6 && hex.len() != 8 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Some([channel(0)?, channel(2)?, channel(4)?, alpha])
+}
+
+fn scale_color(color: [u8; 4], factor: f32) -> [u8; 4] {
+    let scale = |c: u8| (f32::from(c) * factor).round().clamp(0.0, 255.0) as u8;
+    [scale(color[0]), scale(color[1]), scale(color[2]), color[3]]
+}
+
+/// 方块的预览颜色；空气、结构空位等不可见方块返回 None
+pub fn block_color(state: &BlockState) -> Option<[u8; 4]> {
+    if state.is_air() || state.name == STRUCTURE_VOID {
+        return None;
+    }
+    let path = state.path();
+    if matches!(path, "barrier" | "light" | "moving_piston") {
+        return None;
+    }
+    Some(lookup_color(path).unwrap_or_else(|| fallback_color(&state.name)))
+}
+
+fn lookup_color(path: &str) -> Option<[u8; 4]> {
+    let table = color_table();
+    if let Some(color) = table.get(path) {
+        return Some(*color);
+    }
+
+    for prefix in ["waxed_", "infested_", "potted_"] {
+        if let Some(rest) = path.strip_prefix(prefix) {
+            return lookup_color(rest);
+        }
+    }
+
+    if let Some((dye, rest)) = DYE_COLORS
+        .iter()
+        .filter_map(|dye| path.strip_prefix(dye).and_then(|r| r.strip_prefix('_')).map(|r| (*dye, r)))
+        .max_by_key(|(dye, _)| dye.len())
+    {
+        let wool = table.get(format!("{}_wool", dye).as_str()).copied();
+        let concrete = table.get(format!("{}_concrete", dye).as_str()).copied();
+        match rest {
+            "carpet" | "bed" | "banner" | "wall_banner" | "candle" | "candle_cake" => return wool,
+            "stained_glass" | "stained_glass_pane" => return wool.map(|c| [c[0], c[1], c[2], 0x90]),
+            "concrete_powder" => return concrete.map(|c| scale_color(c, 1.15)),
+            "glazed_terracotta" => return concrete,
+            "shulker_box" => return wool.map(|c| scale_color(c, 0.9)),
+            _ => {}
+        }
+    }
+
+    let unstripped = path.strip_prefix("stripped_").unwrap_or(path);
+    for wood in WOOD_TYPES {
+        let Some(rest) = unstripped.strip_prefix(wood).and_then(|r| r.strip_prefix('_')) else { continue };
+        let planks = table.get(format!("{}_planks", wood).as_str()).copied();
+        return match rest {
+            "log" | "wood" | "stem" | "hyphae" if unstripped == path => table
+                .get(format!("{}_log", wood).as_str())
+                .or_else(|| table.get(format!("{}_stem", wood).as_str()))
+                .copied()
+                .or(planks),
+            "leaves" | "sapling" => table.get("oak_leaves").copied(),
+            _ => planks,
+        };
+    }
+
+    for suffix in SHAPE_SUFFIXES {
+        if let Some(base) = path.strip_suffix(suffix) {
+            for candidate in [base.to_string(), format!("{}s", base), format!("{}_block", base), format!("{}_planks", base)] {
+                if let Some(color) = table.get(candidate.as_str()) {
+                    return Some(*color);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// 未收录的方块按名称哈希生成一个稳定的灰调颜色
+fn fallback_color(name: &str) -> [u8; 4] {
+    let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| (h ^ u64::from(b)).wrapping_mul(0x100_0000_01b3));
+    let channel = |shift: u32| 90 + ((hash >> shift) & 0x5f) as u8;
+    [channel(0), channel(8), channel(16), 255]
+}
+
+struct PaletteColors {
+    colors: Vec<Option<[u8; 4]>>,
+}
+
+impl PaletteColors {
+    fn new(region: &Region) -> PaletteColors {
+        PaletteColors { colors: region.palette.iter().map(block_color).collect() }
+    }
+
+    fn at(&self, region: &Region, x: i32, y: i32, z: i32) -> Option<[u8; 4]> {
+        self.colors[region.blocks[region.index(x, y, z)] as usize]
+    }
+
+    /// 不透明方块会遮挡相邻方块的面
+    fn opaque(&self, region: &Region, x: i32, y: i32, z: i32) -> bool {
+        region.contains(x, y, z) && self.at(region, x, y, z).is_some_and(|c| c[3] == 255)
+    }
+}
+
+pub fn render_schematic(schematic: &Schematic, options: &RenderOptions) -> Result<RgbaImage, RenderError> {
+    if !(16..=MAX_PREVIEW_SIZE).contains(&options.size) {
+        return Err(RenderError::InvalidSize(options.size));
+    }
+    let region = match schematic.regions.len() {
+        0 => return Err(SchematicError::Invalid("Schematic has no regions".to_string()).into()),
+        1 => Cow::Borrowed(&schematic.regions[0]),
+        _ => Cow::Owned(schematic.flatten(&BlockState::air())?.0),
+    };
+    match options.view {
+        PreviewView::Isometric => render_isometric(&region, options),
+        PreviewView::TopDown => render_top_down(&region, options),
+    }
+}
+
+/// 等距视图：从 +X/+Z 方向俯视，每个方块绘制顶面与两个侧面
+fn render_isometric(region: &Region, options: &RenderOptions) -> Result<RgbaImage, RenderError> {
+    let (w, h, l) = (region.size.x, region.size.y, region.size.z);
+    let span = (w + l) as u32;
+    let natural = |u: u32| (span * 2 * u, span * u + 2 * h as u32 * u);
+
+    // 每格半宽 2u 像素；u 至少为 1，仍超出尺寸时渲染后缩小
+    let (nw, nh) = natural(1);
+    let u = (options.size / nw.max(nh)).max(1);
+    let (width, height) = natural(u);
+    if u64::from(width) * u64::from(height) > MAX_RASTER_PIXELS {
+        return Err(RenderError::TooLarge);
+    }
+
+    let mask = cube_mask(u);
+    let tile = (4 * u) as usize;
+    let colors = PaletteColors::new(region);
+    let mut image = RgbaImage::new(width, height, options.background);
+
+    // 按 x+y+z 递增绘制，后画的方块更靠近观察者
+    for sum in 0..=(w - 1) + (h - 1) + (l - 1) {
+        for y in 0..h.min(sum + 1) {
+            for x in (sum - y - (l - 1)).max(0)..w.min(sum - y + 1) {
+                let z = sum - x - y;
+                let Some(color) = colors.at(region, x, y, z) else { continue };
+                let visible = [
+                    !colors.opaque(region, x, y + 1, z),
+                    !colors.opaque(region, x, y, z + 1),
+                    !colors.opaque(region, x + 1, y, z),
+                ];
+                if !visible.iter().any(|v| *v) {
+                    continue;
+                }
+                let shaded = [color, scale_color(color, 0.8), scale_color(color, 0.64)];
+                let sx = (x - z + l - 1) as u32 * 2 * u;
+                let sy = (x + z) as u32 * u + (h - 1 - y) as u32 * 2 * u;
+                for (i, face) in mask.iter().enumerate() {
+                    let Some(face) = face else { continue };
+                    if visible[*face] {
+                        image.blend(sx + (i % tile) as u32, sy + (i / tile) as u32, shaded[*face]);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(fit(image, options.size))
+}
+
+/// 4u×4u 方块贴图中每个像素属于哪个面：0 顶面、1 左侧 (+Z)、2 右侧 (+X)
+fn cube_mask(u: u32) -> Vec<Option<usize>> {
+    let size = 4 * u;
+    let u = u as f32;
+    let mut mask = Vec::with_capacity((size * size) as usize);
+    for py in 0..size {
+        for px in 0..size {
+            let (x, y) = (px as f32 + 0.5, py as f32 + 0.5);
+            let face = if (x - 2.0 * u).abs() / (2.0 * u) + (y - u).abs() / u <= 1.0 {
+                Some(0)
+            } else if x <= 2.0 * u && y >= u + x / 2.0 && y <= 3.0 * u + x / 2.0 {
+                Some(1)
+            } else if x > 2.0 * u && y >= 2.0 * u - (x - 2.0 * u) / 2.0 && y <= 4.0 * u - (x - 2.0 * u) / 2.0 {
+                Some(2)
+            } else {
+                None
+            };
+            mask.push(face);
+        }
+    }
+    mask
+}
+
+/// 俯视图：每列取最高的可见方块，按与北侧相邻列的高度差明暗处理（类似地图）
+fn render_top_down(region: &Region, options: &RenderOptions) -> Result<RgbaImage, RenderError> {
+    let (w, h, l) = (region.size.x, region.size.y, region.size.z);
+    let scale = (options.size / w.max(l) as u32).max(1);
+    let (width, height) = (w as u32 * scale, l as u32 * scale);
+    if u64::from(width) * u64::from(height) > MAX_RASTER_PIXELS {
+        return Err(RenderError::TooLarge);
+    }
+
+    let colors = PaletteColors::new(region);
+    let mut heights = vec![-1i32; (w * l) as usize];
+    let mut columns = vec![[0u8; 4]; (w * l) as usize];
+    for z in 0..l {
+        for x in 0..w {
+            // 从上往下叠加半透明方块，遇到不透明方块为止
+            let mut column = [0u8; 4];
+            let mut top = -1;
+            for y in (0..h).rev() {
+                let Some(color) = colors.at(region, x, y, z) else { continue };
+                if top < 0 {
+                    top = y;
+                    column = color;
+                } else {
+                    column = over(column, color);
+                }
+                if column[3] == 255 {
+                    break;
+                }
+            }
+            let i = (z * w + x) as usize;
+            heights[i] = top;
+            columns[i] = column;
+        }
+    }
+
+    let mut image = RgbaImage::new(width, height, options.background);
+    for z in 0..l {
+        for x in 0..w {
+            let i = (z * w + x) as usize;
+            if heights[i] < 0 {
+                continue;
+            }
+            let north = if z > 0 { heights[i - w as usize] } else { heights[i] };
+            let factor = match heights[i].cmp(&north) {
+                std::cmp::Ordering::Greater => 1.1,
+                std::cmp::Ordering::Less => 0.82,
+                std::cmp::Ordering::Equal => 1.0,
+            };
+            let color = scale_color(columns[i], factor);
+            for py in 0..scale {
+                for px in 0..scale {
+                    image.blend(x as u32 * scale + px, z as u32 * scale + py, color);
+                }
+            }
+        }
+    }
+
+    Ok(fit(image, options.size))
+}
+
+/// front 叠加在 back 之上
+fn over(front: [u8; 4], back: [u8; 4]) -> [u8; 4] {
+    let mut image = RgbaImage { width: 1, height: 1, pixels: back.to_vec() };
+    image.blend(0, 0, front);
+    image.pixel(0, 0)
+}
+
+fn fit(image: RgbaImage, size: u32) -> RgbaImage {
+    let largest = image.width.max(image.height);
+    if largest <= size {
+        image
+    } else {
+        image.downscale(largest.div_ceil(size))
+    }
+}
+
+/// 渲染预览并编码为 PNG
+pub fn render_preview_png(schematic: &Schematic, options: &RenderOptions) -> Result<Vec<u8>, RenderError> {
+    render_schematic(schematic, options)?.to_png()
+}
+
+/// 渲染投影预览 PNG；view 为 isometric 或 top_down，size 为最大边长（像素）
+#[wasm_bindgen]
+pub fn render_schematic_preview(data: &[u8], view: &str, size: u32) -> Result<Vec<u8>, NeptuniumError> {
+    let view = PreviewView::from_name(view).ok_or_else(|| {
+        NeptuniumError::new(format!("Unsupported preview view: {}", view), "RENDER_INVALID_VIEW".to_string())
+    })?;
+    let schematic = Schematic::from_bytes(data)?;
+    let options = RenderOptions { view, size, ..RenderOptions::default() };
+    Ok(render_preview_png(&schematic, &options)?)
+}
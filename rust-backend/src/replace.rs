@@ -0,0 +1,285 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::{
+    BlockState, ConversionOutput, ConversionWarning, MaterialList, MaterialMode, NeptuniumError, Schematic,
+    Substitution,
+};
+
+// 调色板查找替换：按规则批量替换方块，例如 `minecraft:oak_planks -> minecraft:spruce_planks` 或 `oak_* -> spruce_*`
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplaceRule {
+    /// 规则原文，用于报告
+    pub source: String,
+    /// 匹配模式：方块 id 中可用 `*` 通配，给出的属性须全部相同
+    pub from: BlockState,
+    /// 替换目标：`*` 依次填入匹配到的内容，给出的属性覆盖原属性
+    pub to: BlockState,
+    /// 保留原方块的属性（朝向、半砖位置等）
+    pub preserve_properties: bool,
+}
+
+/// 规则的 JSON 写法：`"from -> to"` 或 `{"from": ..., "to": ..., "preserve_properties": false}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum RuleInput {
+    Text(String),
+    Object {
+        from: String,
+        to: String,
+        #[serde(default = "default_preserve")]
+        preserve_properties: bool,
+    },
+}
+
+fn default_preserve() -> bool {
+    true
+}
+
+fn invalid_rule(message: String) -> NeptuniumError {
+    NeptuniumError::new(message, "INVALID_REPLACE_RULE".to_string())
+}
+
+impl ReplaceRule {
+    pub fn new(from: &str, to: &str, preserve_properties: bool) -> Result<ReplaceRule, NeptuniumError> {
+        let source = format!("{} -> {}", from.trim(), to.trim());
+        let parse = |input: &str| {
+            BlockState::parse(input).map_err(|e| invalid_rule(format!("Invalid rule `{}`: {}", source, e)))
+        };
+        let (from, to) = (parse(from)?, parse(to)?);
+        if to.name.matches('*').count() > from.name.matches('*').count() {
+            return Err(invalid_rule(format!("Rule `{}` uses more wildcards in the target than in the pattern", source)));
+        }
+        Ok(ReplaceRule { source, from, to, preserve_properties })
+    }
+
+    /// 解析 `from -> to` 形式的规则
+    pub fn parse(rule: &str) -> Result<ReplaceRule, NeptuniumError> {
+        let (from, to) = rule
+            .split_once("->")
+            .ok_or_else(|| invalid_rule(format!("Rule `{}` must have the form `from -> to`", rule.trim())))?;
+        ReplaceRule::new(from, to, true)
+    }
+
+    /// 解析规则列表：JSON 数组，或每行一条的纯文本（`#` 开头为注释）
+    pub fn parse_list(input: &str) -> Result<Vec<ReplaceRule>, NeptuniumError> {
+        if input.trim_start().starts_with('[') {
+            let inputs: Vec<RuleInput> = serde_json::from_str(input)
+                .map_err(|e| invalid_rule(format!("Invalid replacement rules: {}", e)))?;
+            return inputs
+                .into_iter()
+                .map(|rule| match rule {
+                    RuleInput::Text(text) => ReplaceRule::parse(&text),
+                    RuleInput::Object { from, to, preserve_properties } => ReplaceRule::new(&from, &to, preserve_properties),
+                })
+                .collect();
+        }
+        input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(ReplaceRule::parse)
+            .collect()
+    }
+
+    /// 规则匹配时返回替换后的方块状态
+    pub fn apply(&self, state: &BlockState) -> Option<BlockState> {
+        let captures = glob_captures(&self.from.name, &state.name)?;
+        if self.from.properties.iter().any(|(key, value)| state.property(key) != Some(value.as_str())) {
+            return None;
+        }
+
+        let mut name = String::new();
+        let mut captures = captures.into_iter();
+        for (i, part) in self.to.name.split('*').enumerate() {
+            if i > 0 {
+                name.push_str(captures.next().unwrap_or_default());
+            }
+            name.push_str(part);
+        }
+        let mut properties = if self.preserve_properties { state.properties.clone() } else { BTreeMap::new() };
+        properties.extend(self.to.properties.iter().map(|(k, v)| (k.clone(), v.clone())));
+        Some(BlockState { name, properties })
+    }
+}
+
+/// `*` 通配匹配，返回各个 `*` 匹配到的内容
+pub(crate) fn glob_captures<'a>(pattern: &str, text: &'a str) -> Option<Vec<&'a str>> {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return (pattern == text).then(Vec::new);
+    }
+    let first = parts[0];
+    let last = parts[parts.len() - 1];
+    if text.len() < first.len() + last.len() || !text.starts_with(first) || !text.ends_with(last) {
+        return None;
+    }
+    let middle = &text[first.len()..text.len() - last.len()];
+    let mut captures = Vec::new();
+    let mut rest = middle;
+    for part in &parts[1..parts.len() - 1] {
+        let found = rest.find(part)?;
+        captures.push(&rest[..found]);
+        rest = &rest[found + part.len()..];
+    }
+    captures.push(rest);
+    Some(captures)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplaceReport {
+    /// 按数量降序
+    pub substitutions: Vec<Substitution>,
+    pub total_replaced: u64,
+    /// 没有匹配到任何方块的规则
+    pub unused_rules: Vec<String>,
+    /// 所在方块的 id 被替换（包括替换为空气）后移除的方块实体数量
+    pub removed_block_entities: usize,
+}
+
+/// 对所有区域的调色板依次尝试规则，每个方块状态只应用第一条匹配的规则
+pub fn replace_blocks(schematic: &mut Schematic, rules: &[ReplaceRule]) -> ReplaceReport {
+    let mut substitutions: BTreeMap<(String, String), u64> = BTreeMap::new();
+    let mut used = vec![false; rules.len()];
+    let mut removed_block_entities = 0;
+
+    for region in schematic.regions.iter_mut() {
+        // 方块实体只对原方块有效：方块 id 改变（包括替换为空气）后移除
+        let original_ids: Vec<Option<String>> = region
+            .block_entities
+            .iter()
+            .map(|be| {
+                let pos = be.pos;
+                region.contains(pos.x, pos.y, pos.z).then(|| region.block(pos.x, pos.y, pos.z).name.clone())
+            })
+            .collect();
+        let counts = region.palette_counts();
+        for (state, count) in region.palette.iter_mut().zip(counts) {
+            let Some((index, replaced)) = rules.iter().enumerate().find_map(|(i, rule)| rule.apply(state).map(|s| (i, s))) else {
+                continue;
+            };
+            if count > 0 {
+                used[index] = true;
+                if replaced != *state {
+                    *substitutions.entry((state.to_string(), replaced.to_string())).or_default() += count;
+                }
+            }
+            *state = replaced;
+        }
+        region.compact_palette();
+
+        let before = region.block_entities.len();
+        let mut keep = region
+            .block_entities
+            .iter()
+            .zip(original_ids)
+            .map(|(be, id)| id.is_none_or(|id| region.block(be.pos.x, be.pos.y, be.pos.z).name == id))
+            .collect::<Vec<bool>>()
+            .into_iter();
+        region.block_entities.retain(|_| keep.next().unwrap_or(true));
+        removed_block_entities += before - region.block_entities.len();
+    }
+
+    let mut substitutions: Vec<Substitution> = substitutions
+        .into_iter()
+        .map(|((from, to), count)| Substitution { from, to, count })
+        .collect();
+    substitutions.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.from.cmp(&b.from)));
+    ReplaceReport {
+        total_replaced: substitutions.iter().map(|s| s.count).sum(),
+        substitutions,
+        unused_rules: rules.iter().zip(used).filter(|(_, used)| !used).map(|(rule, _)| rule.source.clone()).collect(),
+        removed_block_entities,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[wasm_bindgen]
+pub struct ReplaceOutput {
+    output: ConversionOutput,
+    report: ReplaceReport,
+    materials: MaterialList,
+}
+
+#[wasm_bindgen]
+impl ReplaceOutput {
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.output.data()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn format(&self) -> String {
+        self.output.format()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn extension(&self) -> String {
+        self.output.extension()
+    }
+
+    /// 写出的文件与转换警告
+    #[wasm_bindgen(getter)]
+    pub fn output(&self) -> ConversionOutput {
+        self.output.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn report_json(&self) -> String {
+        serde_json::to_string(&self.report).unwrap_or_default()
+    }
+
+    /// 替换后的材料清单
+    #[wasm_bindgen(getter)]
+    pub fn materials_json(&self) -> String {
+        serde_json::to_string(&self.materials).unwrap_or_default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn warnings_json(&self) -> String {
+        self.output.warnings_json()
+    }
+}
+
+impl ReplaceOutput {
+    pub fn report(&self) -> &ReplaceReport {
+        &self.report
+    }
+
+    pub fn materials(&self) -> &MaterialList {
+        &self.materials
+    }
+
+    pub fn warnings(&self) -> &[ConversionWarning] {
+        self.output.warnings()
+    }
+}
+
+// 导出的函数
+/// 按规则替换方块并写出；rules 为 JSON 数组或每行一条的文本，target_format 留空时写回源格式
+#[wasm_bindgen]
+pub fn replace_schematic_blocks(data: &[u8], rules: &str, target_format: &str, as_items: bool) -> Result<ReplaceOutput, NeptuniumError> {
+    let rules = ReplaceRule::parse_list(rules)?;
+    let target = crate::resolve_export_format(data, target_format)?;
+    let mut schematic = Schematic::from_bytes(data)?;
+    let report = replace_blocks(&mut schematic, &rules);
+    let mode = if as_items { MaterialMode::Items } else { MaterialMode::BlockStates };
+    let materials = MaterialList::from_schematic(&schematic, mode);
+    let (bytes, mut warnings) = crate::write_schematic(&schematic, target)?;
+    if report.removed_block_entities > 0 {
+        warnings.push(ConversionWarning::new(
+            "block_entities_removed",
+            format!("{} block entities were removed because their block was replaced", report.removed_block_entities),
+        ));
+    }
+    Ok(ReplaceOutput { output: ConversionOutput::new(bytes, target, warnings), report, materials })
+}
+
+/// 只生成替换报告（JSON），不写出文件
+#[wasm_bindgen]
+pub fn preview_block_replacement(data: &[u8], rules: &str) -> Result<String, NeptuniumError> {
+    let rules = ReplaceRule::parse_list(rules)?;
+    let mut schematic = Schematic::from_bytes(data)?;
+    Ok(serde_json::to_string(&replace_blocks(&mut schematic, &rules)).unwrap_or_default())
+}
@@ -0,0 +1,587 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use crate::{
+    normalize_id, normalize_legacy_block_entity_id, IngestLimits, NbtCompound, NbtTag, NeptuniumError, Region,
+    Schematic, Vec3i,
+};
+
+// 上传内容安全扫描：匿名用户上传的投影会直接分发给其他玩家，
+// 在共享之前标记命令方块、书签类超大文本（禁书攻击）、自定义刷怪笼以及过量实体等危险或卡顿内容
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    #[default]
+    None,
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::None => "none",
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+
+    /// 计入风险分的权重
+    fn weight(&self) -> u32 {
+        match self {
+            Severity::None | Severity::Info => 0,
+            Severity::Low => 5,
+            Severity::Medium => 15,
+            Severity::High => 40,
+            Severity::Critical => 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafetyAction {
+    Allow,
+    Warn,
+    Quarantine,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafetyOptions {
+    /// 实体总数
+    pub max_entities: usize,
+    /// 方块实体总数
+    pub max_block_entities: usize,
+    /// 单个字符串的字节数
+    pub max_string_bytes: usize,
+    /// 单本书（成书 / 书与笔）全部文本的字节数
+    pub max_book_bytes: usize,
+    /// 单个告示牌全部文本的字节数
+    pub max_sign_bytes: usize,
+    /// 单个方块实体或实体中字符串与数组的总字节数
+    pub max_payload_bytes: usize,
+    /// 报告中保留的发现条数，超出时保留最严重的；被丢弃的发现仍计入严重程度与风险分
+    pub max_findings: usize,
+    /// 达到该严重程度即隔离
+    pub quarantine_at: Severity,
+    /// 匿名上传使用的隔离阈值
+    pub anonymous_quarantine_at: Severity,
+}
+
+impl SafetyOptions {
+    pub const DEFAULT: SafetyOptions = SafetyOptions {
+        max_entities: 1024,
+        max_block_entities: 8192,
+        max_string_bytes: 32 * 1024,
+        max_book_bytes: 64 * 1024,
+        max_sign_bytes: 4 * 1024,
+        max_payload_bytes: 256 * 1024,
+        max_findings: 256,
+        quarantine_at: Severity::High,
+        anonymous_quarantine_at: Severity::Medium,
+    };
+
+    /// 解析 JSON 配置，未给出的字段使用默认值；空字符串表示全部默认
+    pub fn from_json(json: &str) -> Result<SafetyOptions, NeptuniumError> {
+        if json.trim().is_empty() {
+            return Ok(SafetyOptions::default());
+        }
+        serde_json::from_str(json)
+            .map_err(|e| NeptuniumError::new(format!("Invalid safety options: {}", e), "INVALID_SAFETY_OPTIONS".to_string()))
+    }
+}
+
+impl Default for SafetyOptions {
+    fn default() -> Self {
+        SafetyOptions::DEFAULT
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SafetyFinding {
+    /// 发现类型，如 `command_block`、`oversized_book`
+    pub kind: String,
+    pub severity: Severity,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub region: String,
+    /// 投影坐标系中的位置；针对整个投影的发现没有位置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pos: Option<Vec3i>,
+    pub message: String,
+    /// 命令内容、实体 id 等附加信息，过长时截断
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SafetyReport {
+    /// 所有发现中最高的严重程度
+    pub severity: Severity,
+    /// 0–100 的风险分
+    pub risk_score: u32,
+    pub action: SafetyAction,
+    /// 按严重程度降序
+    pub findings: Vec<SafetyFinding>,
+    /// 发现条数超过 `max_findings` 时为 true
+    pub truncated: bool,
+    pub block_entity_count: usize,
+    pub entity_count: usize,
+}
+
+impl SafetyReport {
+    pub fn is_quarantined(&self) -> bool {
+        self.action == SafetyAction::Quarantine
+    }
+}
+
+// 附加信息的最大字符数
+const MAX_DETAIL_CHARS: usize = 256;
+
+const COMMAND_BLOCKS: &[&str] = &[
+    "minecraft:command_block",
+    "minecraft:chain_command_block",
+    "minecraft:repeating_command_block",
+];
+
+const SPAWNERS: &[&str] = &["minecraft:mob_spawner", "minecraft:spawner", "minecraft:trial_spawner"];
+
+const SIGNS: &[&str] = &["minecraft:sign", "minecraft:hanging_sign"];
+
+const BOOKS: &[&str] = &["minecraft:written_book", "minecraft:writable_book"];
+
+/// 刷怪笼中可被用于破坏或卡服的实体
+const DANGEROUS_SPAWNS: &[&str] = &[
+    "minecraft:command_block_minecart",
+    "minecraft:tnt",
+    "minecraft:tnt_minecart",
+    "minecraft:end_crystal",
+    "minecraft:wither",
+    "minecraft:ender_dragon",
+    "minecraft:lightning_bolt",
+    "minecraft:falling_block",
+    "minecraft:fireball",
+    "minecraft:wither_skull",
+];
+
+/// 堆中的发现按"最先被挤出"排序：严重程度最低、同级中最晚发现的位于堆顶
+struct RankedFinding {
+    sequence: usize,
+    finding: SafetyFinding,
+}
+
+impl RankedFinding {
+    fn key(&self) -> (Reverse<Severity>, usize) {
+        (Reverse(self.finding.severity), self.sequence)
+    }
+}
+
+impl PartialEq for RankedFinding {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for RankedFinding {}
+
+impl PartialOrd for RankedFinding {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RankedFinding {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+struct Scan<'a> {
+    options: &'a SafetyOptions,
+    /// 只保留最严重的 `max_findings` 条
+    findings: BinaryHeap<RankedFinding>,
+    reported: usize,
+    severity: Severity,
+    score: u32,
+    truncated: bool,
+}
+
+impl Scan<'_> {
+    fn report(&mut self, finding: SafetyFinding) {
+        self.severity = self.severity.max(finding.severity);
+        self.score = self.score.saturating_add(finding.severity.weight());
+        let ranked = RankedFinding { sequence: self.reported, finding };
+        self.reported += 1;
+        if self.findings.len() < self.options.max_findings {
+            self.findings.push(ranked);
+            return;
+        }
+        self.truncated = true;
+        if self.findings.peek().is_some_and(|weakest| ranked < *weakest) {
+            self.findings.pop();
+            self.findings.push(ranked);
+        }
+    }
+
+    /// 按严重程度降序，同级保持发现顺序
+    fn into_findings(self) -> Vec<SafetyFinding> {
+        self.findings.into_sorted_vec().into_iter().map(|ranked| ranked.finding).collect()
+    }
+
+    fn add(&mut self, kind: &str, severity: Severity, region: &str, pos: Option<Vec3i>, message: String, detail: Option<String>) {
+        self.report(SafetyFinding {
+            kind: kind.to_string(),
+            severity,
+            region: region.to_string(),
+            pos,
+            message,
+            detail: detail.map(|d| truncate_detail(&d)),
+        });
+    }
+}
+
+fn truncate_detail(text: &str) -> String {
+    match text.char_indices().nth(MAX_DETAIL_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// 超过上限四倍以上视为刻意构造的攻击载荷
+fn oversize_severity(bytes: usize, limit: usize) -> Severity {
+    if bytes > limit.saturating_mul(4) {
+        Severity::Critical
+    } else {
+        Severity::High
+    }
+}
+
+/// 一个方块实体或实体内部的文本与数据量统计
+#[derive(Default)]
+struct Payload {
+    bytes: usize,
+    largest_string: usize,
+    /// 每本书全部文本的字节数
+    books: Vec<(String, usize)>,
+}
+
+fn measure(tag: &NbtTag, payload: &mut Payload) {
+    match tag {
+        NbtTag::String(value) => {
+            payload.bytes += value.len();
+            payload.largest_string = payload.largest_string.max(value.len());
+        }
+        NbtTag::ByteArray(values) => payload.bytes += values.len(),
+        NbtTag::IntArray(values) => payload.bytes += values.len() * 4,
+        NbtTag::LongArray(values) => payload.bytes += values.len() * 8,
+        NbtTag::List(list) => list.iter().for_each(|value| measure(value, payload)),
+        NbtTag::Compound(compound) => measure_compound(compound, payload),
+        _ => {}
+    }
+}
+
+fn measure_compound(compound: &NbtCompound, payload: &mut Payload) {
+    if let Some(id) = compound.get_str("id").map(normalize_id) {
+        if BOOKS.contains(&id.as_str()) {
+            payload.books.push((id, text_bytes(compound)));
+        }
+    }
+    for (_, value) in compound.iter() {
+        measure(value, payload);
+    }
+}
+
+/// 标签内全部字符串的字节数
+fn text_bytes(compound: &NbtCompound) -> usize {
+    fn walk(tag: &NbtTag) -> usize {
+        match tag {
+            NbtTag::String(value) => value.len(),
+            NbtTag::List(list) => list.iter().map(walk).sum(),
+            NbtTag::Compound(compound) => compound.iter().map(|(_, value)| walk(value)).sum(),
+            _ => 0,
+        }
+    }
+    compound.iter().map(|(_, value)| walk(value)).sum()
+}
+
+/// 检查方块实体或实体中的超大书本、字符串与总数据量
+fn scan_payload(scan: &mut Scan, nbt: &NbtCompound, region: &str, pos: Vec3i, owner: &str) {
+    let mut payload = Payload::default();
+    measure_compound(nbt, &mut payload);
+    let options = scan.options;
+
+    for (book, bytes) in &payload.books {
+        if *bytes > options.max_book_bytes {
+            scan.add(
+                "oversized_book",
+                oversize_severity(*bytes, options.max_book_bytes),
+                region,
+                Some(pos),
+                format!("{} in {} contains {} bytes of text (limit {})", book, owner, bytes, options.max_book_bytes),
+                None,
+            );
+        }
+    }
+    if payload.largest_string > options.max_string_bytes {
+        scan.add(
+            "oversized_string",
+            oversize_severity(payload.largest_string, options.max_string_bytes),
+            region,
+            Some(pos),
+            format!("{} contains a {} byte string (limit {})", owner, payload.largest_string, options.max_string_bytes),
+            None,
+        );
+    }
+    if payload.bytes > options.max_payload_bytes {
+        scan.add(
+            "oversized_payload",
+            if payload.bytes > options.max_payload_bytes.saturating_mul(4) { Severity::High } else { Severity::Medium },
+            region,
+            Some(pos),
+            format!("{} carries {} bytes of data (limit {})", owner, payload.bytes, options.max_payload_bytes),
+            None,
+        );
+    }
+}
+
+fn scan_command(scan: &mut Scan, nbt: &NbtCompound, region: &str, pos: Vec3i, kind: &str, block: &str) {
+    let command = nbt.get_str("Command").unwrap_or_default().trim();
+    let auto = nbt.get_i64("auto") == Some(1);
+    let (severity, message) = if command.is_empty() {
+        (Severity::Low, format!("{} without a command", block))
+    } else if auto {
+        (Severity::Critical, format!("always-active {} with a command", block))
+    } else {
+        (Severity::High, format!("{} with a command", block))
+    };
+    let detail = (!command.is_empty()).then(|| command.to_string());
+    scan.add(kind, severity, region, Some(pos), message, detail);
+}
+
+fn scan_sign(scan: &mut Scan, nbt: &NbtCompound, region: &str, pos: Vec3i, block: &str) {
+    let bytes = text_bytes(nbt);
+    let limit = scan.options.max_sign_bytes;
+    if bytes > limit {
+        scan.add(
+            "oversized_sign",
+            oversize_severity(bytes, limit),
+            region,
+            Some(pos),
+            format!("{} contains {} bytes of text (limit {})", block, bytes, limit),
+            None,
+        );
+    }
+}
+
+/// 刷怪笼会生成的实体：旧版 `SpawnData` 即实体本身，1.18 起包在 `entity` 中；试炼刷怪笼的配置可内联在方块实体里
+fn spawner_entities(nbt: &NbtCompound) -> Vec<&NbtCompound> {
+    fn unwrap_data(data: &NbtCompound) -> &NbtCompound {
+        data.get_compound("entity").unwrap_or(data)
+    }
+
+    let mut entities = Vec::new();
+    for key in ["SpawnData", "spawn_data"] {
+        if let Some(data) = nbt.get_compound(key) {
+            entities.push(unwrap_data(data));
+        }
+    }
+    let mut potentials: Vec<&NbtCompound> = nbt.get_list("SpawnPotentials").map(|l| l.compounds().collect()).unwrap_or_default();
+    for config in ["normal_config", "ominous_config"] {
+        if let Some(list) = nbt.get_compound(config).and_then(|c| c.get_list("spawn_potentials")) {
+            potentials.extend(list.compounds());
+        }
+    }
+    for potential in potentials {
+        let entity = potential
+            .get_compound("data")
+            .map(unwrap_data)
+            .or_else(|| potential.get_compound("Entity"))
+            .or_else(|| potential.get_compound("Properties"));
+        entities.extend(entity);
+    }
+    entities.retain(|entity| !entity.is_empty());
+    entities
+}
+
+/// 实体及其乘客中出现的危险实体 id
+fn dangerous_spawns(entity: &NbtCompound, found: &mut Vec<String>, depth: usize) {
+    if let Some(id) = entity.get_str("id").map(normalize_id) {
+        if DANGEROUS_SPAWNS.contains(&id.as_str()) && !found.contains(&id) {
+            found.push(id);
+        }
+    }
+    if depth < IngestLimits::DEFAULT.max_depth {
+        for passenger in entity.get_list("Passengers").into_iter().flat_map(|l| l.compounds()) {
+            dangerous_spawns(passenger, found, depth + 1);
+        }
+    }
+}
+
+fn scan_spawner(scan: &mut Scan, nbt: &NbtCompound, region: &str, pos: Vec3i, block: &str) {
+    let mut dangerous = Vec::new();
+    let mut custom = Vec::new();
+    for entity in spawner_entities(nbt) {
+        dangerous_spawns(entity, &mut dangerous, 0);
+        let id = entity.get_str("id").map(normalize_id).unwrap_or_default();
+        if entity.keys().any(|key| key != "id") && !custom.contains(&id) {
+            custom.push(id);
+        }
+    }
+    if !dangerous.is_empty() {
+        scan.add(
+            "spawner_dangerous_entity",
+            Severity::High,
+            region,
+            Some(pos),
+            format!("{} spawns dangerous entities", block),
+            Some(dangerous.join(", ")),
+        );
+    }
+    if !custom.is_empty() {
+        scan.add(
+            "spawner_custom_nbt",
+            Severity::Medium,
+            region,
+            Some(pos),
+            format!("{} spawns entities with custom NBT", block),
+            Some(custom.join(", ")),
+        );
+    }
+
+    // 原版默认：每次 4 个、附近上限 6 个、间隔 200–800 刻
+    let spawn_count = nbt.get_i64("SpawnCount").unwrap_or(0);
+    let max_nearby = nbt.get_i64("MaxNearbyEntities").unwrap_or(0);
+    let max_delay = nbt.get_i64("MaxSpawnDelay");
+    if spawn_count > 16 || max_nearby > 64 || max_delay.is_some_and(|d| d < 10) {
+        scan.add(
+            "spawner_extreme_settings",
+            Severity::Medium,
+            region,
+            Some(pos),
+            format!("{} has extreme spawn settings", block),
+            Some(format!(
+                "SpawnCount={} MaxNearbyEntities={} MaxSpawnDelay={}",
+                spawn_count,
+                max_nearby,
+                max_delay.unwrap_or_default()
+            )),
+        );
+    }
+}
+
+fn scan_region(scan: &mut Scan, region: &Region) {
+    let name = region.name.as_str();
+    let origin = region.origin;
+    for block_entity in &region.block_entities {
+        let rel = block_entity.pos;
+        let pos = Vec3i::new(origin.x + rel.x, origin.y + rel.y, origin.z + rel.z);
+        let block = if region.contains(rel.x, rel.y, rel.z) {
+            region.block(rel.x, rel.y, rel.z).name.clone()
+        } else {
+            String::new()
+        };
+        let id = if block_entity.id.is_empty() {
+            block.clone()
+        } else {
+            normalize_legacy_block_entity_id(&block_entity.id)
+        };
+        let label = if block.is_empty() || block == "minecraft:air" { id.clone() } else { block };
+        let nbt = &block_entity.nbt;
+
+        if COMMAND_BLOCKS.contains(&id.as_str()) {
+            scan_command(scan, nbt, name, pos, "command_block", &label);
+        } else if SIGNS.contains(&id.as_str()) || label.ends_with("_sign") {
+            scan_sign(scan, nbt, name, pos, &label);
+        } else if SPAWNERS.contains(&id.as_str()) {
+            scan_spawner(scan, nbt, name, pos, &label);
+        }
+        scan_payload(scan, nbt, name, pos, &label);
+    }
+
+    for entity in &region.entities {
+        let pos = Vec3i::new(
+            origin.x + entity.pos[0].floor() as i32,
+            origin.y + entity.pos[1].floor() as i32,
+            origin.z + entity.pos[2].floor() as i32,
+        );
+        let id = normalize_id(&entity.id);
+        if id == "minecraft:command_block_minecart" {
+            scan_command(scan, &entity.nbt, name, pos, "command_block_minecart", &id);
+        }
+        scan_payload(scan, &entity.nbt, name, pos, &id);
+    }
+}
+
+/// 扫描投影中的危险或卡顿内容；`anonymous` 为 true 时使用更严格的隔离阈值
+pub fn scan_schematic(schematic: &Schematic, options: &SafetyOptions, anonymous: bool) -> SafetyReport {
+    let mut scan = Scan {
+        options,
+        findings: BinaryHeap::new(),
+        reported: 0,
+        severity: Severity::None,
+        score: 0,
+        truncated: false,
+    };
+    for region in &schematic.regions {
+        scan_region(&mut scan, region);
+    }
+
+    let block_entity_count: usize = schematic.regions.iter().map(|r| r.block_entities.len()).sum();
+    let entity_count: usize = schematic.regions.iter().map(|r| r.entities.len()).sum();
+    if entity_count > options.max_entities {
+        scan.add(
+            "too_many_entities",
+            Severity::High,
+            "",
+            None,
+            format!("{} entities (limit {})", entity_count, options.max_entities),
+            None,
+        );
+    }
+    if block_entity_count > options.max_block_entities {
+        scan.add(
+            "too_many_block_entities",
+            Severity::Medium,
+            "",
+            None,
+            format!("{} block entities (limit {})", block_entity_count, options.max_block_entities),
+            None,
+        );
+    }
+
+    let quarantine_at = if anonymous { options.anonymous_quarantine_at } else { options.quarantine_at };
+    let action = if scan.severity >= quarantine_at {
+        SafetyAction::Quarantine
+    } else if scan.severity >= Severity::Low {
+        SafetyAction::Warn
+    } else {
+        SafetyAction::Allow
+    };
+    let (severity, risk_score, truncated) = (scan.severity, scan.score.min(100), scan.truncated);
+
+    SafetyReport {
+        severity,
+        risk_score,
+        action,
+        findings: scan.into_findings(),
+        truncated,
+        block_entity_count,
+        entity_count,
+    }
+}
+
+// 导出的函数
+#[wasm_bindgen]
+pub fn scan_schematic_safety(data: &[u8], options_json: &str, anonymous: bool) -> Result<String, NeptuniumError> {
+    let options = SafetyOptions::from_json(options_json)?;
+    let schematic = Schematic::from_bytes(data)?;
+    Ok(serde_json::to_string(&scan_schematic(&schematic, &options, anonymous)).unwrap_or_default())
+}
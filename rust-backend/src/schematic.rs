@@ -0,0 +1,457 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use crate::{
+    detect_from_root, IngestLimits, NbtCompound, NbtTag, SchematicError, SchematicFormat, Vec3i,
+};
+
+// 各投影格式共用的内存模型：多区域、调色板 + 方块索引、方块实体与实体
+
+/// 单个区域允许的最大体积，防止按声明尺寸分配过大内存
+pub const MAX_REGION_VOLUME: i64 = 1 << 27;
+
+pub const AIR: &str = "minecraft:air";
+pub const STRUCTURE_VOID: &str = "minecraft:structure_void";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BlockState {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, String>,
+}
+
+impl BlockState {
+    pub fn new(name: impl Into<String>) -> BlockState {
+        BlockState { name: normalize_id(&name.into()), properties: BTreeMap::new() }
+    }
+
+    pub fn air() -> BlockState {
+        BlockState::new(AIR)
+    }
+
+    pub fn with_property(mut self, key: impl Into<String>, value: impl Into<String>) -> BlockState {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(String::as_str)
+    }
+
+    /// 不含命名空间的方块名，如 `oak_stairs`
+    pub fn path(&self) -> &str {
+        self.name.split_once(':').map(|(_, path)| path).unwrap_or(&self.name)
+    }
+
+    pub fn is_air(&self) -> bool {
+        matches!(self.name.as_str(), "minecraft:air" | "minecraft:cave_air" | "minecraft:void_air")
+    }
+
+    /// 解析 `minecraft:oak_stairs[facing=east,half=bottom]` 形式的字符串
+    pub fn parse(input: &str) -> Result<BlockState, SchematicError> {
+        let input = input.trim();
+        let (name, props) = match input.find('[') {
+            Some(start) => {
+                let end = input
+                    .strip_suffix(']')
+                    .ok_or_else(|| SchematicError::Invalid(format!("Malformed block state `{}`", input)))?;
+                (&input[..start], Some(&end[start + 1..]))
+            }
+            None => (input, None),
+        };
+        if name.is_empty() {
+            return Err(SchematicError::Invalid(format!("Malformed block state `{}`", input)));
+        }
+
+        let mut state = BlockState::new(name);
+        if let Some(props) = props {
+            for pair in props.split(',').filter(|p| !p.trim().is_empty()) {
+                let (key, value) = pair
+                    .split_once('=')
+                    .ok_or_else(|| SchematicError::Invalid(format!("Malformed block state `{}`", input)))?;
+                state.properties.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+        Ok(state)
+    }
+
+    /// 读取 `{Name, Properties}` 形式的调色板条目
+    pub fn from_nbt(compound: &NbtCompound) -> Option<BlockState> {
+        let mut state = BlockState::new(compound.get_str("Name")?);
+        if let Some(props) = compound.get_compound("Properties") {
+            for (key, value) in props.iter() {
+                state.properties.insert(key.to_string(), value.as_str()?.to_string());
+            }
+        }
+        Some(state)
+    }
+
+    pub fn to_nbt(&self) -> NbtCompound {
+        let mut compound = NbtCompound::new();
+        compound.insert("Name", NbtTag::String(self.name.clone()));
+        if !self.properties.is_empty() {
+            let props = self
+                .properties
+                .iter()
+                .map(|(k, v)| (k.clone(), NbtTag::String(v.clone())))
+                .collect();
+            compound.insert("Properties", NbtTag::Compound(props));
+        }
+        compound
+    }
+}
+
+impl fmt::Display for BlockState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if !self.properties.is_empty() {
+            let props: Vec<String> = self.properties.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+            write!(f, "[{}]", props.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// 补全缺省的 `minecraft:` 命名空间
+pub fn normalize_id(id: &str) -> String {
+    if id.contains(':') {
+        id.to_string()
+    } else {
+        format!("minecraft:{}", id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockEntity {
+    /// 相对区域最小角的位置
+    pub pos: Vec3i,
+    pub id: String,
+    /// 除位置与 id 以外的全部数据
+    pub nbt: NbtCompound,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    /// 相对区域最小角的位置
+    pub pos: [f64; 3],
+    pub id: String,
+    /// 除 Pos 与 id 以外的全部数据
+    pub nbt: NbtCompound,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Region {
+    pub name: String,
+    /// 区域最小角相对于投影原点的位置
+    pub origin: Vec3i,
+    /// 各分量均为正
+    pub size: Vec3i,
+    pub palette: Vec<BlockState>,
+    /// 调色板索引，按 y → z → x 顺序排列
+    pub blocks: Vec<u32>,
+    pub block_entities: Vec<BlockEntity>,
+    pub entities: Vec<Entity>,
+}
+
+impl Region {
+    /// 创建一个填满空气的区域
+    pub fn new(name: impl Into<String>, origin: Vec3i, size: Vec3i) -> Result<Region, SchematicError> {
+        let volume = checked_volume(size)?;
+        Ok(Region {
+            name: name.into(),
+            origin,
+            size,
+            palette: vec![BlockState::air()],
+            blocks: vec![0; volume],
+            block_entities: Vec::new(),
+            entities: Vec::new(),
+        })
+    }
+
+    pub fn volume(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn contains(&self, x: i32, y: i32, z: i32) -> bool {
+        (0..self.size.x).contains(&x) && (0..self.size.y).contains(&y) && (0..self.size.z).contains(&z)
+    }
+
+    pub fn index(&self, x: i32, y: i32, z: i32) -> usize {
+        ((y as usize * self.size.z as usize) + z as usize) * self.size.x as usize + x as usize
+    }
+
+    /// 由线性索引还原坐标
+    pub fn position(&self, index: usize) -> Vec3i {
+        let sx = self.size.x as usize;
+        let sz = self.size.z as usize;
+        Vec3i::new((index % sx) as i32, (index / (sx * sz)) as i32, ((index / sx) % sz) as i32)
+    }
+
+    pub fn block(&self, x: i32, y: i32, z: i32) -> &BlockState {
+        &self.palette[self.blocks[self.index(x, y, z)] as usize]
+    }
+
+    /// 查找或追加调色板条目
+    pub fn palette_index(&mut self, state: &BlockState) -> u32 {
+        match self.palette.iter().position(|s| s == state) {
+            Some(index) => index as u32,
+            None => {
+                self.palette.push(state.clone());
+                (self.palette.len() - 1) as u32
+            }
+        }
+    }
+
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, state: &BlockState) {
+        let palette_index = self.palette_index(state);
+        let index = self.index(x, y, z);
+        self.blocks[index] = palette_index;
+    }
+
+    pub fn block_entity(&self, pos: Vec3i) -> Option<&BlockEntity> {
+        self.block_entities.iter().find(|be| be.pos == pos)
+    }
+
+    /// 每个调色板条目出现的次数
+    pub fn palette_counts(&self) -> Vec<u64> {
+        let mut counts = vec![0u64; self.palette.len()];
+        for index in &self.blocks {
+            counts[*index as usize] += 1;
+        }
+        counts
+    }
+
+    /// 去掉未使用的调色板条目并合并重复条目
+    pub fn compact_palette(&mut self) {
+        let counts = self.palette_counts();
+        let mut lookup: HashMap<&BlockState, u32> = HashMap::new();
+        let mut palette = Vec::new();
+        let mut remap = vec![0u32; self.palette.len()];
+        for (old, state) in self.palette.iter().enumerate() {
+            if counts[old] == 0 && old != 0 {
+                continue;
+            }
+            remap[old] = *lookup.entry(state).or_insert_with(|| {
+                palette.push(state.clone());
+                (palette.len() - 1) as u32
+            });
+        }
+        for index in self.blocks.iter_mut() {
+            *index = remap[*index as usize];
+        }
+        self.palette = palette;
+    }
+
+    /// 校验方块数组长度与调色板索引
+    pub fn validate(&self) -> Result<(), SchematicError> {
+        if self.blocks.len() != checked_volume(self.size)? {
+            return Err(SchematicError::Invalid(format!("Region `{}` block count does not match its size", self.name)));
+        }
+        if let Some(bad) = self.blocks.iter().find(|i| **i as usize >= self.palette.len()) {
+            return Err(SchematicError::Invalid(format!(
+                "Region `{}` references palette index {} but the palette has {} entries",
+                self.name,
+                bad,
+                self.palette.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+pub fn checked_volume(size: Vec3i) -> Result<usize, SchematicError> {
+    if size.x <= 0 || size.y <= 0 || size.z <= 0 {
+        return Err(SchematicError::Invalid(format!("Invalid region size {}x{}x{}", size.x, size.y, size.z)));
+    }
+    // 三个 i32 的乘积可能超出 i64，溢出即视为超限
+    let volume = i64::from(size.x).checked_mul(i64::from(size.y)).and_then(|v| v.checked_mul(i64::from(size.z)));
+    match volume {
+        Some(volume) if volume <= MAX_REGION_VOLUME => Ok(volume as usize),
+        _ => Err(SchematicError::Invalid(format!(
+            "Region size {}x{}x{} exceeds the volume limit of {}",
+            size.x, size.y, size.z, MAX_REGION_VOLUME
+        ))),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schematic {
+    pub name: String,
+    pub author: String,
+    pub description: String,
+    pub data_version: i32,
+    /// 读取时的源格式
+    pub format: SchematicFormat,
+    pub regions: Vec<Region>,
+}
+
+impl Schematic {
+    pub fn new(name: impl Into<String>, data_version: i32, format: SchematicFormat) -> Schematic {
+        Schematic {
+            name: name.into(),
+            author: String::new(),
+            description: String::new(),
+            data_version,
+            format,
+            regions: Vec::new(),
+        }
+    }
+
+    /// 复制名称、作者、版本等元数据，不含任何区域
+    pub fn empty_like(&self) -> Schematic {
+        Schematic {
+            name: self.name.clone(),
+            author: self.author.clone(),
+            description: self.description.clone(),
+            data_version: self.data_version,
+            format: self.format,
+            regions: Vec::new(),
+        }
+    }
+
+    /// 识别格式并读取为通用模型，使用当前配置的上限
+    pub fn from_bytes(data: &[u8]) -> Result<Schematic, SchematicError> {
+        Schematic::from_bytes_with_limits(data, &IngestLimits::current())
+    }
+
+    pub fn from_bytes_with_limits(data: &[u8], limits: &IngestLimits) -> Result<Schematic, SchematicError> {
+        let document = crate::read_document_with_limits(data, limits)?;
+        Schematic::from_root(&document.root)
+    }
+
+    pub fn from_root(root: &NbtCompound) -> Result<Schematic, SchematicError> {
+        let schematic = match detect_from_root(root).0 {
+            SchematicFormat::Litematica => crate::read_litematic(root)?,
+            SchematicFormat::Sponge => crate::read_sponge(root)?,
+            SchematicFormat::McEdit => crate::read_mcedit(root)?,
+            SchematicFormat::Structure => crate::read_structure(root)?,
+            SchematicFormat::McStructure => crate::read_mcstructure(root)?,
+            SchematicFormat::Unknown => {
+                return Err(SchematicError::Invalid("NBT root does not match any schematic format".to_string()))
+            }
+        };
+        for region in &schematic.regions {
+            region.validate()?;
+        }
+        Ok(schematic)
+    }
+
+    /// 所有区域的包围盒 (最小角, 尺寸)；超出 i32 坐标范围时报错
+    pub fn bounds(&self) -> Result<(Vec3i, Vec3i), SchematicError> {
+        if self.regions.is_empty() {
+            return Ok((Vec3i::default(), Vec3i::default()));
+        }
+        let mut min = Vec3i::new(i32::MAX, i32::MAX, i32::MAX);
+        let mut max = Vec3i::new(i32::MIN, i32::MIN, i32::MIN);
+        for region in &self.regions {
+            let out_of_range =
+                || SchematicError::Invalid(format!("Region `{}` extends beyond the coordinate range", region.name));
+            let end = |origin: i32, size: i32| origin.checked_add(size).ok_or_else(out_of_range);
+            min.x = min.x.min(region.origin.x);
+            min.y = min.y.min(region.origin.y);
+            min.z = min.z.min(region.origin.z);
+            max.x = max.x.max(end(region.origin.x, region.size.x)?);
+            max.y = max.y.max(end(region.origin.y, region.size.y)?);
+            max.z = max.z.max(end(region.origin.z, region.size.z)?);
+        }
+        let span = |max: i32, min: i32| {
+            max.checked_sub(min)
+                .ok_or_else(|| SchematicError::Invalid("Schematic bounds exceed the coordinate range".to_string()))
+        };
+        Ok((min, Vec3i::new(span(max.x, min.x)?, span(max.y, min.y)?, span(max.z, min.z)?)))
+    }
+
+    pub fn total_volume(&self) -> u64 {
+        self.regions.iter().map(|r| r.volume() as u64).sum()
+    }
+
+    /// 将所有区域合并为覆盖整体包围盒的单个区域，区域之间的空隙用 `fill` 填充。
+    /// 后面的区域覆盖前面的区域（空气不覆盖），返回合并后的区域与冲突方块数
+    pub fn flatten(&self, fill: &BlockState) -> Result<(Region, u64), SchematicError> {
+        let (min, size) = self.bounds()?;
+        let mut merged = Region::new("Main", min, size)?;
+        merged.palette[0] = fill.clone();
+        let mut conflicts = 0;
+
+        for region in &self.regions {
+            let remap: Vec<u32> = region.palette.iter().map(|state| merged.palette_index(state)).collect();
+            let offset = Vec3i::new(region.origin.x - min.x, region.origin.y - min.y, region.origin.z - min.z);
+            for (index, palette_index) in region.blocks.iter().enumerate() {
+                let source = &region.palette[*palette_index as usize];
+                if source.is_air() && self.regions.len() > 1 {
+                    continue;
+                }
+                let pos = region.position(index);
+                let target = merged.index(pos.x + offset.x, pos.y + offset.y, pos.z + offset.z);
+                let existing = merged.blocks[target];
+                if existing != 0 && existing != remap[*palette_index as usize] && !merged.palette[existing as usize].is_air() {
+                    conflicts += 1;
+                }
+                merged.blocks[target] = remap[*palette_index as usize];
+            }
+
+            for block_entity in &region.block_entities {
+                let pos = Vec3i::new(block_entity.pos.x + offset.x, block_entity.pos.y + offset.y, block_entity.pos.z + offset.z);
+                merged.block_entities.retain(|be| be.pos != pos);
+                merged.block_entities.push(BlockEntity { pos, ..block_entity.clone() });
+            }
+            for entity in &region.entities {
+                let pos = [
+                    entity.pos[0] + f64::from(offset.x),
+                    entity.pos[1] + f64::from(offset.y),
+                    entity.pos[2] + f64::from(offset.z),
+                ];
+                merged.entities.push(Entity { pos, ..entity.clone() });
+            }
+        }
+
+        merged.compact_palette();
+        Ok((merged, conflicts))
+    }
+
+    /// 非空气方块总数
+    pub fn total_blocks(&self) -> u64 {
+        self.regions
+            .iter()
+            .map(|region| {
+                let counts = region.palette_counts();
+                region
+                    .palette
+                    .iter()
+                    .zip(counts)
+                    .filter(|(state, _)| !state.is_air() && state.name != STRUCTURE_VOID)
+                    .map(|(_, count)| count)
+                    .sum::<u64>()
+            })
+            .sum()
+    }
+}
+
+// 各格式读取器共用的小工具
+
+pub(crate) fn required_compound<'a>(root: &'a NbtCompound, key: &str) -> Result<&'a NbtCompound, SchematicError> {
+    root.get_compound(key).ok_or_else(|| SchematicError::MissingField(key.to_string()))
+}
+
+pub(crate) fn required_i32(root: &NbtCompound, key: &str) -> Result<i32, SchematicError> {
+    root.get_i32(key).ok_or_else(|| SchematicError::MissingField(key.to_string()))
+}
+
+/// 读取长度为 3 的整数列表或整数数组坐标
+pub(crate) fn read_int_triple(tag: Option<&NbtTag>) -> Option<Vec3i> {
+    match tag? {
+        NbtTag::IntArray(values) if values.len() == 3 => Some(Vec3i::new(values[0], values[1], values[2])),
+        NbtTag::List(list) if list.len() == 3 => {
+            let v: Vec<i32> = list.iter().filter_map(|t| t.as_i64().map(|v| v as i32)).collect();
+            (v.len() == 3).then(|| Vec3i::new(v[0], v[1], v[2]))
+        }
+        _ => None,
+    }
+}
+
+/// 读取长度为 3 的浮点数列表坐标
+pub(crate) fn read_double_triple(tag: Option<&NbtTag>) -> Option<[f64; 3]> {
+    let list = tag?.as_list()?;
+    let v: Vec<f64> = list.iter().filter_map(NbtTag::as_f64).collect();
+    (v.len() == 3).then(|| [v[0], v[1], v[2]])
+}
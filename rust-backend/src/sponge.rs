@@ -0,0 +1,260 @@
+use crate::litematic::entity_to_nbt;
+use crate::schematic::{read_double_triple, read_int_triple, required_i32};
+use crate::{
+    checked_volume, BlockEntity, BlockState, ConversionWarning, Entity, NbtCompound, NbtCompression, NbtDocument,
+    NbtList, NbtTag, Region, Schematic, SchematicError, SchematicFormat, Vec3i, TAG_COMPOUND, TAG_DOUBLE,
+};
+
+// Sponge (.schem) v1/v2/v3 读写
+
+/// v1 文件不记录 DataVersion，按 WorldEdit 7 首个版本（1.13.2）处理
+const SPONGE_V1_DATA_VERSION: i32 = 1631;
+
+/// 调色板 id 上限，防止稀疏 id 导致超大分配
+const MAX_PALETTE_ID: i32 = 1 << 20;
+
+pub fn read_sponge(root: &NbtCompound) -> Result<Schematic, SchematicError> {
+    // v3 将内容包在 "Schematic" 复合标签内
+    let body = root.get_compound("Schematic").unwrap_or(root);
+    let version = body.get_i32("Version").unwrap_or(1);
+
+    // 尺寸以无符号 short 存储
+    let dimension = |key: &str| required_i32(body, key).map(|v| i32::from(v as u16));
+    let size = Vec3i::new(dimension("Width")?, dimension("Height")?, dimension("Length")?);
+    let volume = checked_volume(size)?;
+
+    let (palette_tag, block_data, block_entities) = if version >= 3 {
+        let blocks = body
+            .get_compound("Blocks")
+            .ok_or_else(|| SchematicError::MissingField("Blocks".to_string()))?;
+        (blocks.get_compound("Palette"), blocks.get_byte_array("Data"), blocks.get_list("BlockEntities"))
+    } else {
+        (
+            body.get_compound("Palette"),
+            body.get_byte_array("BlockData"),
+            body.get_list("BlockEntities").or_else(|| body.get_list("TileEntities")),
+        )
+    };
+    let palette_tag = palette_tag.ok_or_else(|| SchematicError::MissingField("Palette".to_string()))?;
+    let block_data = block_data.ok_or_else(|| SchematicError::MissingField("BlockData".to_string()))?;
+
+    let palette = read_palette(palette_tag)?;
+    let blocks = decode_varints(block_data, volume)?;
+    if let Some(bad) = blocks.iter().find(|i| **i as usize >= palette.len()) {
+        return Err(SchematicError::Invalid(format!("Block data references unknown palette id {}", bad)));
+    }
+
+    let data_version = body
+        .get_i32("DataVersion")
+        .unwrap_or(if version >= 2 { 0 } else { SPONGE_V1_DATA_VERSION });
+    let mut schematic = Schematic::new("", data_version, SchematicFormat::Sponge);
+    if let Some(metadata) = body.get_compound("Metadata") {
+        schematic.name = metadata.get_str("Name").unwrap_or_default().to_string();
+        schematic.author = metadata.get_str("Author").unwrap_or_default().to_string();
+    }
+
+    // Offset 是相对粘贴点的偏移，与 Litematica 区域位置含义一致
+    let origin = read_int_triple(body.get("Offset")).unwrap_or_default();
+    let mut region = Region {
+        name: "Main".to_string(),
+        origin,
+        size,
+        palette,
+        blocks,
+        block_entities: Vec::new(),
+        entities: Vec::new(),
+    };
+
+    if let Some(list) = block_entities {
+        for tag in list.compounds() {
+            let mut nbt = tag.clone();
+            let Some(pos) = read_int_triple(nbt.remove("Pos").as_ref()) else { continue };
+            let id = take_id(&mut nbt);
+            let nbt = unwrap_data(nbt, version);
+            region.block_entities.push(BlockEntity { pos, id, nbt });
+        }
+    }
+
+    if let Some(list) = body.get_list("Entities") {
+        for tag in list.compounds() {
+            let mut nbt = tag.clone();
+            let Some(pos) = read_double_triple(nbt.remove("Pos").as_ref()) else { continue };
+            let id = take_id(&mut nbt);
+            let mut nbt = unwrap_data(nbt, version);
+            nbt.remove("Pos");
+            region.entities.push(Entity { pos, id, nbt });
+        }
+    }
+
+    schematic.regions.push(region);
+    Ok(schematic)
+}
+
+/// 写出 Sponge v2 或 v3；多区域投影会合并为一个区域
+pub fn write_sponge(
+    schematic: &Schematic,
+    version: i32,
+    warnings: &mut Vec<ConversionWarning>,
+) -> Result<NbtDocument, SchematicError> {
+    let region = crate::single_region(schematic, &BlockState::air(), warnings)?;
+    if region.size.x > i32::from(u16::MAX) || region.size.y > i32::from(u16::MAX) || region.size.z > i32::from(u16::MAX) {
+        return Err(SchematicError::Invalid("Sponge schematics are limited to 65535 blocks per axis".to_string()));
+    }
+
+    let mut palette = NbtCompound::new();
+    for (id, state) in region.palette.iter().enumerate() {
+        palette.insert(state.to_string(), NbtTag::Int(id as i32));
+    }
+    let block_data = encode_varints(&region.blocks);
+
+    let mut block_entities = NbtList::new(TAG_COMPOUND);
+    for block_entity in &region.block_entities {
+        let mut nbt = NbtCompound::new();
+        nbt.insert("Pos", NbtTag::IntArray(vec![block_entity.pos.x, block_entity.pos.y, block_entity.pos.z]));
+        nbt.insert("Id", NbtTag::String(block_entity.id.clone()));
+        if version >= 3 {
+            nbt.insert("Data", NbtTag::Compound(block_entity.nbt.clone()));
+        } else {
+            for (key, value) in block_entity.nbt.iter() {
+                nbt.insert(key, value.clone());
+            }
+        }
+        block_entities.values.push(NbtTag::Compound(nbt));
+    }
+
+    let mut entities = NbtList::new(TAG_COMPOUND);
+    for entity in &region.entities {
+        let nbt = if version >= 3 {
+            let mut nbt = NbtCompound::new();
+            nbt.insert(
+                "Pos",
+                NbtTag::List(NbtList {
+                    element_type: TAG_DOUBLE,
+                    values: entity.pos.iter().map(|v| NbtTag::Double(*v)).collect(),
+                }),
+            );
+            nbt.insert("Id", NbtTag::String(entity.id.clone()));
+            nbt.insert("Data", NbtTag::Compound(entity.nbt.clone()));
+            nbt
+        } else {
+            let mut nbt = entity_to_nbt(entity);
+            if let Some(id) = nbt.remove("id") {
+                nbt.insert("Id", id);
+            }
+            nbt
+        };
+        entities.values.push(NbtTag::Compound(nbt));
+    }
+
+    let mut metadata = NbtCompound::new();
+    metadata.insert("Name", NbtTag::String(schematic.name.clone()));
+    metadata.insert("Author", NbtTag::String(schematic.author.clone()));
+    metadata.insert("Date", NbtTag::Long(chrono::Utc::now().timestamp_millis()));
+
+    let mut body = NbtCompound::new();
+    body.insert("Version", NbtTag::Int(version));
+    body.insert("DataVersion", NbtTag::Int(schematic.data_version));
+    body.insert("Metadata", NbtTag::Compound(metadata));
+    body.insert("Width", NbtTag::Short(region.size.x as u16 as i16));
+    body.insert("Height", NbtTag::Short(region.size.y as u16 as i16));
+    body.insert("Length", NbtTag::Short(region.size.z as u16 as i16));
+    body.insert("Offset", NbtTag::IntArray(vec![region.origin.x, region.origin.y, region.origin.z]));
+
+    if version >= 3 {
+        let mut blocks = NbtCompound::new();
+        blocks.insert("Palette", NbtTag::Compound(palette));
+        blocks.insert("Data", NbtTag::ByteArray(block_data));
+        blocks.insert("BlockEntities", NbtTag::List(block_entities));
+        body.insert("Blocks", NbtTag::Compound(blocks));
+        body.insert("Entities", NbtTag::List(entities));
+        let mut root = NbtCompound::new();
+        root.insert("Schematic", NbtTag::Compound(body));
+        Ok(NbtDocument::new("", root, NbtCompression::Gzip))
+    } else {
+        body.insert("PaletteMax", NbtTag::Int(region.palette.len() as i32));
+        body.insert("Palette", NbtTag::Compound(palette));
+        body.insert("BlockData", NbtTag::ByteArray(block_data));
+        body.insert("BlockEntities", NbtTag::List(block_entities));
+        body.insert("Entities", NbtTag::List(entities));
+        Ok(NbtDocument::new("Schematic", body, NbtCompression::Gzip))
+    }
+}
+
+fn read_palette(palette_tag: &NbtCompound) -> Result<Vec<BlockState>, SchematicError> {
+    let mut entries = Vec::with_capacity(palette_tag.len());
+    for (key, value) in palette_tag.iter() {
+        let id = value
+            .as_i64()
+            .filter(|id| (0..i64::from(MAX_PALETTE_ID)).contains(id))
+            .ok_or_else(|| SchematicError::Invalid(format!("Invalid palette id for `{}`", key)))?;
+        entries.push((id as usize, BlockState::parse(key)?));
+    }
+
+    let len = entries.iter().map(|(id, _)| id + 1).max().unwrap_or(0);
+    let mut palette = vec![BlockState::air(); len];
+    for (id, state) in entries {
+        palette[id] = state;
+    }
+    if palette.is_empty() {
+        return Err(SchematicError::Invalid("Palette is empty".to_string()));
+    }
+    Ok(palette)
+}
+
+/// 解码 BlockData 中的 varint 序列
+pub(crate) fn decode_varints(data: &[i8], count: usize) -> Result<Vec<u32>, SchematicError> {
+    if data.len() < count {
+        return Err(SchematicError::Invalid("Block data is shorter than the schematic volume".to_string()));
+    }
+    let mut values = Vec::with_capacity(count);
+    let mut iter = data.iter().map(|b| *b as u8);
+    while values.len() < count {
+        let mut value: u32 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = iter
+                .next()
+                .ok_or_else(|| SchematicError::Invalid("Block data ended in the middle of a varint".to_string()))?;
+            value |= u32::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+            if shift > 28 {
+                return Err(SchematicError::Invalid("Block data contains an oversized varint".to_string()));
+            }
+        }
+        values.push(value);
+    }
+    Ok(values)
+}
+
+pub(crate) fn encode_varints(values: &[u32]) -> Vec<i8> {
+    let mut out = Vec::with_capacity(values.len());
+    for value in values {
+        let mut value = *value;
+        while value >= 0x80 {
+            out.push(((value & 0x7f) as u8 | 0x80) as i8);
+            value >>= 7;
+        }
+        out.push(value as i8);
+    }
+    out
+}
+
+fn take_id(nbt: &mut NbtCompound) -> String {
+    nbt.remove("Id")
+        .or_else(|| nbt.remove("id"))
+        .and_then(|tag| tag.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// v3 把实体数据放在 "Data" 子标签中，v1/v2 直接与 Pos/Id 并列
+fn unwrap_data(mut nbt: NbtCompound, version: i32) -> NbtCompound {
+    if version >= 3 {
+        if let Some(NbtTag::Compound(data)) = nbt.remove("Data") {
+            return data;
+        }
+    }
+    nbt
+}
@@ -0,0 +1,1265 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use crate::litematic::bits_for_palette;
+use crate::nbt::decode_mutf8;
+use crate::{
+    legacy_block_state, BlockState, IngestLimits, MaterialList, MaterialMode, NbtCompound, NbtCompression, NbtError,
+    NbtList, NbtTag, NeptuniumError, SchematicError, SchematicFormat, Vec3i, TAG_BYTE, TAG_BYTE_ARRAY, TAG_COMPOUND,
+    TAG_DOUBLE, TAG_END, TAG_FLOAT, TAG_INT, TAG_INT_ARRAY, TAG_LIST, TAG_LONG, TAG_LONG_ARRAY, TAG_SHORT, TAG_STRING,
+};
+
+// 增量式 NBT 解析：数据可分块喂入，以回调形式交给访问者，
+// 访问者可跳过或整体收集子树，整个过程不构建完整的标签树
+
+/// 访问者对容器标签的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    /// 逐个回调其内容
+    Enter,
+    /// 跳过整个子树
+    Skip,
+    /// 构建为完整标签后通过 `value` 一次性交给访问者，适合调色板等小型子树
+    Collect,
+}
+
+/// 数组标签中的一段数据（大端序原始字节），大数组会分多段回调
+#[derive(Debug, Clone, Copy)]
+pub struct ArrayChunk<'a> {
+    pub element_type: u8,
+    data: &'a [u8],
+}
+
+impl<'a> ArrayChunk<'a> {
+    pub fn len(&self) -> usize {
+        self.data.len() / element_size(self.element_type)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn bytes(&self) -> impl Iterator<Item = i8> + 'a {
+        self.data.iter().map(|b| *b as i8)
+    }
+
+    pub fn ints(&self) -> impl Iterator<Item = i32> + 'a {
+        self.data.chunks_exact(4).map(|c| i32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+    }
+
+    pub fn longs(&self) -> impl Iterator<Item = i64> + 'a {
+        self.data
+            .chunks_exact(8)
+            .map(|c| i64::from_be_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]))
+    }
+}
+
+/// 解析事件的接收者；列表元素没有名称，以 None 表示
+pub trait NbtVisitor {
+    fn start_compound(&mut self, _name: Option<&str>) -> Visit {
+        Visit::Enter
+    }
+
+    fn end_compound(&mut self) {}
+
+    fn start_list(&mut self, _name: Option<&str>, _element_type: u8, _length: usize) -> Visit {
+        Visit::Enter
+    }
+
+    fn end_list(&mut self) {}
+
+    fn start_array(&mut self, _name: Option<&str>, _element_type: u8, _length: usize) -> Visit {
+        Visit::Enter
+    }
+
+    fn array_chunk(&mut self, _chunk: ArrayChunk<'_>) {}
+
+    fn end_array(&mut self) {}
+
+    /// 数值、字符串，以及以 `Visit::Collect` 收集完成的容器
+    fn value(&mut self, _name: Option<&str>, _value: NbtTag) {}
+}
+
+fn element_size(array_type: u8) -> usize {
+    match array_type {
+        TAG_INT_ARRAY => 4,
+        TAG_LONG_ARRAY => 8,
+        _ => 1,
+    }
+}
+
+fn peek_u16(bytes: &[u8], at: usize) -> Option<usize> {
+    Some(usize::from(u16::from_be_bytes([*bytes.get(at)?, *bytes.get(at + 1)?])))
+}
+
+/// 标签开始处必须一次性读完的字节数；数据不足以判断时返回 None
+fn prefix_len(tag_type: u8, bytes: &[u8]) -> Option<usize> {
+    Some(match tag_type {
+        TAG_BYTE => 1,
+        TAG_SHORT => 2,
+        TAG_INT | TAG_FLOAT => 4,
+        TAG_LONG | TAG_DOUBLE => 8,
+        TAG_STRING => 2 + peek_u16(bytes, 0)?,
+        TAG_LIST => 5,
+        TAG_BYTE_ARRAY | TAG_INT_ARRAY | TAG_LONG_ARRAY => 4,
+        _ => 0,
+    })
+}
+
+fn child_mode(parent: Visit, visit: impl FnOnce() -> Visit) -> Visit {
+    match parent {
+        Visit::Enter => visit(),
+        other => other,
+    }
+}
+
+#[derive(Debug)]
+enum FrameKind {
+    Compound,
+    List { element_type: u8, remaining: usize },
+    Array { element_type: u8, remaining: usize },
+}
+
+#[derive(Debug)]
+struct Frame {
+    kind: FrameKind,
+    mode: Visit,
+    name: Option<String>,
+}
+
+/// 未压缩 NBT 的增量解析器。使用显式栈而非递归，缓冲区只保留尚未解析完的一个标签头，
+/// 大数组按已到达的数据分段回调
+pub struct NbtStreamParser {
+    limits: IngestLimits,
+    buf: Vec<u8>,
+    pos: usize,
+    /// 已从缓冲区丢弃的字节数，用于错误信息中的偏移
+    offset: usize,
+    stack: Vec<Frame>,
+    /// 正在收集的容器，与 `Visit::Collect` 的栈帧一一对应
+    building: Vec<NbtTag>,
+    started: bool,
+    finished: bool,
+}
+
+impl NbtStreamParser {
+    pub fn new(limits: IngestLimits) -> NbtStreamParser {
+        NbtStreamParser {
+            limits,
+            buf: Vec::new(),
+            pos: 0,
+            offset: 0,
+            stack: Vec::new(),
+            building: Vec::new(),
+            started: false,
+            finished: false,
+        }
+    }
+
+    /// 根标签是否已经结束
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn feed<V: NbtVisitor + ?Sized>(&mut self, data: &[u8], visitor: &mut V) -> Result<(), NbtError> {
+        if self.finished {
+            return match data.len() {
+                0 => Ok(()),
+                extra => Err(NbtError::TrailingData(extra)),
+            };
+        }
+        self.buf.extend_from_slice(data);
+        while !self.finished && self.step(visitor)? {}
+        if self.finished && self.pos < self.buf.len() {
+            return Err(NbtError::TrailingData(self.buf.len() - self.pos));
+        }
+        self.buf.drain(..self.pos);
+        self.offset += self.pos;
+        self.pos = 0;
+        Ok(())
+    }
+
+    /// 输入结束时调用，根标签未闭合即为数据截断
+    pub fn finish(&self) -> Result<(), NbtError> {
+        if !self.finished {
+            return Err(NbtError::UnexpectedEof(self.offset + self.buf.len()));
+        }
+        Ok(())
+    }
+
+    /// 解析一个标签头或一段数组数据；数据不足时返回 false 等待下一块
+    fn step<V: NbtVisitor + ?Sized>(&mut self, visitor: &mut V) -> Result<bool, NbtError> {
+        let available = &self.buf[self.pos..];
+        let Some(frame) = self.stack.last_mut() else {
+            let Some(&tag_type) = available.first() else { return Ok(false) };
+            if tag_type != TAG_COMPOUND {
+                return Err(NbtError::InvalidRootTag(tag_type));
+            }
+            let Some(name_len) = peek_u16(available, 1) else { return Ok(false) };
+            let Some(name) = available.get(3..3 + name_len) else { return Ok(false) };
+            let name = decode_mutf8(name).ok_or(NbtError::InvalidString(self.offset + self.pos + 1))?;
+            self.pos += 3 + name_len;
+            self.started = true;
+            self.open(TAG_COMPOUND, Some(name), visitor)?;
+            return Ok(true);
+        };
+
+        match &mut frame.kind {
+            FrameKind::Compound => {
+                let Some(&tag_type) = available.first() else { return Ok(false) };
+                if tag_type == TAG_END {
+                    self.pos += 1;
+                    self.close(visitor);
+                    return Ok(true);
+                }
+                if tag_type > TAG_LONG_ARRAY {
+                    return Err(NbtError::InvalidTagType { tag_type, offset: self.offset + self.pos });
+                }
+                let Some(name_len) = peek_u16(available, 1) else { return Ok(false) };
+                let header = 3 + name_len;
+                let Some(prefix) = available.get(header..).and_then(|rest| prefix_len(tag_type, rest)) else {
+                    return Ok(false);
+                };
+                if available.len() < header + prefix {
+                    return Ok(false);
+                }
+                let name = decode_mutf8(&available[3..header]).ok_or(NbtError::InvalidString(self.offset + self.pos + 1))?;
+                self.pos += header;
+                self.open(tag_type, Some(name), visitor)?;
+                Ok(true)
+            }
+            FrameKind::List { element_type, remaining } => {
+                if *remaining == 0 {
+                    self.close(visitor);
+                    return Ok(true);
+                }
+                let element_type = *element_type;
+                match prefix_len(element_type, available) {
+                    Some(prefix) if available.len() >= prefix => {
+                        *remaining -= 1;
+                        self.open(element_type, None, visitor)?;
+                        Ok(true)
+                    }
+                    _ => Ok(false),
+                }
+            }
+            FrameKind::Array { element_type, remaining } => {
+                if *remaining == 0 {
+                    self.close(visitor);
+                    return Ok(true);
+                }
+                let element_type = *element_type;
+                let size = element_size(element_type);
+                let count = (available.len() / size).min(*remaining);
+                if count == 0 {
+                    return Ok(false);
+                }
+                *remaining -= count;
+                let chunk = ArrayChunk { element_type, data: &available[..count * size] };
+                match frame.mode {
+                    Visit::Enter => visitor.array_chunk(chunk),
+                    Visit::Skip => {}
+                    Visit::Collect => match self.building.last_mut() {
+                        Some(NbtTag::ByteArray(values)) => values.extend(chunk.bytes()),
+                        Some(NbtTag::IntArray(values)) => values.extend(chunk.ints()),
+                        Some(NbtTag::LongArray(values)) => values.extend(chunk.longs()),
+                        _ => {}
+                    },
+                }
+                self.pos += count * size;
+                Ok(true)
+            }
+        }
+    }
+
+    /// 读取标签的开头部分（调用方已确认数据足够）
+    fn open<V: NbtVisitor + ?Sized>(&mut self, tag_type: u8, name: Option<String>, visitor: &mut V) -> Result<(), NbtError> {
+        let offset = self.offset + self.pos;
+        let parent = self.stack.last().map_or(Visit::Enter, |f| f.mode);
+        match tag_type {
+            TAG_COMPOUND => {
+                self.check_depth()?;
+                let mode = child_mode(parent, || visitor.start_compound(name.as_deref()));
+                if mode == Visit::Collect {
+                    self.building.push(NbtTag::Compound(NbtCompound::new()));
+                }
+                self.stack.push(Frame { kind: FrameKind::Compound, mode, name });
+            }
+            TAG_LIST => {
+                self.check_depth()?;
+                let element_type = self.buf[self.pos];
+                if element_type > TAG_LONG_ARRAY {
+                    return Err(NbtError::InvalidTagType { tag_type: element_type, offset });
+                }
+                let length = self.length_at(self.pos + 1, self.limits.max_list_length)?;
+                if element_type == TAG_END && length > 0 {
+                    return Err(NbtError::InvalidTagType { tag_type: TAG_END, offset });
+                }
+                self.pos += 5;
+                let mode = child_mode(parent, || visitor.start_list(name.as_deref(), element_type, length));
+                if mode == Visit::Collect {
+                    self.building.push(NbtTag::List(NbtList::new(element_type)));
+                }
+                self.stack.push(Frame { kind: FrameKind::List { element_type, remaining: length }, mode, name });
+            }
+            TAG_BYTE_ARRAY | TAG_INT_ARRAY | TAG_LONG_ARRAY => {
+                let length = self.length_at(self.pos, self.limits.max_array_length)?;
+                self.pos += 4;
+                let mode = child_mode(parent, || visitor.start_array(name.as_deref(), tag_type, length));
+                if mode == Visit::Collect {
+                    self.building.push(match tag_type {
+                        TAG_BYTE_ARRAY => NbtTag::ByteArray(Vec::new()),
+                        TAG_INT_ARRAY => NbtTag::IntArray(Vec::new()),
+                        _ => NbtTag::LongArray(Vec::new()),
+                    });
+                }
+                let kind = FrameKind::Array { element_type: tag_type, remaining: length };
+                self.stack.push(Frame { kind, mode, name });
+            }
+            _ => {
+                let len = prefix_len(tag_type, &self.buf[self.pos..]).unwrap_or(0);
+                if parent != Visit::Skip {
+                    let tag = self.read_scalar(tag_type, len)?;
+                    self.emit(name, tag, visitor);
+                }
+                self.pos += len;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_scalar(&self, tag_type: u8, len: usize) -> Result<NbtTag, NbtError> {
+        let b = &self.buf[self.pos..self.pos + len];
+        let be8 = || [b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]];
+        Ok(match tag_type {
+            TAG_BYTE => NbtTag::Byte(b[0] as i8),
+            TAG_SHORT => NbtTag::Short(i16::from_be_bytes([b[0], b[1]])),
+            TAG_INT => NbtTag::Int(i32::from_be_bytes([b[0], b[1], b[2], b[3]])),
+            TAG_LONG => NbtTag::Long(i64::from_be_bytes(be8())),
+            TAG_FLOAT => NbtTag::Float(f32::from_be_bytes([b[0], b[1], b[2], b[3]])),
+            TAG_DOUBLE => NbtTag::Double(f64::from_be_bytes(be8())),
+            _ => NbtTag::String(decode_mutf8(&b[2..]).ok_or(NbtError::InvalidString(self.offset + self.pos))?),
+        })
+    }
+
+    fn length_at(&self, at: usize, limit: usize) -> Result<usize, NbtError> {
+        let b = &self.buf[at..at + 4];
+        let length = i32::from_be_bytes([b[0], b[1], b[2], b[3]]);
+        let offset = self.offset + at;
+        if length < 0 {
+            return Err(NbtError::NegativeLength { length, offset });
+        }
+        let length = length as usize;
+        if length > limit {
+            return Err(NbtError::ListTooLong { length, limit, offset });
+        }
+        Ok(length)
+    }
+
+    /// 数组不会有子标签，栈中的帧数即新容器的嵌套深度
+    fn check_depth(&self) -> Result<(), NbtError> {
+        if self.stack.len() > self.limits.max_depth {
+            return Err(NbtError::TooDeep(self.limits.max_depth));
+        }
+        Ok(())
+    }
+
+    /// 将完成的值交给所在容器：回调访问者，或追加到正在收集的父容器
+    fn emit<V: NbtVisitor + ?Sized>(&mut self, name: Option<String>, tag: NbtTag, visitor: &mut V) {
+        match self.stack.last().map_or(Visit::Enter, |f| f.mode) {
+            Visit::Enter => visitor.value(name.as_deref(), tag),
+            Visit::Skip => {}
+            Visit::Collect => match self.building.last_mut() {
+                Some(NbtTag::Compound(compound)) => {
+                    compound.insert(name.unwrap_or_default(), tag);
+                }
+                Some(NbtTag::List(list)) => list.values.push(tag),
+                _ => {}
+            },
+        }
+    }
+
+    fn close<V: NbtVisitor + ?Sized>(&mut self, visitor: &mut V) {
+        let Some(frame) = self.stack.pop() else { return };
+        match frame.mode {
+            Visit::Enter => match frame.kind {
+                FrameKind::Compound => visitor.end_compound(),
+                FrameKind::List { .. } => visitor.end_list(),
+                FrameKind::Array { .. } => visitor.end_array(),
+            },
+            Visit::Skip => {}
+            Visit::Collect => {
+                if let Some(tag) = self.building.pop() {
+                    self.emit(frame.name, tag, visitor);
+                }
+            }
+        }
+        if self.stack.is_empty() {
+            self.finished = true;
+        }
+    }
+}
+
+// 每次送入解压器的输入上限；deflate 的压缩比不超过约 1032:1，单次输出因此有界
+const INFLATE_STEP: usize = 8 * 1024;
+
+enum StreamDecoder {
+    Raw,
+    Gzip(flate2::write::MultiGzDecoder<Vec<u8>>),
+    Zlib(flate2::write::ZlibDecoder<Vec<u8>>),
+}
+
+/// 带解压的增量读取器：按块喂入原始文件数据，自动识别 gzip / zlib / 未压缩
+pub struct NbtStreamReader {
+    parser: NbtStreamParser,
+    decoder: Option<StreamDecoder>,
+    /// 识别压缩方式之前缓存的开头字节
+    header: Vec<u8>,
+    compression: NbtCompression,
+    bytes_read: usize,
+    bytes_decoded: usize,
+    max_decompressed_bytes: usize,
+}
+
+impl NbtStreamReader {
+    pub fn new(limits: IngestLimits) -> NbtStreamReader {
+        NbtStreamReader {
+            parser: NbtStreamParser::new(limits),
+            decoder: None,
+            header: Vec::new(),
+            compression: NbtCompression::None,
+            bytes_read: 0,
+            bytes_decoded: 0,
+            max_decompressed_bytes: limits.max_decompressed_bytes,
+        }
+    }
+
+    pub fn compression(&self) -> NbtCompression {
+        self.compression
+    }
+
+    pub fn bytes_read(&self) -> usize {
+        self.bytes_read
+    }
+
+    pub fn bytes_decoded(&self) -> usize {
+        self.bytes_decoded
+    }
+
+    pub fn push<V: NbtVisitor + ?Sized>(&mut self, chunk: &[u8], visitor: &mut V) -> Result<(), NbtError> {
+        self.bytes_read += chunk.len();
+        if self.decoder.is_some() {
+            return self.inflate(chunk, visitor);
+        }
+        self.header.extend_from_slice(chunk);
+        if self.header.len() < 2 {
+            return Ok(());
+        }
+        self.start_decoder(visitor)
+    }
+
+    pub fn finish<V: NbtVisitor + ?Sized>(&mut self, visitor: &mut V) -> Result<(), NbtError> {
+        if self.decoder.is_none() {
+            self.start_decoder(visitor)?;
+        }
+        let rest = match self.decoder.as_mut() {
+            Some(StreamDecoder::Gzip(decoder)) => {
+                decoder.try_finish().map_err(|e| NbtError::Decompression(e.to_string()))?;
+                std::mem::take(decoder.get_mut())
+            }
+            Some(StreamDecoder::Zlib(decoder)) => {
+                decoder.try_finish().map_err(|e| NbtError::Decompression(e.to_string()))?;
+                std::mem::take(decoder.get_mut())
+            }
+            _ => Vec::new(),
+        };
+        self.forward(&rest, visitor)?;
+        self.parser.finish()
+    }
+
+    fn start_decoder<V: NbtVisitor + ?Sized>(&mut self, visitor: &mut V) -> Result<(), NbtError> {
+        let header = std::mem::take(&mut self.header);
+        self.compression = NbtCompression::detect(&header);
+        self.decoder = Some(match self.compression {
+            NbtCompression::None => StreamDecoder::Raw,
+            NbtCompression::Gzip => StreamDecoder::Gzip(flate2::write::MultiGzDecoder::new(Vec::new())),
+            NbtCompression::Zlib => StreamDecoder::Zlib(flate2::write::ZlibDecoder::new(Vec::new())),
+        });
+        self.inflate(&header, visitor)
+    }
+
+    fn inflate<V: NbtVisitor + ?Sized>(&mut self, input: &[u8], visitor: &mut V) -> Result<(), NbtError> {
+        let decompression = |e: std::io::Error| NbtError::Decompression(e.to_string());
+        for step in input.chunks(INFLATE_STEP) {
+            let output = match self.decoder.as_mut() {
+                Some(StreamDecoder::Gzip(decoder)) => {
+                    decoder.write_all(step).and_then(|_| decoder.flush()).map_err(decompression)?;
+                    std::mem::take(decoder.get_mut())
+                }
+                Some(StreamDecoder::Zlib(decoder)) => {
+                    decoder.write_all(step).and_then(|_| decoder.flush()).map_err(decompression)?;
+                    std::mem::take(decoder.get_mut())
+                }
+                _ => {
+                    self.forward(step, visitor)?;
+                    continue;
+                }
+            };
+            self.forward(&output, visitor)?;
+        }
+        Ok(())
+    }
+
+    fn forward<V: NbtVisitor + ?Sized>(&mut self, data: &[u8], visitor: &mut V) -> Result<(), NbtError> {
+        self.bytes_decoded += data.len();
+        if self.bytes_decoded > self.max_decompressed_bytes {
+            return Err(NbtError::DecompressedTooLarge(self.max_decompressed_bytes));
+        }
+        self.parser.feed(data, visitor)
+    }
+}
+
+// 基于流式解析的投影摘要：元数据与各方块状态数量，跳过方块实体、实体与预览图
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamRegionSummary {
+    pub name: String,
+    pub size: Vec3i,
+    pub palette_size: usize,
+    /// 非空气方块数
+    pub block_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamSummary {
+    pub format: SchematicFormat,
+    pub compression: NbtCompression,
+    pub name: String,
+    pub author: String,
+    pub description: String,
+    pub data_version: Option<i32>,
+    pub version: Option<i32>,
+    pub size: Vec3i,
+    pub regions: Vec<StreamRegionSummary>,
+    /// 非空气方块总数
+    pub total_blocks: u64,
+    pub materials: MaterialList,
+    pub bytes_read: usize,
+    pub bytes_decoded: usize,
+}
+
+/// 单个区域的计数：调色板可能出现在方块数据之后，因此按索引计数，结束时再对应到方块状态
+#[derive(Debug, Default)]
+struct RegionTally {
+    name: String,
+    size: Option<Vec3i>,
+    position: Option<Vec3i>,
+    palette: Option<Vec<BlockState>>,
+    counts: Vec<u64>,
+    decoded: u64,
+    /// Litematica：读到 BlockStates 时调色板尚未出现，暂存打包数据
+    packed: Vec<i64>,
+    unpacker: Option<BitUnpacker>,
+}
+
+impl RegionTally {
+    fn tally(&mut self, index: u32, limit: usize) -> Result<(), SchematicError> {
+        let index = index as usize;
+        if index >= self.counts.len() {
+            if index >= limit {
+                return Err(SchematicError::PaletteTooLarge { region: self.name.clone(), size: index + 1, limit });
+            }
+            self.counts.resize(index + 1, 0);
+        }
+        self.counts[index] += 1;
+        self.decoded += 1;
+        Ok(())
+    }
+
+    /// 声明尺寸的体积；三个 i32 的乘积可能超出 u64，溢出时视为未知
+    fn volume(&self) -> Option<u64> {
+        self.size.and_then(|s| {
+            u64::from(s.x.unsigned_abs())
+                .checked_mul(u64::from(s.y.unsigned_abs()))?
+                .checked_mul(u64::from(s.z.unsigned_abs()))
+        })
+    }
+}
+
+/// 紧密排列（可跨越 long 边界）位数组的流式解包
+#[derive(Debug)]
+struct BitUnpacker {
+    bits: u32,
+    acc: u128,
+    acc_bits: u32,
+}
+
+impl BitUnpacker {
+    fn new(bits: u32) -> BitUnpacker {
+        BitUnpacker { bits, acc: 0, acc_bits: 0 }
+    }
+
+    fn push(&mut self, long: i64, mut each: impl FnMut(u32) -> Result<(), SchematicError>) -> Result<(), SchematicError> {
+        let mask = (1u128 << self.bits) - 1;
+        self.acc |= u128::from(long as u64) << self.acc_bits;
+        self.acc_bits += 64;
+        while self.acc_bits >= self.bits {
+            each((self.acc & mask) as u32)?;
+            self.acc >>= self.bits;
+            self.acc_bits -= self.bits;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArrayTarget {
+    LitematicStates,
+    SpongeData,
+    McEditBlocks,
+    McEditData,
+    McEditAdd,
+}
+
+#[derive(Debug, Default)]
+struct SummaryVisitor {
+    limits: IngestLimits,
+    /// 当前容器路径，不含根标签；Sponge v3 的 "Schematic" 外壳不计入
+    path: Vec<String>,
+    root_open: bool,
+    error: Option<SchematicError>,
+
+    name: Option<String>,
+    author: Option<String>,
+    description: Option<String>,
+    version: Option<i32>,
+    data_version: Option<i32>,
+    enclosing_size: Option<Vec3i>,
+    dimensions: [Option<i32>; 3],
+    structure_size: Option<Vec3i>,
+
+    litematic: bool,
+    sponge: bool,
+    structure_blocks: bool,
+
+    regions: Vec<RegionTally>,
+    array: Option<ArrayTarget>,
+    varint: (u32, u32),
+    mcedit_blocks: Vec<u8>,
+    mcedit_data: Vec<u8>,
+    mcedit_add: Option<Vec<u8>>,
+    structure_palette: Option<Vec<BlockState>>,
+}
+
+impl SummaryVisitor {
+    fn new(limits: IngestLimits) -> SummaryVisitor {
+        SummaryVisitor { limits, ..Default::default() }
+    }
+
+    fn at(&self, path: &[&str]) -> bool {
+        self.path.len() == path.len() && self.path.iter().zip(path).all(|(a, b)| b.is_empty() || a == b)
+    }
+
+    /// 单区域格式共用的 "Main" 区域
+    fn set_sponge_palette(&mut self, value: &NbtTag) {
+        let limit = self.limits.max_palette_size;
+        match value.as_compound().map(|palette| sponge_palette(palette, limit)).transpose() {
+            Ok(palette) => self.main_region().palette = palette.flatten(),
+            Err(err) => self.fail(err),
+        }
+    }
+
+    fn main_region(&mut self) -> &mut RegionTally {
+        if self.regions.is_empty() {
+            self.regions.push(RegionTally { name: "Main".to_string(), ..Default::default() });
+        }
+        &mut self.regions[0]
+    }
+
+    fn current_region(&mut self) -> Option<&mut RegionTally> {
+        let name = self.path.get(1)?;
+        self.regions.iter_mut().rev().find(|r| r.name == *name)
+    }
+
+    fn fail(&mut self, err: SchematicError) {
+        self.error.get_or_insert(err);
+    }
+
+    fn enter(&mut self, name: Option<&str>, visit: Visit) -> Visit {
+        if visit == Visit::Enter {
+            self.path.push(name.unwrap_or_default().to_string());
+        }
+        visit
+    }
+
+    /// 区域结束：补齐延后解包的数据，并扣除末尾填充位被计为索引 0 的部分
+    fn finish_region(&mut self) {
+        let limit = self.limits.max_palette_size;
+        let Some(region) = self.current_region() else { return };
+        let packed = std::mem::take(&mut region.packed);
+        if let Some(len) = region.palette.as_ref().map(Vec::len).filter(|_| !packed.is_empty()) {
+            let mut unpacker = BitUnpacker::new(bits_for_palette(len));
+            let mut result = Ok(());
+            for long in packed {
+                result = unpacker.push(long, |index| region.tally(index, limit));
+                if result.is_err() {
+                    break;
+                }
+            }
+            if let Err(err) = result {
+                self.fail(err);
+                return;
+            }
+        }
+        if let Some(volume) = region.volume() {
+            let excess = region.decoded.saturating_sub(volume);
+            if let Some(first) = region.counts.first_mut() {
+                *first = first.saturating_sub(excess);
+            }
+        }
+    }
+
+    fn detected_format(&self) -> SchematicFormat {
+        if self.litematic {
+            SchematicFormat::Litematica
+        } else if self.sponge {
+            SchematicFormat::Sponge
+        } else if !self.mcedit_blocks.is_empty() {
+            SchematicFormat::McEdit
+        } else if self.structure_blocks && self.structure_size.is_some() {
+            SchematicFormat::Structure
+        } else {
+            SchematicFormat::Unknown
+        }
+    }
+
+    fn into_summary(mut self, reader: &NbtStreamReader, mode: MaterialMode) -> Result<StreamSummary, SchematicError> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        let format = self.detected_format();
+        let dimensions = match self.dimensions {
+            [Some(x), Some(y), Some(z)] => Some(Vec3i::new(i32::from(x as u16), i32::from(y as u16), i32::from(z as u16))),
+            _ => None,
+        };
+        match format {
+            // 流式解析只处理大端序，.mcstructure 不会在这里被识别
+            SchematicFormat::Unknown | SchematicFormat::McStructure => {
+                return Err(SchematicError::Invalid("NBT root does not match any schematic format".to_string()))
+            }
+            SchematicFormat::Sponge => self.main_region().size = dimensions,
+            SchematicFormat::Structure => {
+                let size = self.structure_size;
+                let palette = self.structure_palette.take();
+                let region = self.main_region();
+                region.size = size;
+                region.palette = palette;
+            }
+            SchematicFormat::McEdit => self.tally_mcedit(dimensions),
+            SchematicFormat::Litematica => {}
+        }
+
+        let mut totals: HashMap<BlockState, u64> = HashMap::new();
+        let mut regions = Vec::with_capacity(self.regions.len());
+        for region in &self.regions {
+            let palette = region.palette.as_deref().unwrap_or_default();
+            let mut block_count = 0;
+            for (index, count) in region.counts.iter().enumerate().filter(|(_, c)| **c > 0) {
+                let state = palette.get(index).ok_or_else(|| {
+                    SchematicError::Invalid(format!("Region `{}` references palette index {} outside its palette", region.name, index))
+                })?;
+                if !state.is_air() {
+                    block_count += count;
+                }
+                *totals.entry(state.clone()).or_default() += count;
+            }
+            regions.push(StreamRegionSummary {
+                name: region.name.clone(),
+                size: region.size.map(|s| Vec3i::new(s.x.abs(), s.y.abs(), s.z.abs())).unwrap_or_default(),
+                palette_size: palette.len(),
+                block_count,
+            });
+        }
+
+        let size = self.enclosing_size.or(dimensions).or(self.structure_size).unwrap_or_else(|| self.region_bounds());
+        Ok(StreamSummary {
+            format,
+            compression: reader.compression(),
+            name: self.name.unwrap_or_default(),
+            author: self.author.unwrap_or_default(),
+            description: self.description.unwrap_or_default(),
+            data_version: self.data_version,
+            version: self.version,
+            size,
+            total_blocks: regions.iter().map(|r| r.block_count).sum(),
+            regions,
+            materials: MaterialList::from_state_counts(totals, mode),
+            bytes_read: reader.bytes_read(),
+            bytes_decoded: reader.bytes_decoded(),
+        })
+    }
+
+    /// 旧版 MCEdit 的方块 id 分散在 Blocks / Data / AddBlocks 三个数组中，需全部读完后再配对
+    fn tally_mcedit(&mut self, size: Option<Vec3i>) {
+        let volume = size.map_or(0, |s| s.x as usize * s.y as usize * s.z as usize);
+        let volume = volume.min(self.mcedit_blocks.len()).min(self.mcedit_data.len());
+        let mut counts: HashMap<(u16, u8), u64> = HashMap::new();
+        for i in 0..volume {
+            let mut id = u16::from(self.mcedit_blocks[i]);
+            if let Some(add) = self.mcedit_add.as_ref().and_then(|add| add.get(i >> 1)) {
+                let nibble = if i & 1 == 0 { add >> 4 } else { add & 0x0f };
+                id |= u16::from(nibble) << 8;
+            }
+            *counts.entry((id, self.mcedit_data[i] & 0x0f)).or_default() += 1;
+        }
+        let region = self.main_region();
+        region.size = size;
+        let (palette, counts): (Vec<BlockState>, Vec<u64>) =
+            counts.into_iter().map(|((id, meta), count)| (legacy_block_state(id, meta), count)).unzip();
+        region.palette = Some(palette);
+        region.counts = counts;
+    }
+
+    /// Litematica 缺少 EnclosingSize 时由各区域的位置与尺寸计算
+    fn region_bounds(&self) -> Vec3i {
+        let corners = self.regions.iter().filter_map(|r| {
+            let (position, size) = (r.position.unwrap_or_default(), r.size?);
+            let corner = |p: i32, s: i32| if s < 0 { (p + s + 1, p + 1) } else { (p, p + s) };
+            Some([corner(position.x, size.x), corner(position.y, size.y), corner(position.z, size.z)])
+        });
+        let mut bounds: Option<[(i32, i32); 3]> = None;
+        for c in corners {
+            bounds = Some(match bounds {
+                None => c,
+                Some(b) => [0, 1, 2].map(|i| (b[i].0.min(c[i].0), b[i].1.max(c[i].1))),
+            });
+        }
+        bounds.map_or_else(Vec3i::default, |b| Vec3i::new(b[0].1 - b[0].0, b[1].1 - b[1].0, b[2].1 - b[2].0))
+    }
+
+    fn sponge_varints(&mut self, bytes: impl Iterator<Item = i8>) {
+        let limit = self.limits.max_palette_size;
+        for byte in bytes {
+            let byte = byte as u8;
+            let (value, shift) = &mut self.varint;
+            *value |= u32::from(byte & 0x7f) << *shift;
+            if byte & 0x80 != 0 {
+                *shift += 7;
+                if *shift > 28 {
+                    self.fail(SchematicError::Invalid("Block data contains an oversized varint".to_string()));
+                    return;
+                }
+                continue;
+            }
+            let index = std::mem::take(&mut self.varint).0;
+            if let Err(err) = self.main_region().tally(index, limit) {
+                self.fail(err);
+                return;
+            }
+        }
+    }
+
+    fn litematic_states(&mut self, longs: impl Iterator<Item = i64>) {
+        let limit = self.limits.max_palette_size;
+        let Some(region) = self.current_region() else { return };
+        let Some(mut unpacker) = region.unpacker.take() else {
+            region.packed.extend(longs);
+            return;
+        };
+        let mut result = Ok(());
+        for long in longs {
+            result = unpacker.push(long, |index| region.tally(index, limit));
+            if result.is_err() {
+                break;
+            }
+        }
+        region.unpacker = Some(unpacker);
+        if let Err(err) = result {
+            self.fail(err);
+        }
+    }
+}
+
+fn palette_from_list(list: &NbtList) -> Option<Vec<BlockState>> {
+    list.compounds().map(BlockState::from_nbt).collect()
+}
+
+impl NbtVisitor for SummaryVisitor {
+    fn start_compound(&mut self, name: Option<&str>) -> Visit {
+        if !self.root_open {
+            self.root_open = true;
+            return Visit::Enter;
+        }
+        if self.error.is_some() {
+            return Visit::Skip;
+        }
+        let key = name.unwrap_or_default();
+        let visit = if self.at(&[]) {
+            match key {
+                // Sponge v3 外壳：其内容按根标签处理
+                "Schematic" => {
+                    self.sponge = true;
+                    return Visit::Enter;
+                }
+                "Regions" => {
+                    self.litematic = true;
+                    Visit::Enter
+                }
+                "Metadata" | "Blocks" => Visit::Enter,
+                "Palette" => {
+                    self.sponge = true;
+                    Visit::Collect
+                }
+                _ => Visit::Skip,
+            }
+        } else if self.at(&["Metadata"]) {
+            if key == "EnclosingSize" { Visit::Collect } else { Visit::Skip }
+        } else if self.at(&["Regions"]) {
+            self.regions.push(RegionTally { name: key.to_string(), ..Default::default() });
+            Visit::Enter
+        } else if self.at(&["Regions", ""]) {
+            if matches!(key, "Size" | "Position") { Visit::Collect } else { Visit::Skip }
+        } else if self.at(&["Blocks"]) {
+            if key == "Palette" { Visit::Collect } else { Visit::Skip }
+        } else if self.at(&["blocks"]) {
+            Visit::Enter
+        } else {
+            Visit::Skip
+        };
+        self.enter(name, visit)
+    }
+
+    fn end_compound(&mut self) {
+        if self.at(&["Regions", ""]) {
+            self.finish_region();
+        }
+        self.path.pop();
+    }
+
+    fn start_list(&mut self, name: Option<&str>, _element_type: u8, _length: usize) -> Visit {
+        if self.error.is_some() {
+            return Visit::Skip;
+        }
+        let key = name.unwrap_or_default();
+        let visit = if self.at(&[]) {
+            match key {
+                "palette" | "palettes" | "size" => Visit::Collect,
+                "blocks" => {
+                    self.structure_blocks = true;
+                    Visit::Enter
+                }
+                _ => Visit::Skip,
+            }
+        } else if self.at(&["Regions", ""]) && key == "BlockStatePalette" {
+            Visit::Collect
+        } else {
+            Visit::Skip
+        };
+        self.enter(name, visit)
+    }
+
+    fn end_list(&mut self) {
+        self.path.pop();
+    }
+
+    fn start_array(&mut self, name: Option<&str>, element_type: u8, _length: usize) -> Visit {
+        if self.error.is_some() {
+            return Visit::Skip;
+        }
+        let key = name.unwrap_or_default();
+        let target = if self.at(&[]) {
+            match (key, element_type) {
+                ("BlockData", TAG_BYTE_ARRAY) => {
+                    self.sponge = true;
+                    Some(ArrayTarget::SpongeData)
+                }
+                ("Blocks", TAG_BYTE_ARRAY) => Some(ArrayTarget::McEditBlocks),
+                ("Data", TAG_BYTE_ARRAY) => Some(ArrayTarget::McEditData),
+                ("AddBlocks", TAG_BYTE_ARRAY) => Some(ArrayTarget::McEditAdd),
+                _ => None,
+            }
+        } else if self.at(&["Blocks"]) && key == "Data" {
+            Some(ArrayTarget::SpongeData)
+        } else if self.at(&["Regions", ""]) && key == "BlockStates" && element_type == TAG_LONG_ARRAY {
+            let bits = self.current_region().and_then(|r| r.palette.as_ref()).map(|p| bits_for_palette(p.len()));
+            if let Some(region) = self.current_region() {
+                region.unpacker = bits.map(BitUnpacker::new);
+            }
+            Some(ArrayTarget::LitematicStates)
+        } else {
+            None
+        };
+        self.array = target;
+        if target == Some(ArrayTarget::McEditAdd) {
+            self.mcedit_add = Some(Vec::new());
+        }
+        if target.is_some() { Visit::Enter } else { Visit::Skip }
+    }
+
+    fn array_chunk(&mut self, chunk: ArrayChunk<'_>) {
+        if self.error.is_some() {
+            return;
+        }
+        match self.array {
+            Some(ArrayTarget::LitematicStates) => self.litematic_states(chunk.longs()),
+            Some(ArrayTarget::SpongeData) => self.sponge_varints(chunk.bytes()),
+            Some(ArrayTarget::McEditBlocks) => self.mcedit_blocks.extend(chunk.bytes().map(|b| b as u8)),
+            Some(ArrayTarget::McEditData) => self.mcedit_data.extend(chunk.bytes().map(|b| b as u8)),
+            Some(ArrayTarget::McEditAdd) => {
+                if let Some(add) = self.mcedit_add.as_mut() {
+                    add.extend(chunk.bytes().map(|b| b as u8));
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn end_array(&mut self) {
+        self.array = None;
+    }
+
+    fn value(&mut self, name: Option<&str>, value: NbtTag) {
+        let key = name.unwrap_or_default();
+        if self.at(&[]) {
+            match key {
+                "Version" => self.version = value.as_i64().map(|v| v as i32),
+                "MinecraftDataVersion" | "DataVersion" => self.data_version = value.as_i64().map(|v| v as i32),
+                "Width" => self.dimensions[0] = value.as_i64().map(|v| v as i32),
+                "Height" => self.dimensions[1] = value.as_i64().map(|v| v as i32),
+                "Length" => self.dimensions[2] = value.as_i64().map(|v| v as i32),
+                "author" => self.author = value.as_str().map(str::to_string),
+                "size" => self.structure_size = crate::schematic::read_int_triple(Some(&value)),
+                "palette" => self.structure_palette = value.as_list().and_then(palette_from_list),
+                "palettes" => {
+                    // 带随机变体的结构取第一套调色板
+                    let first = value.as_list().and_then(|p| p.iter().next()).and_then(NbtTag::as_list);
+                    self.structure_palette = first.and_then(palette_from_list);
+                }
+                "Palette" => self.set_sponge_palette(&value),
+                _ => {}
+            }
+        } else if self.at(&["Metadata"]) {
+            match key {
+                "Name" => self.name = value.as_str().map(str::to_string),
+                "Author" => self.author = value.as_str().map(str::to_string),
+                "Description" => self.description = value.as_str().map(str::to_string),
+                "EnclosingSize" => self.enclosing_size = value.as_compound().and_then(Vec3i::from_compound),
+                _ => {}
+            }
+        } else if self.at(&["Regions", ""]) {
+            let Some(region) = self.current_region() else { return };
+            match key {
+                "Size" => region.size = value.as_compound().and_then(Vec3i::from_compound),
+                "Position" => region.position = value.as_compound().and_then(Vec3i::from_compound),
+                "BlockStatePalette" => region.palette = value.as_list().and_then(palette_from_list),
+                _ => {}
+            }
+        } else if self.at(&["Blocks"]) && key == "Palette" {
+            self.set_sponge_palette(&value);
+        } else if self.at(&["blocks", ""]) && key == "state" {
+            let limit = self.limits.max_palette_size;
+            let index = value.as_i64().filter(|i| *i >= 0).unwrap_or(i64::MAX);
+            let result = self.main_region().tally(u32::try_from(index).unwrap_or(u32::MAX), limit);
+            if let Err(err) = result {
+                self.fail(err);
+            }
+        }
+    }
+}
+
+/// Sponge 调色板为 方块状态 → id 的映射，转为按 id 排列的列表。
+/// id 决定列表长度，分配前先按调色板上限检查，避免单个超大 id 触发巨量分配
+fn sponge_palette(palette: &NbtCompound, limit: usize) -> Result<Option<Vec<BlockState>>, SchematicError> {
+    let mut entries = Vec::with_capacity(palette.len());
+    for (key, value) in palette.iter() {
+        let Some(id) = value.as_i64().and_then(|id| u64::try_from(id).ok()) else { return Ok(None) };
+        if id >= limit as u64 {
+            let size = usize::try_from(id).unwrap_or(usize::MAX).saturating_add(1);
+            return Err(SchematicError::PaletteTooLarge { region: "Main".to_string(), size, limit });
+        }
+        let Ok(state) = BlockState::parse(key) else { return Ok(None) };
+        entries.push((id as usize, state));
+    }
+    let len = entries.iter().map(|(id, _)| id + 1).max().unwrap_or(0);
+    let mut states = vec![BlockState::air(); len];
+    for (id, state) in entries {
+        states[id] = state;
+    }
+    Ok(Some(states))
+}
+
+/// 分块读取投影并汇总元数据与方块数量。Sponge、结构文件以及调色板先于 BlockStates 出现的 Litematica 区域
+/// 边读边统计，内存占用与方块数据大小无关；MCEdit 的 Blocks / Data / AddBlocks 数组和调色板出现在 BlockStates
+/// 之后的 Litematica 区域须先完整缓存方块数据，内存占用与这些数组的大小成正比
+pub struct SchematicSummaryReader {
+    reader: NbtStreamReader,
+    visitor: SummaryVisitor,
+}
+
+impl SchematicSummaryReader {
+    pub fn new(limits: IngestLimits) -> SchematicSummaryReader {
+        SchematicSummaryReader { reader: NbtStreamReader::new(limits), visitor: SummaryVisitor::new(limits) }
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), SchematicError> {
+        self.reader.push(chunk, &mut self.visitor)?;
+        match self.visitor.error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    pub fn finish(mut self, mode: MaterialMode) -> Result<StreamSummary, SchematicError> {
+        self.reader.finish(&mut self.visitor)?;
+        self.visitor.into_summary(&self.reader, mode)
+    }
+
+    pub fn bytes_read(&self) -> usize {
+        self.reader.bytes_read()
+    }
+
+    pub fn bytes_decoded(&self) -> usize {
+        self.reader.bytes_decoded()
+    }
+}
+
+/// 供 JS 端逐块喂入 ReadableStream 数据
+#[wasm_bindgen]
+pub struct StreamingSchematicReader {
+    inner: Option<SchematicSummaryReader>,
+}
+
+impl Default for StreamingSchematicReader {
+    fn default() -> Self {
+        StreamingSchematicReader::new()
+    }
+}
+
+#[wasm_bindgen]
+impl StreamingSchematicReader {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> StreamingSchematicReader {
+        StreamingSchematicReader { inner: Some(SchematicSummaryReader::new(IngestLimits::current())) }
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), NeptuniumError> {
+        Ok(self.reader()?.push(chunk)?)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bytes_read(&self) -> usize {
+        self.inner.as_ref().map_or(0, SchematicSummaryReader::bytes_read)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bytes_decoded(&self) -> usize {
+        self.inner.as_ref().map_or(0, SchematicSummaryReader::bytes_decoded)
+    }
+
+    /// 输入结束，返回摘要 JSON；之后读取器不可再使用
+    pub fn finish(&mut self, as_items: bool) -> Result<String, NeptuniumError> {
+        self.reader()?;
+        let reader = self.inner.take().expect("reader checked above");
+        let mode = if as_items { MaterialMode::Items } else { MaterialMode::BlockStates };
+        let summary = reader.finish(mode)?;
+        Ok(serde_json::to_string(&summary).unwrap_or_default())
+    }
+}
+
+impl StreamingSchematicReader {
+    fn reader(&mut self) -> Result<&mut SchematicSummaryReader, NeptuniumError> {
+        self.inner
+            .as_mut()
+            .ok_or_else(|| NeptuniumError::new("Streaming reader already finished".to_string(), "STREAM_FINISHED".to_string()))
+    }
+}
+
+/// 对已在内存中的文件使用同一流式摘要，避免构建完整的标签树
+#[wasm_bindgen]
+pub fn summarize_schematic(data: &[u8], as_items: bool) -> Result<String, NeptuniumError> {
+    let mut reader = StreamingSchematicReader::new();
+    reader.push(data)?;
+    reader.finish(as_items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExportFormat, Region, Schematic};
+
+    /// 4×3×5 的区域，石头地面上摆一些玻璃和一个箱子
+    fn sample() -> Schematic {
+        let mut region = Region::new("Main", Vec3i::new(0, 0, 0), Vec3i::new(4, 3, 5)).unwrap();
+        for i in 0..20 {
+            region.set_block(i % 4, 0, i / 4, &BlockState::new("minecraft:stone"));
+        }
+        region.set_block(1, 1, 1, &BlockState::new("minecraft:glass"));
+        region.set_block(2, 1, 3, &BlockState::new("minecraft:glass"));
+        region.set_block(3, 2, 4, &BlockState::new("minecraft:gold_block"));
+        let mut schematic = Schematic::new("Sample", 3700, SchematicFormat::Litematica);
+        schematic.author = "Builder".to_string();
+        schematic.regions.push(region);
+        schematic
+    }
+
+    fn summarize(data: &[u8], chunk: usize) -> StreamSummary {
+        let mut reader = SchematicSummaryReader::new(IngestLimits::DEFAULT);
+        for part in data.chunks(chunk) {
+            reader.push(part).unwrap();
+        }
+        reader.finish(MaterialMode::BlockStates).unwrap()
+    }
+
+    #[test]
+    fn streamed_counts_match_the_full_reader_for_every_java_format() {
+        let schematic = sample();
+        for target in [
+            ExportFormat::Litematica,
+            ExportFormat::SpongeV2,
+            ExportFormat::SpongeV3,
+            ExportFormat::McEdit,
+            ExportFormat::Structure,
+        ] {
+            let (data, _) = crate::write_schematic(&schematic, target).unwrap();
+            let summary = summarize(&data, 7);
+            assert_eq!(summary.format, target.schematic_format(), "{:?}", target);
+            assert_eq!(summary.size, Vec3i::new(4, 3, 5), "{:?}", target);
+            assert_eq!(summary.total_blocks, 23, "{:?}", target);
+            assert_eq!(summary.bytes_read, data.len(), "{:?}", target);
+            let read = Schematic::from_bytes(&data).unwrap();
+            let expected = MaterialList::from_schematic(&read, MaterialMode::BlockStates);
+            assert_eq!(summary.materials.entries, expected.entries, "{:?}", target);
+        }
+    }
+
+    #[test]
+    fn chunk_boundaries_do_not_change_the_summary() {
+        let (data, _) = crate::write_schematic(&sample(), ExportFormat::Litematica).unwrap();
+        let whole = summarize(&data, data.len());
+        for chunk in [1, 2, 3, 64] {
+            let split = summarize(&data, chunk);
+            assert_eq!(serde_json::to_value(&split).unwrap(), serde_json::to_value(&whole).unwrap(), "{}", chunk);
+        }
+        assert_eq!(whole.author, "Builder");
+        assert_eq!(whole.data_version, Some(3700));
+        assert_eq!(whole.regions[0].block_count, 23);
+    }
+
+    #[test]
+    fn limits_apply_while_streaming() {
+        let (data, _) = crate::write_schematic(&sample(), ExportFormat::SpongeV2).unwrap();
+        let limits = IngestLimits { max_decompressed_bytes: 64, ..IngestLimits::DEFAULT };
+        let mut reader = SchematicSummaryReader::new(limits);
+        let result = data.chunks(16).try_for_each(|part| reader.push(part));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn declared_volumes_that_overflow_are_unknown() {
+        let tally = |size| RegionTally { size: Some(size), ..RegionTally::default() };
+        assert_eq!(tally(Vec3i::new(4, 3, -5)).volume(), Some(60));
+        assert_eq!(tally(Vec3i::new(i32::MIN, i32::MIN, i32::MIN)).volume(), None);
+        assert_eq!(RegionTally::default().volume(), None);
+    }
+}
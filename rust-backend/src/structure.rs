@@ -0,0 +1,178 @@
+use crate::schematic::{read_double_triple, read_int_triple};
+use crate::{
+    checked_volume, BlockEntity, BlockState, ConversionWarning, Entity, NbtCompound, NbtCompression, NbtDocument,
+    NbtList, NbtTag, Region, Schematic, SchematicError, SchematicFormat, STRUCTURE_VOID, TAG_COMPOUND, TAG_DOUBLE,
+    TAG_INT, Vec3i,
+};
+use std::collections::HashMap;
+
+// 原版结构方块 (.nbt / .structure) 读写
+
+/// 结构方块可保存/加载的单轴最大尺寸
+pub const STRUCTURE_BLOCK_LIMIT: i32 = 48;
+
+pub fn read_structure(root: &NbtCompound) -> Result<Schematic, SchematicError> {
+    let size = read_int_triple(root.get("size")).ok_or_else(|| SchematicError::MissingField("size".to_string()))?;
+    let volume = checked_volume(size)?;
+
+    // 带随机变体的结构（如沉船）使用 palettes，取第一套调色板
+    let palette_list = root
+        .get_list("palette")
+        .or_else(|| root.get_list("palettes").and_then(|p| p.iter().next()).and_then(NbtTag::as_list))
+        .ok_or_else(|| SchematicError::MissingField("palette".to_string()))?;
+    let mut palette: Vec<BlockState> = palette_list
+        .compounds()
+        .map(BlockState::from_nbt)
+        .collect::<Option<_>>()
+        .ok_or_else(|| SchematicError::Invalid("Malformed palette entry".to_string()))?;
+
+    // 结构文件不记录的位置即为结构空位
+    let void_index = match palette.iter().position(|s| s.name == STRUCTURE_VOID) {
+        Some(index) => index,
+        None => {
+            palette.push(BlockState::new(STRUCTURE_VOID));
+            palette.len() - 1
+        }
+    } as u32;
+
+    let mut region = Region {
+        name: "Main".to_string(),
+        origin: Default::default(),
+        size,
+        palette,
+        blocks: vec![void_index; volume],
+        block_entities: Vec::new(),
+        entities: Vec::new(),
+    };
+
+    let blocks = root
+        .get_list("blocks")
+        .ok_or_else(|| SchematicError::MissingField("blocks".to_string()))?;
+    for block in blocks.compounds() {
+        let pos = read_int_triple(block.get("pos"))
+            .filter(|p| region.contains(p.x, p.y, p.z))
+            .ok_or_else(|| SchematicError::Invalid("Block position is outside the structure".to_string()))?;
+        let state = block
+            .get_i64("state")
+            .filter(|s| (0..region.palette.len() as i64).contains(s))
+            .ok_or_else(|| SchematicError::Invalid("Block references an unknown palette state".to_string()))?;
+        let index = region.index(pos.x, pos.y, pos.z);
+        region.blocks[index] = state as u32;
+
+        if let Some(nbt) = block.get_compound("nbt") {
+            let mut nbt = nbt.clone();
+            let id = nbt.remove("id").and_then(|t| t.as_str().map(str::to_string)).unwrap_or_default();
+            region.block_entities.push(BlockEntity { pos, id, nbt });
+        }
+    }
+
+    if let Some(entities) = root.get_list("entities") {
+        for entity in entities.compounds() {
+            let Some(pos) = read_double_triple(entity.get("pos")) else { continue };
+            let mut nbt = entity.get_compound("nbt").cloned().unwrap_or_default();
+            let id = nbt.remove("id").and_then(|t| t.as_str().map(str::to_string)).unwrap_or_default();
+            nbt.remove("Pos");
+            region.entities.push(Entity { pos, id, nbt });
+        }
+    }
+
+    let mut schematic = Schematic::new("", root.get_i32("DataVersion").unwrap_or(0), SchematicFormat::Structure);
+    schematic.author = root.get_str("author").unwrap_or_default().to_string();
+    schematic.regions.push(region);
+    Ok(schematic)
+}
+
+/// 写出原版结构格式；区域之间的空隙写为结构空位（即不记录）
+pub fn write_structure(schematic: &Schematic, warnings: &mut Vec<ConversionWarning>) -> Result<NbtDocument, SchematicError> {
+    let region = crate::single_region(schematic, &BlockState::new(STRUCTURE_VOID), warnings)?;
+    if region.size.x > STRUCTURE_BLOCK_LIMIT || region.size.y > STRUCTURE_BLOCK_LIMIT || region.size.z > STRUCTURE_BLOCK_LIMIT {
+        warnings.push(ConversionWarning::new(
+            "exceeds_structure_block_limit",
+            format!(
+                "Size {}x{}x{} exceeds the structure block limit of {} per axis; load it with /place template instead",
+                region.size.x, region.size.y, region.size.z, STRUCTURE_BLOCK_LIMIT
+            ),
+        ));
+    }
+
+    // 结构空位不写入调色板，其余条目按原顺序重新编号
+    let mut palette = NbtList::new(TAG_COMPOUND);
+    let remap: Vec<Option<i32>> = region
+        .palette
+        .iter()
+        .map(|state| {
+            (state.name != STRUCTURE_VOID).then(|| {
+                palette.values.push(NbtTag::Compound(state.to_nbt()));
+                (palette.values.len() - 1) as i32
+            })
+        })
+        .collect();
+
+    let int_list = |values: [i32; 3]| {
+        NbtTag::List(NbtList { element_type: TAG_INT, values: values.iter().map(|v| NbtTag::Int(*v)).collect() })
+    };
+
+    let block_entities: HashMap<Vec3i, &BlockEntity> = region.block_entities.iter().map(|be| (be.pos, be)).collect();
+    let mut blocks = NbtList::new(TAG_COMPOUND);
+    for (index, palette_index) in region.blocks.iter().enumerate() {
+        let Some(state) = remap[*palette_index as usize] else { continue };
+        let pos = region.position(index);
+        let mut block = NbtCompound::new();
+        block.insert("state", NbtTag::Int(state));
+        block.insert("pos", int_list([pos.x, pos.y, pos.z]));
+        if let Some(block_entity) = block_entities.get(&pos) {
+            let mut nbt = NbtCompound::new();
+            nbt.insert("id", NbtTag::String(block_entity.id.clone()));
+            for (key, value) in block_entity.nbt.iter() {
+                nbt.insert(key, value.clone());
+            }
+            block.insert("nbt", NbtTag::Compound(nbt));
+        }
+        blocks.values.push(NbtTag::Compound(block));
+    }
+
+    let orphaned = region
+        .block_entities
+        .iter()
+        .filter(|be| {
+            !region.contains(be.pos.x, be.pos.y, be.pos.z) || region.block(be.pos.x, be.pos.y, be.pos.z).name == STRUCTURE_VOID
+        })
+        .count();
+    if orphaned > 0 {
+        warnings.push(ConversionWarning::new(
+            "block_entity_dropped",
+            format!("{} block entities sit on structure void or out-of-bounds positions and were dropped", orphaned),
+        ));
+    }
+
+    let mut entities = NbtList::new(TAG_COMPOUND);
+    for entity in &region.entities {
+        let mut nbt = NbtCompound::new();
+        nbt.insert("id", NbtTag::String(entity.id.clone()));
+        for (key, value) in entity.nbt.iter() {
+            nbt.insert(key, value.clone());
+        }
+        let mut tag = NbtCompound::new();
+        tag.insert(
+            "pos",
+            NbtTag::List(NbtList { element_type: TAG_DOUBLE, values: entity.pos.iter().map(|v| NbtTag::Double(*v)).collect() }),
+        );
+        tag.insert(
+            "blockPos",
+            int_list([entity.pos[0].floor() as i32, entity.pos[1].floor() as i32, entity.pos[2].floor() as i32]),
+        );
+        tag.insert("nbt", NbtTag::Compound(nbt));
+        entities.values.push(NbtTag::Compound(tag));
+    }
+
+    let mut root = NbtCompound::new();
+    root.insert("DataVersion", NbtTag::Int(schematic.data_version));
+    root.insert("size", int_list([region.size.x, region.size.y, region.size.z]));
+    root.insert("palette", NbtTag::List(palette));
+    root.insert("blocks", NbtTag::List(blocks));
+    root.insert("entities", NbtTag::List(entities));
+    if !schematic.author.is_empty() {
+        root.insert("author", NbtTag::String(schematic.author.clone()));
+    }
+    Ok(NbtDocument::new("", root, NbtCompression::Gzip))
+}
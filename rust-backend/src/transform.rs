@@ -0,0 +1,504 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::{
+    BlockEntity, BlockState, ConversionOutput, Entity, NbtTag, NeptuniumError, Region, Schematic,
+    SchematicError, Vec3i,
+};
+
+// 投影的旋转、镜像与平移：改写方块坐标、方向相关的方块状态属性，以及方块实体与实体的位置和朝向
+
+/// 绕 Y 轴旋转，俯视时的顺时针方向（北 → 东）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rotation {
+    #[default]
+    #[serde(alias = "0")]
+    None,
+    #[serde(alias = "90", alias = "cw")]
+    Clockwise90,
+    #[serde(alias = "180")]
+    Clockwise180,
+    #[serde(alias = "270", alias = "ccw")]
+    CounterClockwise90,
+}
+
+impl Rotation {
+    /// 接受 0 / 90 / 180 / 270 及其负值
+    pub fn from_degrees(degrees: i32) -> Option<Rotation> {
+        match degrees.rem_euclid(360) {
+            0 => Some(Rotation::None),
+            90 => Some(Rotation::Clockwise90),
+            180 => Some(Rotation::Clockwise180),
+            270 => Some(Rotation::CounterClockwise90),
+            _ => None,
+        }
+    }
+
+    /// 顺时针旋转的 90° 次数
+    fn quarter_turns(&self) -> i32 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Clockwise90 => 1,
+            Rotation::Clockwise180 => 2,
+            Rotation::CounterClockwise90 => 3,
+        }
+    }
+}
+
+/// 沿某个轴翻转：`X` 交换东西，`Z` 交换南北
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mirror {
+    #[default]
+    None,
+    #[serde(alias = "front_back")]
+    X,
+    #[serde(alias = "left_right")]
+    Z,
+}
+
+/// 先镜像、再旋转，最后整体平移；旋转后投影包围盒的最小角保持不变
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Transform {
+    pub rotation: Rotation,
+    pub mirror: Mirror,
+    pub offset: Vec3i,
+}
+
+impl Transform {
+    pub fn from_json(json: &str) -> Result<Transform, NeptuniumError> {
+        serde_json::from_str(json)
+            .map_err(|e| NeptuniumError::new(format!("Invalid transform: {}", e), "INVALID_TRANSFORM".to_string()))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.rotation == Rotation::None && self.mirror == Mirror::None && self.offset == Vec3i::default()
+    }
+
+    /// 是否交换 X 与 Z 轴
+    fn swaps_axes(&self) -> bool {
+        self.rotation.quarter_turns() % 2 == 1
+    }
+
+    /// 水平坐标的连续变换（不含平移），方块格子取 `point(x + 0.5, z + 0.5)` 的下取整
+    fn point(&self, x: f64, z: f64) -> (f64, f64) {
+        let (mut x, mut z) = match self.mirror {
+            Mirror::None => (x, z),
+            Mirror::X => (-x, z),
+            Mirror::Z => (x, -z),
+        };
+        for _ in 0..self.rotation.quarter_turns() {
+            (x, z) = (-z, x);
+        }
+        (x, z)
+    }
+
+    fn cell(&self, pos: Vec3i) -> Vec3i {
+        let (x, z) = self.point(f64::from(pos.x) + 0.5, f64::from(pos.z) + 0.5);
+        Vec3i::new(x.floor() as i32, pos.y, z.floor() as i32)
+    }
+
+    /// 水平方向名称的变换，非水平方向返回 None
+    fn direction(&self, direction: &str) -> Option<&'static str> {
+        const HORIZONTAL: [&str; 4] = ["north", "east", "south", "west"];
+        let mut index = HORIZONTAL.iter().position(|d| *d == direction)?;
+        index = match (self.mirror, index) {
+            (Mirror::X, 1) | (Mirror::X, 3) => 4 - index,
+            (Mirror::Z, 0) | (Mirror::Z, 2) => 2 - index,
+            _ => index,
+        };
+        Some(HORIZONTAL[(index + self.rotation.quarter_turns() as usize) % 4])
+    }
+
+    fn direction_or_same<'a>(&self, direction: &'a str) -> &'a str {
+        self.direction(direction).unwrap_or(direction)
+    }
+
+    /// 16 向旋转值（告示牌、旗帜、头颅）：0 为南，按顺时针递增
+    fn rotation_16(&self, value: i32) -> i32 {
+        let mirrored = match self.mirror {
+            Mirror::None => value,
+            Mirror::X => 16 - value,
+            Mirror::Z => 8 - value,
+        };
+        (mirrored + self.rotation.quarter_turns() * 4).rem_euclid(16)
+    }
+
+    /// 实体偏航角：0 为南，90 为西
+    fn yaw(&self, yaw: f32) -> f32 {
+        let mirrored = match self.mirror {
+            Mirror::None => yaw,
+            Mirror::X => -yaw,
+            Mirror::Z => 180.0 - yaw,
+        };
+        (mirrored + 90.0 * self.rotation.quarter_turns() as f32).rem_euclid(360.0)
+    }
+}
+
+fn swap_handedness(value: &str) -> String {
+    if let Some(prefix) = value.strip_suffix("left") {
+        format!("{}right", prefix)
+    } else if let Some(prefix) = value.strip_suffix("right") {
+        format!("{}left", prefix)
+    } else {
+        value.to_string()
+    }
+}
+
+/// 铁轨形状：`north_south`、`ascending_east`、`south_west` 等，南北分量在前
+fn rail_shape(value: &str, transform: &Transform) -> String {
+    if let Some(direction) = value.strip_prefix("ascending_") {
+        return format!("ascending_{}", transform.direction_or_same(direction));
+    }
+    let Some((a, b)) = value.split_once('_') else {
+        return value.to_string();
+    };
+    let (a, b) = (transform.direction_or_same(a), transform.direction_or_same(b));
+    let is_ns = |d: &str| d == "north" || d == "south";
+    match (is_ns(a), is_ns(b)) {
+        (true, true) => "north_south".to_string(),
+        (false, false) => "east_west".to_string(),
+        (true, false) => format!("{}_{}", a, b),
+        (false, true) => format!("{}_{}", b, a),
+    }
+}
+
+/// 改写与方向相关的方块状态属性
+pub fn transform_block_state(state: &BlockState, transform: &Transform) -> BlockState {
+    if state.properties.is_empty() || (transform.rotation == Rotation::None && transform.mirror == Mirror::None) {
+        return state.clone();
+    }
+    let mirrored = transform.mirror != Mirror::None;
+    let mut properties = BTreeMap::new();
+    for (key, value) in &state.properties {
+        // 栅栏、墙、玻璃板、红石线、藤蔓等以方向命名的连接属性
+        let key = transform.direction(key).map(str::to_string).unwrap_or_else(|| key.clone());
+        let value = match key.as_str() {
+            "facing" => transform.direction_or_same(value).to_string(),
+            "axis" if transform.swaps_axes() => match value.as_str() {
+                "x" => "z".to_string(),
+                "z" => "x".to_string(),
+                _ => value.clone(),
+            },
+            "rotation" => match value.parse::<i32>() {
+                Ok(rotation) => transform.rotation_16(rotation).to_string(),
+                Err(_) => value.clone(),
+            },
+            "shape" if value.contains("left") || value.contains("right") => {
+                if mirrored { swap_handedness(value) } else { value.clone() }
+            }
+            "shape" => rail_shape(value, transform),
+            // 门的铰链、大箱子的左右半边
+            "hinge" | "type" if mirrored => swap_handedness(value),
+            // 拼图方块、合成器：`<正面>_<顶面>`
+            "orientation" => value
+                .split('_')
+                .map(|d| transform.direction_or_same(d))
+                .collect::<Vec<_>>()
+                .join("_"),
+            _ => value.clone(),
+        };
+        properties.insert(key, value);
+    }
+    BlockState { name: state.name.clone(), properties }
+}
+
+/// 变换单个区域，返回的区域原点尚未做整体对齐与平移
+fn transform_region(region: &Region, transform: &Transform) -> Result<Region, SchematicError> {
+    let last = Vec3i::new(
+        region.origin.x + region.size.x - 1,
+        region.origin.y + region.size.y - 1,
+        region.origin.z + region.size.z - 1,
+    );
+    let (a, b) = (transform.cell(region.origin), transform.cell(last));
+    let origin = Vec3i::new(a.x.min(b.x), region.origin.y, a.z.min(b.z));
+    let size = if transform.swaps_axes() {
+        Vec3i::new(region.size.z, region.size.y, region.size.x)
+    } else {
+        region.size
+    };
+    let relative = |pos: Vec3i| {
+        let cell = transform.cell(Vec3i::new(region.origin.x + pos.x, region.origin.y + pos.y, region.origin.z + pos.z));
+        Vec3i::new(cell.x - origin.x, cell.y - origin.y, cell.z - origin.z)
+    };
+
+    let mut output = Region::new(region.name.clone(), origin, size)?;
+    output.palette = region.palette.iter().map(|state| transform_block_state(state, transform)).collect();
+    for (index, palette_index) in region.blocks.iter().enumerate() {
+        let pos = relative(region.position(index));
+        let target = output.index(pos.x, pos.y, pos.z);
+        output.blocks[target] = *palette_index;
+    }
+    output.compact_palette();
+
+    output.block_entities = region
+        .block_entities
+        .iter()
+        .map(|block_entity| BlockEntity { pos: relative(block_entity.pos), ..block_entity.clone() })
+        .collect();
+    output.entities = region
+        .entities
+        .iter()
+        .map(|entity| {
+            let (x, z) = transform.point(
+                f64::from(region.origin.x) + entity.pos[0],
+                f64::from(region.origin.z) + entity.pos[2],
+            );
+            let mut entity = Entity {
+                pos: [x - f64::from(origin.x), entity.pos[1], z - f64::from(origin.z)],
+                ..entity.clone()
+            };
+            transform_entity_nbt(&mut entity, transform);
+            map_entity_tile(&mut entity, relative);
+            entity
+        })
+        .collect();
+    Ok(output)
+}
+
+/// 实体朝向：`Rotation` 的偏航角，以及物品展示框 / 画的 `Facing`
+fn transform_entity_nbt(entity: &mut Entity, transform: &Transform) {
+    if let Some(NbtTag::List(rotation)) = entity.nbt.get("Rotation") {
+        let mut values: Vec<NbtTag> = rotation.iter().cloned().collect();
+        if let Some(NbtTag::Float(yaw)) = values.first_mut() {
+            *yaw = transform.yaw(*yaw);
+        }
+        if let Ok(list) = crate::NbtList::from_values(values) {
+            entity.nbt.insert("Rotation", NbtTag::List(list));
+        }
+    }
+
+    let painting = entity.id.ends_with("painting");
+    // 物品展示框使用 3D 方向序号（下 上 北 南 西 东），画使用 2D 序号（南 西 北 东）
+    let names: &[&str] = if painting {
+        &["south", "west", "north", "east"]
+    } else {
+        &["down", "up", "north", "south", "west", "east"]
+    };
+    for key in ["Facing", "facing"] {
+        let Some(index) = entity.nbt.get(key).and_then(NbtTag::as_i64) else {
+            continue;
+        };
+        let Some(name) = names.get(index as usize) else {
+            continue;
+        };
+        let rotated = transform.direction_or_same(name);
+        if let Some(new_index) = names.iter().position(|n| *n == rotated) {
+            entity.nbt.insert(key, NbtTag::Byte(new_index as i8));
+        }
+    }
+}
+
+/// 悬挂实体（物品展示框、画、拴绳结）依附的方块 `TileX/TileY/TileZ`，与方块实体位置同一坐标系，
+/// 按相同的方块映射变换；裁剪、分块平移区域时同样使用
+pub(crate) fn map_entity_tile(entity: &mut Entity, relative: impl Fn(Vec3i) -> Vec3i) {
+    let tile = |key: &str| entity.nbt.get(key).and_then(NbtTag::as_i64).map(|v| v as i32);
+    let (Some(x), Some(y), Some(z)) = (tile("TileX"), tile("TileY"), tile("TileZ")) else {
+        return;
+    };
+    let pos = relative(Vec3i::new(x, y, z));
+    entity.nbt.insert("TileX", NbtTag::Int(pos.x));
+    entity.nbt.insert("TileY", NbtTag::Int(pos.y));
+    entity.nbt.insert("TileZ", NbtTag::Int(pos.z));
+}
+
+/// 原地变换整个投影
+pub fn transform_schematic(schematic: &mut Schematic, transform: &Transform) -> Result<(), SchematicError> {
+    if transform.is_identity() || schematic.regions.is_empty() {
+        return Ok(());
+    }
+    let (min, _) = schematic.bounds()?;
+    let mut regions = schematic
+        .regions
+        .iter()
+        .map(|region| transform_region(region, transform))
+        .collect::<Result<Vec<Region>, SchematicError>>()?;
+
+    // 保持包围盒最小角不变，再应用平移
+    let new_min = Vec3i::new(
+        regions.iter().map(|r| r.origin.x).min().unwrap_or_default(),
+        min.y,
+        regions.iter().map(|r| r.origin.z).min().unwrap_or_default(),
+    );
+    let shift = Vec3i::new(
+        min.x - new_min.x + transform.offset.x,
+        transform.offset.y,
+        min.z - new_min.z + transform.offset.z,
+    );
+    for region in regions.iter_mut() {
+        region.origin = Vec3i::new(region.origin.x + shift.x, region.origin.y + shift.y, region.origin.z + shift.z);
+    }
+    schematic.regions = regions;
+    Ok(())
+}
+
+fn write_transformed(data: &[u8], transform: &Transform, target_format: &str) -> Result<ConversionOutput, NeptuniumError> {
+    let target = crate::resolve_export_format(data, target_format)?;
+    let mut schematic = Schematic::from_bytes(data)?;
+    transform_schematic(&mut schematic, transform)?;
+    let (bytes, warnings) = crate::write_schematic(&schematic, target)?;
+    Ok(ConversionOutput::new(bytes, target, warnings))
+}
+
+// 导出的函数
+/// 变换投影并写出；transform_json 形如 `{"rotation":"clockwise_90","mirror":"x","offset":{"x":0,"y":0,"z":0}}`，
+/// target_format 留空时写回源格式
+#[wasm_bindgen]
+pub fn transform_schematic_file(data: &[u8], transform_json: &str, target_format: &str) -> Result<ConversionOutput, NeptuniumError> {
+    write_transformed(data, &Transform::from_json(transform_json)?, target_format)
+}
+
+/// 按角度旋转并写回源格式，degrees 为 90 的倍数（顺时针，可为负）
+#[wasm_bindgen]
+pub fn rotate_schematic(data: &[u8], degrees: i32) -> Result<ConversionOutput, NeptuniumError> {
+    let rotation = Rotation::from_degrees(degrees).ok_or_else(|| {
+        NeptuniumError::new(format!("Rotation must be a multiple of 90 degrees: {}", degrees), "INVALID_TRANSFORM".to_string())
+    })?;
+    write_transformed(data, &Transform { rotation, ..Transform::default() }, "")
+}
+
+/// 沿 X（交换东西）或 Z（交换南北）轴镜像并写回源格式
+#[wasm_bindgen]
+pub fn mirror_schematic(data: &[u8], axis: &str) -> Result<ConversionOutput, NeptuniumError> {
+    let mirror = match axis.to_lowercase().as_str() {
+        "x" => Mirror::X,
+        "z" => Mirror::Z,
+        _ => {
+            return Err(NeptuniumError::new(format!("Mirror axis must be x or z: {}", axis), "INVALID_TRANSFORM".to_string()))
+        }
+    };
+    write_transformed(data, &Transform { mirror, ..Transform::default() }, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NbtCompound, NbtList, SchematicFormat};
+
+    fn rotate(rotation: Rotation) -> Transform {
+        Transform { rotation, ..Transform::default() }
+    }
+
+    fn mirror(mirror: Mirror) -> Transform {
+        Transform { mirror, ..Transform::default() }
+    }
+
+    fn state(name: &str, properties: &[(&str, &str)]) -> BlockState {
+        properties.iter().fold(BlockState::new(name), |state, (k, v)| state.with_property(*k, *v))
+    }
+
+    /// 3×2×2 的区域，放在 (10, 0, 20)；西北角是朝北的楼梯和箱子，另有一个朝北挂着的物品展示框
+    fn sample() -> Schematic {
+        let mut region = Region::new("Main", Vec3i::new(10, 0, 20), Vec3i::new(3, 2, 2)).unwrap();
+        region.set_block(0, 0, 0, &state("minecraft:oak_stairs", &[("facing", "north"), ("shape", "inner_left")]));
+        region.set_block(2, 1, 1, &state("minecraft:oak_log", &[("axis", "x")]));
+        region.block_entities.push(BlockEntity {
+            pos: Vec3i::new(0, 0, 0),
+            id: "minecraft:chest".to_string(),
+            nbt: NbtCompound::new(),
+        });
+        let mut nbt = NbtCompound::new();
+        nbt.insert("Facing", NbtTag::Byte(2));
+        let rotation = NbtList::from_values(vec![NbtTag::Float(0.0), NbtTag::Float(0.0)]).unwrap();
+        nbt.insert("Rotation", NbtTag::List(rotation));
+        nbt.insert("TileX", NbtTag::Int(1));
+        nbt.insert("TileY", NbtTag::Int(1));
+        nbt.insert("TileZ", NbtTag::Int(0));
+        region.entities.push(Entity { id: "minecraft:item_frame".to_string(), pos: [1.5, 1.5, 0.25], nbt });
+        let mut schematic = Schematic::new("Sample", 3700, SchematicFormat::Litematica);
+        schematic.regions.push(region);
+        schematic
+    }
+
+    #[test]
+    fn degrees_map_to_rotations() {
+        assert_eq!(Rotation::from_degrees(90), Some(Rotation::Clockwise90));
+        assert_eq!(Rotation::from_degrees(-90), Some(Rotation::CounterClockwise90));
+        assert_eq!(Rotation::from_degrees(540), Some(Rotation::Clockwise180));
+        assert_eq!(Rotation::from_degrees(45), None);
+    }
+
+    #[test]
+    fn block_state_properties_follow_rotation_and_mirroring() {
+        let cw = rotate(Rotation::Clockwise90);
+        let stairs = state("minecraft:oak_stairs", &[("facing", "north"), ("shape", "inner_left")]);
+        assert_eq!(
+            transform_block_state(&stairs, &cw),
+            state("minecraft:oak_stairs", &[("facing", "east"), ("shape", "inner_left")])
+        );
+        assert_eq!(
+            transform_block_state(&stairs, &mirror(Mirror::Z)),
+            state("minecraft:oak_stairs", &[("facing", "south"), ("shape", "inner_right")])
+        );
+        let fence = state("minecraft:oak_fence", &[("north", "true"), ("east", "false")]);
+        assert_eq!(
+            transform_block_state(&fence, &cw),
+            state("minecraft:oak_fence", &[("east", "true"), ("south", "false")])
+        );
+        let rail = state("minecraft:rail", &[("shape", "north_east")]);
+        assert_eq!(transform_block_state(&rail, &cw).property("shape"), Some("south_east"));
+        assert_eq!(transform_block_state(&rail, &mirror(Mirror::X)).property("shape"), Some("north_west"));
+        let log = state("minecraft:oak_log", &[("axis", "x")]);
+        assert_eq!(transform_block_state(&log, &cw).property("axis"), Some("z"));
+        let sign = state("minecraft:oak_sign", &[("rotation", "0")]);
+        assert_eq!(transform_block_state(&sign, &cw).property("rotation"), Some("4"));
+        let west = state("minecraft:oak_sign", &[("rotation", "4")]);
+        assert_eq!(transform_block_state(&west, &mirror(Mirror::X)).property("rotation"), Some("12"));
+        let door = state("minecraft:oak_door", &[("facing", "east"), ("hinge", "left")]);
+        assert_eq!(
+            transform_block_state(&door, &mirror(Mirror::X)),
+            state("minecraft:oak_door", &[("facing", "west"), ("hinge", "right")])
+        );
+    }
+
+    #[test]
+    fn clockwise_rotation_keeps_the_minimum_corner_and_moves_contents() {
+        let mut schematic = sample();
+        transform_schematic(&mut schematic, &rotate(Rotation::Clockwise90)).unwrap();
+        let region = &schematic.regions[0];
+        assert_eq!((region.origin, region.size), (Vec3i::new(10, 0, 20), Vec3i::new(2, 2, 3)));
+        // 西北角转到东北角
+        assert_eq!(region.block(1, 0, 0).property("facing"), Some("east"));
+        assert_eq!(region.block(0, 1, 2).property("axis"), Some("z"));
+        assert_eq!(region.block_entities[0].pos, Vec3i::new(1, 0, 0));
+
+        let frame = &region.entities[0];
+        assert_eq!(frame.pos, [1.75, 1.5, 1.5]);
+        assert_eq!(frame.nbt.get("Facing"), Some(&NbtTag::Byte(5)));
+        let tile = |key| frame.nbt.get(key).and_then(NbtTag::as_i64).unwrap();
+        assert_eq!((tile("TileX"), tile("TileY"), tile("TileZ")), (1, 1, 1));
+        let yaw = frame.nbt.get_list("Rotation").and_then(|r| r.iter().next()).and_then(NbtTag::as_f64);
+        assert_eq!(yaw, Some(90.0));
+    }
+
+    #[test]
+    fn full_turns_and_double_mirrors_are_identities() {
+        let original = sample();
+        let mut rotated = original.clone();
+        for _ in 0..4 {
+            transform_schematic(&mut rotated, &rotate(Rotation::Clockwise90)).unwrap();
+        }
+        assert_eq!(rotated, original);
+
+        for axis in [Mirror::X, Mirror::Z] {
+            let mut mirrored = original.clone();
+            transform_schematic(&mut mirrored, &mirror(axis)).unwrap();
+            assert_ne!(mirrored, original, "{:?}", axis);
+            transform_schematic(&mut mirrored, &mirror(axis)).unwrap();
+            assert_eq!(mirrored, original, "{:?}", axis);
+        }
+    }
+
+    #[test]
+    fn offsets_translate_after_rotating() {
+        let mut schematic = sample();
+        let transform =
+            Transform { rotation: Rotation::Clockwise180, offset: Vec3i::new(-10, 5, 0), ..Transform::default() };
+        transform_schematic(&mut schematic, &transform).unwrap();
+        let region = &schematic.regions[0];
+        assert_eq!((region.origin, region.size), (Vec3i::new(0, 5, 20), Vec3i::new(3, 2, 2)));
+        assert_eq!(region.block(2, 0, 1).property("facing"), Some("south"));
+    }
+}
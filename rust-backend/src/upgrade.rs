@@ -0,0 +1,544 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::{
+    BlockEntity, BlockState, ConversionOutput, ConversionWarning, ExportFormat, NbtCompound, NbtList, NbtTag,
+    NeptuniumError, Schematic, SchematicError, FLATTENED_DATA_VERSION, TAG_STRING,
+};
+
+// 按 DataVersion 升级方块状态：重命名、属性变更，并记录每一处替换
+
+/// 正式版与其 DataVersion，按版本升序
+const GAME_VERSIONS: &[(&str, i32)] = &[
+    ("1.12.2", 1343),
+    ("1.13", 1519),
+    ("1.13.2", 1631),
+    ("1.14", 1952),
+    ("1.14.4", 1976),
+    ("1.15", 2225),
+    ("1.15.2", 2230),
+    ("1.16", 2566),
+    ("1.16.5", 2586),
+    ("1.17", 2724),
+    ("1.17.1", 2730),
+    ("1.18", 2860),
+    ("1.18.2", 2975),
+    ("1.19", 3105),
+    ("1.19.2", 3120),
+    ("1.19.4", 3337),
+    ("1.20", 3463),
+    ("1.20.1", 3465),
+    ("1.20.2", 3578),
+    ("1.20.4", 3700),
+    ("1.20.6", 3839),
+    ("1.21", 3953),
+    ("1.21.1", 3955),
+    ("1.21.4", 4189),
+];
+
+/// 不指定目标版本时升级到的版本
+pub const LATEST_DATA_VERSION: i32 = 4189;
+
+/// DataVersion 对应的版本名；介于两个正式版之间时返回较早的那个加 "+"
+pub fn game_version_name(data_version: i32) -> Option<String> {
+    let (name, version) = GAME_VERSIONS.iter().rev().find(|(_, v)| *v <= data_version)?;
+    Some(if *version == data_version { name.to_string() } else { format!("{}+", name) })
+}
+
+/// 版本名（如 `1.20.1`）或数字形式的 DataVersion
+pub fn parse_data_version(input: &str) -> Option<i32> {
+    let input = input.trim();
+    GAME_VERSIONS
+        .iter()
+        .find(|(name, _)| *name == input)
+        .map(|(_, v)| *v)
+        .or_else(|| input.parse::<i32>().ok().filter(|v| *v > 0))
+}
+
+/// 单个升级步骤：在 data_version 引入的变更
+struct UpgradeStep {
+    data_version: i32,
+    fix: fn(&BlockState) -> Option<BlockState>,
+}
+
+const UPGRADE_STEPS: &[UpgradeStep] = &[
+    UpgradeStep { data_version: 1802, fix: fix_1_14_renames },
+    UpgradeStep { data_version: 2209, fix: fix_beehive },
+    UpgradeStep { data_version: 2503, fix: fix_wall_connections },
+    UpgradeStep { data_version: 2511, fix: fix_soul_fire_blocks },
+    UpgradeStep { data_version: 2531, fix: fix_redstone_wire },
+    UpgradeStep { data_version: 2679, fix: fix_cauldron },
+    UpgradeStep { data_version: 2680, fix: fix_dirt_path },
+    UpgradeStep { data_version: 3692, fix: fix_short_grass },
+];
+
+fn renamed(state: &BlockState, name: &str) -> BlockState {
+    BlockState { name: name.to_string(), properties: state.properties.clone() }
+}
+
+/// 1.14：告示牌按木材区分，原 stone_slab 成为 smooth_stone_slab
+fn fix_1_14_renames(state: &BlockState) -> Option<BlockState> {
+    match state.name.as_str() {
+        "minecraft:sign" => Some(renamed(state, "minecraft:oak_sign")),
+        "minecraft:wall_sign" => Some(renamed(state, "minecraft:oak_wall_sign")),
+        "minecraft:stone_slab" => Some(renamed(state, "minecraft:smooth_stone_slab")),
+        _ => None,
+    }
+}
+
+fn fix_beehive(state: &BlockState) -> Option<BlockState> {
+    (state.name == "minecraft:bee_hive").then(|| renamed(state, "minecraft:beehive"))
+}
+
+/// 1.16：墙的四个方向由 true/false 改为 none/low/tall
+fn fix_wall_connections(state: &BlockState) -> Option<BlockState> {
+    if !state.path().ends_with("_wall") {
+        return None;
+    }
+    let mut fixed = state.clone();
+    let mut changed = false;
+    for side in ["east", "north", "south", "west"] {
+        let value = match state.property(side) {
+            Some("true") => "low",
+            Some("false") => "none",
+            _ => continue,
+        };
+        fixed.properties.insert(side.to_string(), value.to_string());
+        changed = true;
+    }
+    changed.then_some(fixed)
+}
+
+/// 1.16 快照中的魂火火把与魂火灯笼在正式版前被移除，由魂火把与魂灯笼替代
+fn fix_soul_fire_blocks(state: &BlockState) -> Option<BlockState> {
+    match state.name.as_str() {
+        "minecraft:soul_fire_torch" => Some(renamed(state, "minecraft:soul_torch")),
+        "minecraft:soul_fire_wall_torch" => Some(renamed(state, "minecraft:soul_wall_torch")),
+        "minecraft:soul_fire_lantern" => Some(renamed(state, "minecraft:soul_lantern")),
+        _ => None,
+    }
+}
+
+/// 1.16：孤立的红石线改为十字形，单向连接的红石线补全为直线，保持原有外观与指向
+fn fix_redstone_wire(state: &BlockState) -> Option<BlockState> {
+    if state.name != "minecraft:redstone_wire" {
+        return None;
+    }
+    let none = |side: &str| state.property(side).is_none_or(|v| v == "none");
+    let x_connected = !none("east") || !none("west");
+    let z_connected = !none("north") || !none("south");
+    let mut fixed = state.clone();
+    for (side, cross_connected) in [("east", z_connected), ("west", z_connected), ("north", x_connected), ("south", x_connected)] {
+        if none(side) && !cross_connected {
+            fixed.properties.insert(side.to_string(), "side".to_string());
+        }
+    }
+    (fixed != *state).then_some(fixed)
+}
+
+/// 1.17：装水的炼药锅成为独立方块
+fn fix_cauldron(state: &BlockState) -> Option<BlockState> {
+    if state.name != "minecraft:cauldron" || state.properties.is_empty() {
+        return None;
+    }
+    match state.property("level") {
+        Some(level) if level != "0" => Some(BlockState::new("minecraft:water_cauldron").with_property("level", level)),
+        _ => Some(BlockState::new("minecraft:cauldron")),
+    }
+}
+
+fn fix_dirt_path(state: &BlockState) -> Option<BlockState> {
+    (state.name == "minecraft:grass_path").then(|| renamed(state, "minecraft:dirt_path"))
+}
+
+/// 1.20.3：草更名为 short_grass
+fn fix_short_grass(state: &BlockState) -> Option<BlockState> {
+    (state.name == "minecraft:grass").then(|| renamed(state, "minecraft:short_grass"))
+}
+
+/// 方块实体数据的升级步骤，返回是否做了修改
+struct BlockEntityStep {
+    data_version: i32,
+    /// 报告中的警告代码与说明
+    code: &'static str,
+    description: &'static str,
+    fix: fn(&mut BlockEntity) -> bool,
+}
+
+const BLOCK_ENTITY_STEPS: &[BlockEntityStep] = &[BlockEntityStep {
+    data_version: 3463,
+    code: "sign_text_migrated",
+    description: "signs were migrated to the 1.20 front and back text format",
+    fix: fix_sign_text,
+}];
+
+/// 1.20：告示牌的 Text1..Text4、Color、GlowingText 移入 front_text，新增空白的 back_text
+fn fix_sign_text(block_entity: &mut BlockEntity) -> bool {
+    if !matches!(block_entity.id.as_str(), "minecraft:sign" | "minecraft:hanging_sign")
+        || block_entity.nbt.contains_key("front_text")
+    {
+        return false;
+    }
+    let nbt = &mut block_entity.nbt;
+    let lines = ["Text1", "Text2", "Text3", "Text4"];
+    if !lines.iter().any(|key| nbt.contains_key(key)) {
+        return false;
+    }
+    let messages = lines.map(|key| match nbt.remove(key) {
+        Some(NbtTag::String(text)) => NbtTag::String(text),
+        _ => NbtTag::String("\"\"".to_string()),
+    });
+    let color = match nbt.remove("Color") {
+        Some(NbtTag::String(color)) => color,
+        _ => "black".to_string(),
+    };
+    let glowing = nbt.remove("GlowingText").and_then(|tag| tag.as_i64()).unwrap_or(0) != 0;
+    let text = |messages: [NbtTag; 4], color: String, glowing: bool| {
+        let mut text = NbtCompound::new();
+        text.insert("messages", NbtTag::List(NbtList { element_type: TAG_STRING, values: messages.to_vec() }));
+        text.insert("color", NbtTag::String(color));
+        text.insert("has_glowing_text", NbtTag::Byte(i8::from(glowing)));
+        NbtTag::Compound(text)
+    };
+    let blank = std::array::from_fn(|_| NbtTag::String("\"\"".to_string()));
+    nbt.insert("front_text", text(messages, color, glowing));
+    nbt.insert("back_text", text(blank, "black".to_string(), false));
+    nbt.insert("is_waxed", NbtTag::Byte(0));
+    true
+}
+
+/// 对单个方块状态依次应用 (from, to] 区间内的升级步骤
+pub fn upgrade_block_state(state: &BlockState, from: i32, to: i32) -> BlockState {
+    let mut current = state.clone();
+    for step in UPGRADE_STEPS.iter().filter(|s| s.data_version > from && s.data_version <= to) {
+        if let Some(fixed) = (step.fix)(&current) {
+            current = fixed;
+        }
+    }
+    current
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Substitution {
+    pub from: String,
+    pub to: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpgradeReport {
+    pub from_data_version: i32,
+    pub to_data_version: i32,
+    pub from_version: Option<String>,
+    pub to_version: Option<String>,
+    /// 源文件未记录 DataVersion，按 1.13 处理
+    pub assumed_source_version: bool,
+    pub substitutions: Vec<Substitution>,
+    /// 方块实体的迁移，以及方块被替换但数据未经迁移的方块实体
+    #[serde(default)]
+    pub warnings: Vec<ConversionWarning>,
+}
+
+impl UpgradeReport {
+    pub fn total_replaced(&self) -> u64 {
+        self.substitutions.iter().map(|s| s.count).sum()
+    }
+}
+
+/// 将投影的调色板升级到目标 DataVersion，并更新投影的 DataVersion
+pub fn upgrade_schematic(schematic: &mut Schematic, target: i32) -> Result<UpgradeReport, SchematicError> {
+    let assumed = schematic.data_version <= 0;
+    let from = if assumed { FLATTENED_DATA_VERSION } else { schematic.data_version };
+    if from < FLATTENED_DATA_VERSION {
+        return Err(SchematicError::Invalid(format!(
+            "DataVersion {} predates the 1.13 flattening and cannot be upgraded",
+            from
+        )));
+    }
+    if target < from {
+        return Err(SchematicError::Invalid(format!(
+            "Cannot downgrade from DataVersion {} to {}",
+            from, target
+        )));
+    }
+
+    let block_entity_steps: Vec<&BlockEntityStep> =
+        BLOCK_ENTITY_STEPS.iter().filter(|s| s.data_version > from && s.data_version <= target).collect();
+    let mut substitutions: BTreeMap<(String, String), u64> = BTreeMap::new();
+    let mut migrated = vec![0u64; block_entity_steps.len()];
+    // 方块被替换、数据却没有任何步骤处理的方块实体，按 id 计数
+    let mut unmigrated: BTreeMap<String, u64> = BTreeMap::new();
+    for region in schematic.regions.iter_mut() {
+        let counts = region.palette_counts();
+        let mut changed = vec![false; region.palette.len()];
+        for ((state, count), changed) in region.palette.iter_mut().zip(counts).zip(changed.iter_mut()) {
+            let upgraded = upgrade_block_state(state, from, target);
+            if upgraded != *state {
+                if count > 0 {
+                    *substitutions.entry((state.to_string(), upgraded.to_string())).or_default() += count;
+                }
+                *state = upgraded;
+                *changed = true;
+            }
+        }
+
+        let mut block_entities = std::mem::take(&mut region.block_entities);
+        for block_entity in block_entities.iter_mut() {
+            let pos = block_entity.pos;
+            let substituted = region.contains(pos.x, pos.y, pos.z)
+                && changed[region.blocks[region.index(pos.x, pos.y, pos.z)] as usize];
+            let mut fixed = false;
+            for (step, count) in block_entity_steps.iter().zip(migrated.iter_mut()) {
+                if (step.fix)(block_entity) {
+                    *count += 1;
+                    fixed = true;
+                }
+            }
+            if substituted && !fixed {
+                *unmigrated.entry(block_entity.id.clone()).or_default() += 1;
+            }
+        }
+        region.block_entities = block_entities;
+        region.compact_palette();
+    }
+    schematic.data_version = target;
+
+    let mut warnings: Vec<ConversionWarning> = block_entity_steps
+        .iter()
+        .zip(migrated)
+        .filter(|(_, count)| *count > 0)
+        .map(|(step, count)| ConversionWarning::new(step.code, format!("{} {}", count, step.description)))
+        .collect();
+    for (id, count) in unmigrated {
+        warnings.push(ConversionWarning::new(
+            "block_entity_not_migrated",
+            format!(
+                "{} {} block entities on upgraded blocks were kept unchanged and may need updating in-game",
+                count, id
+            ),
+        ));
+    }
+
+    let mut substitutions: Vec<Substitution> = substitutions
+        .into_iter()
+        .map(|((from, to), count)| Substitution { from, to, count })
+        .collect();
+    substitutions.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.from.cmp(&b.from)));
+    Ok(UpgradeReport {
+        from_data_version: from,
+        to_data_version: target,
+        from_version: game_version_name(from),
+        to_version: game_version_name(target),
+        assumed_source_version: assumed,
+        substitutions,
+        warnings,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[wasm_bindgen]
+pub struct UpgradeOutput {
+    output: ConversionOutput,
+    report: UpgradeReport,
+}
+
+#[wasm_bindgen]
+impl UpgradeOutput {
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.output.data()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn format(&self) -> String {
+        self.output.format()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn extension(&self) -> String {
+        self.output.extension()
+    }
+
+    /// 写出的文件与转换警告
+    #[wasm_bindgen(getter)]
+    pub fn output(&self) -> ConversionOutput {
+        self.output.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn report_json(&self) -> String {
+        serde_json::to_string(&self.report).unwrap_or_default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn warnings_json(&self) -> String {
+        self.output.warnings_json()
+    }
+}
+
+impl UpgradeOutput {
+    pub fn report(&self) -> &UpgradeReport {
+        &self.report
+    }
+
+    pub fn warnings(&self) -> &[ConversionWarning] {
+        self.output.warnings()
+    }
+}
+
+fn target_data_version(target_version: &str) -> Result<i32, NeptuniumError> {
+    if target_version.trim().is_empty() {
+        return Ok(LATEST_DATA_VERSION);
+    }
+    parse_data_version(target_version).ok_or_else(|| {
+        NeptuniumError::new(format!("Unknown game version: {}", target_version), "UNKNOWN_VERSION".to_string())
+    })
+}
+
+/// 升级投影并写出为目标格式；target_version 为版本名或 DataVersion，留空表示最新版本
+#[wasm_bindgen]
+pub fn upgrade_schematic_file(data: &[u8], target_version: &str, target_format: &str) -> Result<UpgradeOutput, NeptuniumError> {
+    let target = ExportFormat::from_name(target_format).ok_or_else(|| {
+        NeptuniumError::new(format!("Unsupported target format: {}", target_format), "UNSUPPORTED_FORMAT".to_string())
+    })?;
+    let data_version = target_data_version(target_version)?;
+    let mut schematic = Schematic::from_bytes(data)?;
+    let report = upgrade_schematic(&mut schematic, data_version)?;
+    let (bytes, written) = crate::write_schematic(&schematic, target)?;
+    let warnings = report.warnings.iter().cloned().chain(written).collect();
+    Ok(UpgradeOutput { output: ConversionOutput::new(bytes, target, warnings), report })
+}
+
+/// 只生成升级报告（JSON），不写出文件
+#[wasm_bindgen]
+pub fn preview_schematic_upgrade(data: &[u8], target_version: &str) -> Result<String, NeptuniumError> {
+    let data_version = target_data_version(target_version)?;
+    let mut schematic = Schematic::from_bytes(data)?;
+    let report = upgrade_schematic(&mut schematic, data_version)?;
+    Ok(serde_json::to_string(&report).unwrap_or_default())
+}
+
+#[wasm_bindgen]
+pub fn data_version_to_game_version(data_version: i32) -> String {
+    game_version_name(data_version).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Region, SchematicFormat, Vec3i};
+
+    fn schematic_of(data_version: i32, blocks: &[BlockState]) -> Schematic {
+        let size = Vec3i::new(blocks.len() as i32, 1, 1);
+        let mut region = Region::new("Main", Vec3i::new(0, 0, 0), size).unwrap();
+        for (x, state) in blocks.iter().enumerate() {
+            region.set_block(x as i32, 0, 0, state);
+        }
+        let mut schematic = Schematic::new("Old", data_version, SchematicFormat::Litematica);
+        schematic.regions.push(region);
+        schematic
+    }
+
+    fn sign_entity(x: i32) -> BlockEntity {
+        let mut nbt = NbtCompound::new();
+        for (i, line) in ["{\"text\":\"Hello\"}", "\"\"", "\"\"", "{\"text\":\"World\"}"].iter().enumerate() {
+            nbt.insert(format!("Text{}", i + 1), NbtTag::String(line.to_string()));
+        }
+        nbt.insert("Color", NbtTag::String("red".to_string()));
+        BlockEntity { pos: Vec3i::new(x, 0, 0), id: "minecraft:sign".to_string(), nbt }
+    }
+
+    #[test]
+    fn version_names_round_trip() {
+        assert_eq!(parse_data_version("1.20.1"), Some(3465));
+        assert_eq!(parse_data_version("3000"), Some(3000));
+        assert_eq!(parse_data_version("1.99"), None);
+        assert_eq!(game_version_name(3465).as_deref(), Some("1.20.1"));
+        assert_eq!(game_version_name(3466).as_deref(), Some("1.20.1+"));
+        assert_eq!(game_version_name(100), None);
+    }
+
+    #[test]
+    fn steps_apply_only_within_the_version_range() {
+        let wall = BlockState::new("minecraft:cobblestone_wall").with_property("north", "true");
+        assert_eq!(upgrade_block_state(&wall, 2586, LATEST_DATA_VERSION), wall);
+        let upgraded = upgrade_block_state(&wall, 1631, LATEST_DATA_VERSION);
+        assert_eq!(upgraded.property("north"), Some("low"));
+
+        let grass = BlockState::new("minecraft:grass");
+        assert_eq!(upgrade_block_state(&grass, 3465, 3578).name, "minecraft:grass");
+        assert_eq!(upgrade_block_state(&grass, 3465, 3700).name, "minecraft:short_grass");
+    }
+
+    #[test]
+    fn renamed_and_removed_blocks_are_reported_as_substitutions() {
+        let path = BlockState::new("minecraft:grass_path");
+        let lantern = BlockState::new("minecraft:soul_fire_lantern").with_property("hanging", "false");
+        let mut schematic = schematic_of(2504, &[path.clone(), path, lantern]);
+        let report = upgrade_schematic(&mut schematic, LATEST_DATA_VERSION).unwrap();
+
+        assert_eq!(schematic.data_version, LATEST_DATA_VERSION);
+        let region = &schematic.regions[0];
+        assert_eq!(region.block(0, 0, 0).name, "minecraft:dirt_path");
+        assert_eq!(region.block(2, 0, 0).name, "minecraft:soul_lantern");
+        let substitutions: Vec<_> =
+            report.substitutions.iter().map(|s| (s.from.as_str(), s.to.as_str(), s.count)).collect();
+        assert_eq!(
+            substitutions,
+            [
+                ("minecraft:grass_path", "minecraft:dirt_path", 2),
+                ("minecraft:soul_fire_lantern[hanging=false]", "minecraft:soul_lantern[hanging=false]", 1),
+            ]
+        );
+        assert_eq!(report.total_replaced(), 3);
+    }
+
+    #[test]
+    fn sign_text_moves_to_the_front_side() {
+        let mut schematic = schematic_of(1631, &[BlockState::new("minecraft:sign").with_property("rotation", "4")]);
+        schematic.regions[0].block_entities.push(sign_entity(0));
+        let report = upgrade_schematic(&mut schematic, LATEST_DATA_VERSION).unwrap();
+
+        let region = &schematic.regions[0];
+        assert_eq!(region.block(0, 0, 0).name, "minecraft:oak_sign");
+        let nbt = &region.block_entities[0].nbt;
+        assert!(!nbt.contains_key("Text1") && !nbt.contains_key("Color"));
+        let front = nbt.get_compound("front_text").unwrap();
+        let messages: Vec<_> = front.get_list("messages").unwrap().iter().filter_map(NbtTag::as_str).collect();
+        assert_eq!(messages, ["{\"text\":\"Hello\"}", "\"\"", "\"\"", "{\"text\":\"World\"}"]);
+        assert_eq!(front.get_str("color"), Some("red"));
+        assert_eq!(nbt.get_compound("back_text").and_then(|t| t.get_list("messages")).map(NbtList::len), Some(4));
+        let codes: Vec<_> = report.warnings.iter().map(|w| w.code.as_str()).collect();
+        assert_eq!(codes, ["sign_text_migrated"]);
+
+        // 只升级到 1.14 时方块更名而文字保持原格式，需提示
+        let mut schematic = schematic_of(1631, &[BlockState::new("minecraft:sign")]);
+        schematic.regions[0].block_entities.push(sign_entity(0));
+        let report = upgrade_schematic(&mut schematic, 1976).unwrap();
+        assert!(schematic.regions[0].block_entities[0].nbt.contains_key("Text1"));
+        let codes: Vec<_> = report.warnings.iter().map(|w| w.code.as_str()).collect();
+        assert_eq!(codes, ["block_entity_not_migrated"]);
+    }
+
+    #[test]
+    fn block_entities_on_substituted_blocks_without_a_migration_are_flagged() {
+        let mut schematic = schematic_of(2200, &[BlockState::new("minecraft:bee_hive")]);
+        schematic.regions[0].block_entities.push(BlockEntity {
+            pos: Vec3i::new(0, 0, 0),
+            id: "minecraft:beehive".to_string(),
+            nbt: NbtCompound::new(),
+        });
+        let report = upgrade_schematic(&mut schematic, 2230).unwrap();
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].code, "block_entity_not_migrated");
+        assert!(report.warnings[0].message.starts_with("1 minecraft:beehive"));
+    }
+
+    #[test]
+    fn downgrades_and_pre_flattening_sources_are_rejected() {
+        let mut schematic = schematic_of(3700, &[BlockState::new("minecraft:stone")]);
+        assert!(upgrade_schematic(&mut schematic, 3465).is_err());
+        let mut schematic = schematic_of(1343, &[BlockState::new("minecraft:stone")]);
+        assert!(upgrade_schematic(&mut schematic, 3465).is_err());
+    }
+}
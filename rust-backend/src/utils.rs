@@ -64,7 +64,7 @@ pub fn validate_password(password: &str) -> ValidationResult {
     
     let has_uppercase = password.chars().any(|c| c.is_uppercase());
     let has_lowercase = password.chars().any(|c| c.is_lowercase());
-    let has_digit = password.chars().any(|c| c.is_digit(10));
+    let has_digit = password.chars().any(|c| c.is_ascii_digit());
     
     if !has_uppercase {
         return ValidationResult::new(false, "Password must contain at least one uppercase letter".to_string());
@@ -88,7 +88,8 @@ pub fn validate_file_type(filename: &str) -> ValidationResult {
         ".schem", 
         ".schematic",
         ".nbt",
-        ".structure"
+        ".structure",
+        ".mcstructure"
     ];
     
     let filename_lower = filename.to_lowercase();
@@ -101,7 +102,7 @@ pub fn validate_file_type(filename: &str) -> ValidationResult {
     
     ValidationResult::new(
         false, 
-        "Invalid file type. Allowed: .litematic, .schem, .schematic, .nbt, .structure".to_string()
+        "Invalid file type. Allowed: .litematic, .schem, .schematic, .nbt, .structure, .mcstructure".to_string()
     )
 }
 
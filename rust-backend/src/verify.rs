@@ -0,0 +1,513 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use chrono::Utc;
+use crate::{
+    extract_anvil_region, is_empty_block, parse_region_file_name, upgrade_block_state, AnvilRegion, BlockState,
+    ConversionWarning, CropBox, IngestLimits, MaterialList, MaterialMode, NeptuniumError, ProjectionFile, Region,
+    Schematic, SchematicError, Vec3i, REGION_CHUNKS, STRUCTURE_VOID,
+};
+
+// 建造进度核对：把投影放到世界中的给定位置，与上传的存档区域文件逐格比较
+
+/// 默认最多列出的不符位置，计数不受此限制
+pub const DEFAULT_MAX_LISTED_MISMATCHES: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VerifyOptions {
+    /// 比较时忽略的方块属性，如 `waterlogged`、`powered`
+    pub ignore_properties: Vec<String>,
+    /// 统计投影中为空气、世界中却有方块的位置
+    pub report_extra: bool,
+    pub max_listed_mismatches: usize,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        VerifyOptions {
+            ignore_properties: Vec::new(),
+            report_extra: true,
+            max_listed_mismatches: DEFAULT_MAX_LISTED_MISMATCHES,
+        }
+    }
+}
+
+impl VerifyOptions {
+    pub fn from_json(json: &str) -> Result<VerifyOptions, NeptuniumError> {
+        if json.trim().is_empty() {
+            return Ok(VerifyOptions::default());
+        }
+        serde_json::from_str(json).map_err(|e| {
+            NeptuniumError::new(format!("Invalid verify options: {}", e), "INVALID_VERIFY_OPTIONS".to_string())
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchKind {
+    /// 应有方块的位置是空气
+    Missing,
+    WrongBlock,
+    /// 方块正确但属性不同，如朝向
+    WrongState,
+    /// 投影中为空气的位置放了方块
+    Extra,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildMismatch {
+    /// 世界坐标
+    pub pos: Vec3i,
+    pub kind: MismatchKind,
+    pub expected: Option<String>,
+    pub found: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerProgress {
+    /// 世界坐标中的层高
+    pub y: i32,
+    /// 该层已核对的非空气方块数
+    pub expected: u64,
+    pub correct: u64,
+    pub percent_complete: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildProgress {
+    /// 对应的投影文件 id，由 `BuildVerifier::finish_for_projection` 填入
+    pub file_id: Option<String>,
+    pub checked_at: String,
+    /// 投影原点在世界中的位置
+    pub origin: Vec3i,
+    pub data_version: i32,
+    /// 已核对的非空气方块数，不含未上传区域文件覆盖的位置
+    pub expected: u64,
+    pub correct: u64,
+    pub missing: u64,
+    pub wrong_block: u64,
+    pub wrong_state: u64,
+    pub extra: u64,
+    /// 不在任何已上传区域文件内的非空气方块数
+    pub unchecked: u64,
+    pub percent_complete: f64,
+    /// 按层高升序
+    pub layers: Vec<LayerProgress>,
+    /// 按 y → z → x 排序，超过上限时截断
+    pub mismatches: Vec<BuildMismatch>,
+    pub truncated: bool,
+    /// 缺失与放错的方块还需要的物品；属性不符的方块只需调整，不计入
+    pub remaining_materials: MaterialList,
+    pub warnings: Vec<ConversionWarning>,
+}
+
+/// 世界中一块长方体的方块快照，来自单个区域文件
+#[derive(Debug, Clone)]
+pub struct WorldSnapshot {
+    /// 世界坐标
+    pub bounds: CropBox,
+    pub region: Region,
+    pub data_version: i32,
+}
+
+impl WorldSnapshot {
+    /// 读取区域文件与 bounds 相交的部分；不相交时返回 None，相交部分的区块均未生成时视为全部空气
+    pub fn from_anvil(
+        anvil: &AnvilRegion,
+        bounds: &CropBox,
+        limits: &IngestLimits,
+        warnings: &mut Vec<ConversionWarning>,
+    ) -> Result<Option<WorldSnapshot>, SchematicError> {
+        let span = REGION_CHUNKS * 16;
+        let area = CropBox::new(
+            Vec3i::new(anvil.x * span, bounds.min.y, anvil.z * span),
+            Vec3i::new(span, bounds.size.y, span),
+        );
+        let Some(inside) = area.intersect(bounds)? else {
+            return Ok(None);
+        };
+        let generated = anvil.chunks().into_iter().any(|(x, z)| {
+            let (block_x, block_z) = ((anvil.x * REGION_CHUNKS + x) * 16, (anvil.z * REGION_CHUNKS + z) * 16);
+            block_x < inside.min.x + inside.size.x
+                && block_x + 16 > inside.min.x
+                && block_z < inside.min.z + inside.size.z
+                && block_z + 16 > inside.min.z
+        });
+        if !generated {
+            return Ok(Some(WorldSnapshot {
+                bounds: inside,
+                region: Region::new("Main", Vec3i::default(), inside.size)?,
+                data_version: 0,
+            }));
+        }
+        let extracted = extract_anvil_region(anvil, None, &inside, limits, warnings)?;
+        let data_version = extracted.data_version;
+        let region = extracted
+            .regions
+            .into_iter()
+            .next()
+            .ok_or_else(|| SchematicError::Invalid("Empty world snapshot".to_string()))?;
+        Ok(Some(WorldSnapshot { bounds: inside, region, data_version }))
+    }
+
+    /// 世界坐标处的调色板索引，不在快照范围内时返回 None
+    fn palette_index(&self, pos: Vec3i) -> Option<u32> {
+        let (x, y, z) = (pos.x - self.bounds.min.x, pos.y - self.bounds.min.y, pos.z - self.bounds.min.z);
+        self.region.contains(x, y, z).then(|| self.region.blocks[self.region.index(x, y, z)])
+    }
+}
+
+/// 投影放置后在世界中占据的长方体；超出世界坐标范围时报错
+pub fn placement_bounds(schematic: &Schematic, origin: Vec3i) -> Result<CropBox, SchematicError> {
+    let (min, size) = schematic.bounds()?;
+    let out_of_range = || SchematicError::Invalid("Placed schematic extends beyond the coordinate range".to_string());
+    let corner = |origin: i32, min: i32, size: i32| {
+        origin.checked_add(min).filter(|start| start.checked_add(size).is_some()).ok_or_else(out_of_range)
+    };
+    let start = Vec3i::new(
+        corner(origin.x, min.x, size.x)?,
+        corner(origin.y, min.y, size.y)?,
+        corner(origin.z, min.z, size.z)?,
+    );
+    Ok(CropBox::new(start, size))
+}
+
+fn percent(correct: u64, expected: u64) -> f64 {
+    if expected == 0 {
+        return 100.0;
+    }
+    (correct as f64 * 10_000.0 / expected as f64).round() / 100.0
+}
+
+/// 逐格比较投影与世界快照；结构空位不参与比较，两边的方块状态先升级到较新的 DataVersion
+pub fn verify_build(
+    schematic: &Schematic,
+    origin: Vec3i,
+    snapshots: &[WorldSnapshot],
+    options: &VerifyOptions,
+) -> Result<BuildProgress, SchematicError> {
+    // 确认放置后的世界坐标不会溢出
+    placement_bounds(schematic, origin)?;
+    let region = match schematic.regions.len() {
+        0 => return Err(SchematicError::Invalid("Schematic has no regions".to_string())),
+        1 => Cow::Borrowed(&schematic.regions[0]),
+        _ => Cow::Owned(schematic.flatten(&BlockState::new(STRUCTURE_VOID))?.0),
+    };
+    let data_version = snapshots.iter().map(|s| s.data_version).chain([schematic.data_version]).max().unwrap_or(0);
+    let normalize = |state: &BlockState, from: i32| {
+        let mut state = if from > 0 { upgrade_block_state(state, from, data_version) } else { state.clone() };
+        for key in &options.ignore_properties {
+            state.properties.remove(key);
+        }
+        state
+    };
+    let expected_palette: Vec<BlockState> =
+        region.palette.iter().map(|s| normalize(s, schematic.data_version)).collect();
+    let world_palettes: Vec<Vec<BlockState>> =
+        snapshots.iter().map(|s| s.region.palette.iter().map(|p| normalize(p, s.data_version)).collect()).collect();
+
+    let mut progress = BuildProgress {
+        file_id: None,
+        checked_at: Utc::now().to_rfc3339(),
+        origin,
+        data_version,
+        expected: 0,
+        correct: 0,
+        missing: 0,
+        wrong_block: 0,
+        wrong_state: 0,
+        extra: 0,
+        unchecked: 0,
+        percent_complete: 0.0,
+        layers: Vec::new(),
+        mismatches: Vec::new(),
+        truncated: false,
+        remaining_materials: MaterialList::from_counts(HashMap::new(), MaterialMode::Items),
+        warnings: Vec::new(),
+    };
+    let mut layers: Vec<(u64, u64)> = vec![(0, 0); region.size.y as usize];
+    let mut remaining: HashMap<BlockState, u64> = HashMap::new();
+
+    // 按 y → z → x 遍历，不符位置自然有序
+    for index in 0..region.volume() {
+        let expected = &expected_palette[region.blocks[index] as usize];
+        if expected.name == STRUCTURE_VOID {
+            continue;
+        }
+        let local = region.position(index);
+        let pos = Vec3i::new(
+            origin.x + region.origin.x + local.x,
+            origin.y + region.origin.y + local.y,
+            origin.z + region.origin.z + local.z,
+        );
+        let found = snapshots
+            .iter()
+            .zip(&world_palettes)
+            .find_map(|(snapshot, palette)| snapshot.palette_index(pos).map(|i| &palette[i as usize]));
+        let expects_block = !expected.is_air();
+        let Some(found) = found else {
+            if expects_block {
+                progress.unchecked += 1;
+            }
+            continue;
+        };
+
+        let kind = if !expects_block {
+            match is_empty_block(found) {
+                false if options.report_extra => Some(MismatchKind::Extra),
+                _ => None,
+            }
+        } else if is_empty_block(found) {
+            Some(MismatchKind::Missing)
+        } else if found.name != expected.name {
+            Some(MismatchKind::WrongBlock)
+        } else if found.properties != expected.properties {
+            Some(MismatchKind::WrongState)
+        } else {
+            None
+        };
+
+        if expects_block {
+            let layer = &mut layers[local.y as usize];
+            layer.0 += 1;
+            progress.expected += 1;
+            if kind.is_none() {
+                layer.1 += 1;
+                progress.correct += 1;
+            }
+        }
+        let Some(kind) = kind else { continue };
+        match kind {
+            MismatchKind::Missing => progress.missing += 1,
+            MismatchKind::WrongBlock => progress.wrong_block += 1,
+            MismatchKind::WrongState => progress.wrong_state += 1,
+            MismatchKind::Extra => progress.extra += 1,
+        }
+        if matches!(kind, MismatchKind::Missing | MismatchKind::WrongBlock) {
+            *remaining.entry(region.palette[region.blocks[index] as usize].clone()).or_default() += 1;
+        }
+        if progress.mismatches.len() < options.max_listed_mismatches {
+            progress.mismatches.push(BuildMismatch {
+                pos,
+                kind,
+                expected: expects_block.then(|| expected.to_string()),
+                found: (!is_empty_block(found)).then(|| found.to_string()),
+            });
+        } else {
+            progress.truncated = true;
+        }
+    }
+
+    progress.percent_complete = percent(progress.correct, progress.expected);
+    progress.layers = layers
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (expected, _))| *expected > 0)
+        .map(|(y, (expected, correct))| LayerProgress {
+            y: origin.y + region.origin.y + y as i32,
+            expected,
+            correct,
+            percent_complete: percent(correct, expected),
+        })
+        .collect();
+    progress.remaining_materials = MaterialList::from_state_counts(remaining, MaterialMode::Items);
+    if progress.unchecked > 0 {
+        progress.warnings.push(ConversionWarning::new(
+            "positions_unchecked",
+            format!("{} blocks lie outside the uploaded region files and were not checked", progress.unchecked),
+        ));
+    }
+    Ok(progress)
+}
+
+/// 供 JS 端逐个加入区域文件（投影可能跨越多个区域），最后生成进度报告
+#[wasm_bindgen]
+pub struct BuildVerifier {
+    schematic: Schematic,
+    origin: Vec3i,
+    /// 放置后占据的世界坐标长方体，构造时校验
+    placement: CropBox,
+    options: VerifyOptions,
+    snapshots: Vec<WorldSnapshot>,
+    regions: Vec<(i32, i32)>,
+    warnings: Vec<ConversionWarning>,
+}
+
+#[wasm_bindgen]
+impl BuildVerifier {
+    /// (x, y, z) 为投影原点在世界中的位置；options_json 见 `VerifyOptions`，留空使用默认值
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8], x: i32, y: i32, z: i32, options_json: &str) -> Result<BuildVerifier, NeptuniumError> {
+        let schematic = Schematic::from_bytes(data)?;
+        let origin = Vec3i::new(x, y, z);
+        Ok(BuildVerifier {
+            placement: placement_bounds(&schematic, origin)?,
+            schematic,
+            origin,
+            options: VerifyOptions::from_json(options_json)?,
+            snapshots: Vec::new(),
+            regions: Vec::new(),
+            warnings: Vec::new(),
+        })
+    }
+
+    /// 加入一个区域文件；filename 形如 `r.0.-1.mca`，无法解析时由区块数据推断坐标
+    pub fn add_region(&mut self, data: &[u8], filename: &str) -> Result<(), NeptuniumError> {
+        let limits = IngestLimits::current();
+        let anvil = AnvilRegion::parse(data, parse_region_file_name(filename), &limits)?;
+        if self.regions.contains(&(anvil.x, anvil.z)) {
+            return Err(NeptuniumError::new(
+                format!("Region r.{}.{}.mca was already added", anvil.x, anvil.z),
+                "DUPLICATE_REGION".to_string(),
+            ));
+        }
+        match WorldSnapshot::from_anvil(&anvil, &self.placement, &limits, &mut self.warnings)? {
+            Some(snapshot) => {
+                self.regions.push((anvil.x, anvil.z));
+                self.snapshots.push(snapshot);
+                Ok(())
+            }
+            None => Err(NeptuniumError::new(
+                format!("Region r.{}.{}.mca does not overlap the placed schematic", anvil.x, anvil.z),
+                "REGION_NOT_OVERLAPPING".to_string(),
+            )),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn region_count(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// 投影放置后占据的世界坐标长方体（JSON），用于提示需要上传哪些区域文件
+    #[wasm_bindgen(getter)]
+    pub fn placement_json(&self) -> String {
+        serde_json::to_string(&self.placement).unwrap_or_default()
+    }
+
+    /// 进度报告（JSON）
+    pub fn finish(&self) -> Result<String, NeptuniumError> {
+        Ok(serde_json::to_string(&self.report(None)?).unwrap_or_default())
+    }
+
+    /// 带投影文件 id 的进度报告（JSON），可按 `ProjectionFile::progress_path` 存储以追踪历史进度
+    pub fn finish_for_projection(&self, file: &ProjectionFile) -> Result<String, NeptuniumError> {
+        Ok(serde_json::to_string(&self.report(Some(file.file_id()))?).unwrap_or_default())
+    }
+}
+
+impl BuildVerifier {
+    fn report(&self, file_id: Option<String>) -> Result<BuildProgress, NeptuniumError> {
+        let mut progress = verify_build(&self.schematic, self.origin, &self.snapshots, &self.options)?;
+        progress.file_id = file_id;
+        let mut warnings = self.warnings.clone();
+        warnings.append(&mut progress.warnings);
+        progress.warnings = warnings;
+        Ok(progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SchematicFormat;
+
+    fn stairs(facing: &str) -> BlockState {
+        BlockState::new("minecraft:oak_stairs").with_property("facing", facing)
+    }
+
+    /// 3×2×1：底层石头和朝东的楼梯，上层右侧一块玻璃，其余为空气
+    fn plan() -> Schematic {
+        let mut region = Region::new("Main", Vec3i::new(0, 0, 0), Vec3i::new(3, 2, 1)).unwrap();
+        region.set_block(0, 0, 0, &BlockState::new("minecraft:stone"));
+        region.set_block(1, 0, 0, &stairs("east"));
+        region.set_block(2, 1, 0, &BlockState::new("minecraft:glass"));
+        let mut schematic = Schematic::new("Plan", 3700, SchematicFormat::Litematica);
+        schematic.regions.push(region);
+        schematic
+    }
+
+    /// 世界中 (100, 64, 200) 起的快照：石头正确、楼梯朝西、缺玻璃、上层左侧多了泥土
+    fn world(bounds: CropBox) -> WorldSnapshot {
+        let mut region = Region::new("Main", Vec3i::default(), bounds.size).unwrap();
+        let mut place = |x: i32, y: i32, state: &BlockState| {
+            let local = Vec3i::new(x - bounds.min.x, y - bounds.min.y, 200 - bounds.min.z);
+            if region.contains(local.x, local.y, local.z) {
+                region.set_block(local.x, local.y, local.z, state);
+            }
+        };
+        place(100, 64, &BlockState::new("minecraft:stone"));
+        place(101, 64, &stairs("west"));
+        place(100, 65, &BlockState::new("minecraft:dirt"));
+        WorldSnapshot { bounds, region, data_version: 3700 }
+    }
+
+    fn full_world() -> WorldSnapshot {
+        world(CropBox::new(Vec3i::new(100, 64, 200), Vec3i::new(3, 2, 1)))
+    }
+
+    const ORIGIN: Vec3i = Vec3i { x: 100, y: 64, z: 200 };
+
+    #[test]
+    fn every_kind_of_mismatch_is_counted_in_world_coordinates() {
+        let progress = verify_build(&plan(), ORIGIN, &[full_world()], &VerifyOptions::default()).unwrap();
+        assert_eq!((progress.expected, progress.correct), (3, 1));
+        assert_eq!((progress.missing, progress.wrong_block, progress.wrong_state, progress.extra), (1, 0, 1, 1));
+        assert_eq!(progress.percent_complete, 33.33);
+        let mismatches: Vec<_> = progress.mismatches.iter().map(|m| (m.pos, m.kind)).collect();
+        assert_eq!(
+            mismatches,
+            [
+                (Vec3i::new(101, 64, 200), MismatchKind::WrongState),
+                (Vec3i::new(100, 65, 200), MismatchKind::Extra),
+                (Vec3i::new(102, 65, 200), MismatchKind::Missing),
+            ]
+        );
+        let layers: Vec<_> = progress.layers.iter().map(|l| (l.y, l.expected, l.correct)).collect();
+        assert_eq!(layers, [(64, 2, 1), (65, 1, 0)]);
+        assert_eq!(progress.remaining_materials.count_of("minecraft:glass"), 1);
+        assert!(progress.warnings.is_empty());
+    }
+
+    #[test]
+    fn options_ignore_properties_extras_and_cap_the_listing() {
+        let options = VerifyOptions {
+            ignore_properties: vec!["facing".to_string()],
+            report_extra: false,
+            max_listed_mismatches: 0,
+        };
+        let progress = verify_build(&plan(), ORIGIN, &[full_world()], &options).unwrap();
+        assert_eq!((progress.correct, progress.wrong_state, progress.extra, progress.missing), (2, 0, 0, 1));
+        assert!(progress.mismatches.is_empty() && progress.truncated);
+    }
+
+    #[test]
+    fn positions_outside_the_snapshots_are_unchecked() {
+        let partial = world(CropBox::new(Vec3i::new(100, 64, 200), Vec3i::new(2, 2, 1)));
+        let progress = verify_build(&plan(), ORIGIN, &[partial], &VerifyOptions::default()).unwrap();
+        assert_eq!((progress.expected, progress.unchecked), (2, 1));
+        assert_eq!(progress.warnings[0].code, "positions_unchecked");
+    }
+
+    #[test]
+    fn older_plans_are_compared_after_upgrading() {
+        let mut schematic = plan();
+        schematic.data_version = 3465;
+        schematic.regions[0].set_block(2, 1, 0, &BlockState::new("minecraft:grass"));
+        let mut snapshot = full_world();
+        snapshot.region.set_block(2, 1, 0, &BlockState::new("minecraft:short_grass"));
+        let progress = verify_build(&schematic, ORIGIN, &[snapshot], &VerifyOptions::default()).unwrap();
+        assert_eq!((progress.data_version, progress.missing, progress.wrong_block), (3700, 0, 0));
+    }
+
+    #[test]
+    fn placements_beyond_the_coordinate_range_are_rejected() {
+        assert_eq!(placement_bounds(&plan(), ORIGIN).unwrap(), CropBox::new(ORIGIN, Vec3i::new(3, 2, 1)));
+        let far = Vec3i::new(i32::MAX - 1, 0, 0);
+        assert!(placement_bounds(&plan(), far).is_err());
+        assert!(verify_build(&plan(), far, &[], &VerifyOptions::default()).is_err());
+    }
+}